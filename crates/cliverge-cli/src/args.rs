@@ -0,0 +1,340 @@
+//! Command-line argument parsing for the headless frontend
+
+// Hand-rolled instead of pulling in an argument parsing crate to keep the binary small
+
+// 类型别名以减少复杂度警告
+type StringVec = Vec<String>;
+
+pub const USAGE: &str = "\
+CLIverge - Universal CLI Tool Manager (headless)
+
+USAGE:
+    cliverge-cli [OPTIONS] <COMMAND> [ARGS]
+
+COMMANDS:
+    list                 List all tools in the catalog
+    status [ID]...       Check installation status (all tools if no ID is given)
+    install <ID>[@VER]...
+                         Install one or more tools, optionally at an exact version
+    install --locked [ID]...
+                         Install the exact versions recorded in cliverge.lock
+    uninstall <ID>...    Uninstall one or more tools
+    update <ID>...       Update one or more tools to the latest version
+    rollback <ID>...     Reinstall the version that was installed before the last update
+    outdated             List installed tools with available updates
+    help <ID>            Show the help output of a tool
+    sync                 Install or update the tools listed in .cliverge.toml
+    lock                 Record installed versions in cliverge.lock
+
+OPTIONS:
+    --json               Print machine-readable JSON instead of text
+    --check              (sync) Only report what is out of sync, change nothing
+    --manifest <PATH>    (sync, lock) Use this manifest instead of searching for .cliverge.toml
+    --locked             (install) Install from cliverge.lock
+    --with-deps          (install) Install missing prerequisite tools first
+    -n, --dry-run        (install, uninstall, update, rollback) Print the commands that
+                         would run, including fallbacks, without running them
+    -y, --yes            Run install scripts without asking for confirmation
+    -h, --help           Print this help message
+    -V, --version        Print version information
+
+EXIT CODES:
+    0    Success
+    1    Operation failed
+    2    Invalid usage
+    3    Tool not found
+    4    Operation not supported on this platform
+    5    Updates available (outdated only)
+    6    Project out of sync (sync --check only)
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    List,
+    Status {
+        ids: Vec<String>,
+    },
+    Install {
+        ids: Vec<String>,
+        with_deps: bool,
+    },
+    Uninstall {
+        ids: Vec<String>,
+    },
+    Update {
+        ids: Vec<String>,
+    },
+    Rollback {
+        ids: Vec<String>,
+    },
+    Outdated,
+    Help {
+        id: String,
+    },
+    InstallLocked {
+        ids: Vec<String>,
+    },
+    Sync {
+        manifest: Option<String>,
+        check: bool,
+    },
+    Lock {
+        manifest: Option<String>,
+    },
+    Usage,
+    Version,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub json: bool,
+    /// Skip interactive confirmation prompts
+    pub yes: bool,
+    /// Print the plan of a mutating command instead of running it
+    pub dry_run: bool,
+    pub command: Command,
+}
+
+impl Cli {
+    /// Parse arguments, excluding the program name
+    pub fn parse<I, S>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut json = false;
+        let mut yes = false;
+        let mut dry_run = false;
+        let mut check = false;
+        let mut locked = false;
+        let mut with_deps = false;
+        let mut manifest = None;
+        let mut positional = Vec::new();
+
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => json = true,
+                "--check" => check = true,
+                "--locked" => locked = true,
+                "--with-deps" => with_deps = true,
+                "-y" | "--yes" => yes = true,
+                "-n" | "--dry-run" => dry_run = true,
+                "--manifest" => match args.next() {
+                    Some(path) => manifest = Some(path),
+                    None => return Err("'--manifest' requires a path".to_string()),
+                },
+                "-h" | "--help" => {
+                    return Ok(Self {
+                        json,
+                        yes,
+                        dry_run,
+                        command: Command::Usage,
+                    })
+                }
+                "-V" | "--version" => {
+                    return Ok(Self {
+                        json,
+                        yes,
+                        dry_run,
+                        command: Command::Version,
+                    })
+                }
+                flag if flag.starts_with('-') => return Err(format!("Unknown option '{flag}'")),
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let Some(name) = positional.next() else {
+            return Ok(Self {
+                json,
+                yes,
+                dry_run,
+                command: Command::Usage,
+            });
+        };
+        let rest: Vec<String> = positional.collect();
+
+        let command = match name.as_str() {
+            "list" => {
+                Self::expect_no_args(&name, &rest)?;
+                Command::List
+            }
+            "status" => Command::Status { ids: rest },
+            "install" if locked => Command::InstallLocked { ids: rest },
+            "install" => Command::Install {
+                ids: Self::expect_ids(&name, rest)?,
+                with_deps,
+            },
+            "uninstall" => Command::Uninstall {
+                ids: Self::expect_ids(&name, rest)?,
+            },
+            "update" => Command::Update {
+                ids: Self::expect_ids(&name, rest)?,
+            },
+            "rollback" => Command::Rollback {
+                ids: Self::expect_ids(&name, rest)?,
+            },
+            "outdated" => {
+                Self::expect_no_args(&name, &rest)?;
+                Command::Outdated
+            }
+            "help" => match rest.as_slice() {
+                [] => Command::Usage,
+                [id] => Command::Help { id: id.clone() },
+                _ => return Err("'help' takes a single tool ID".to_string()),
+            },
+            "sync" => {
+                Self::expect_no_args(&name, &rest)?;
+                Command::Sync {
+                    manifest: manifest.take(),
+                    check,
+                }
+            }
+            "lock" => {
+                Self::expect_no_args(&name, &rest)?;
+                Command::Lock {
+                    manifest: manifest.take(),
+                }
+            }
+            other => return Err(format!("Unknown command '{other}'")),
+        };
+
+        if check && !matches!(command, Command::Sync { .. }) {
+            return Err(format!("'--check' is only valid for 'sync', not '{name}'"));
+        }
+        if manifest.is_some() {
+            return Err(format!(
+                "'--manifest' is only valid for 'sync' and 'lock', not '{name}'"
+            ));
+        }
+        if locked && !matches!(command, Command::InstallLocked { .. }) {
+            return Err(format!(
+                "'--locked' is only valid for 'install', not '{name}'"
+            ));
+        }
+        if with_deps && !matches!(command, Command::Install { .. }) {
+            return Err("'--with-deps' is only valid for 'install' without '--locked'".to_string());
+        }
+        if dry_run
+            && !matches!(
+                command,
+                Command::Install { .. }
+                    | Command::Uninstall { .. }
+                    | Command::Update { .. }
+                    | Command::Rollback { .. }
+            )
+        {
+            return Err(format!(
+                "'--dry-run' is only valid for 'install', 'uninstall', 'update' and 'rollback', not '{name}'"
+            ));
+        }
+
+        Ok(Self {
+            json,
+            yes,
+            dry_run,
+            command,
+        })
+    }
+
+    fn expect_ids(command: &str, ids: StringVec) -> Result<StringVec, String> {
+        if ids.is_empty() {
+            Err(format!("'{command}' requires at least one tool ID"))
+        } else {
+            Ok(ids)
+        }
+    }
+
+    fn expect_no_args(command: &str, args: &[String]) -> Result<(), String> {
+        if args.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("'{command}' does not take arguments"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Cli::parse(["install", "git", "node"]).unwrap(),
+            Cli {
+                json: false,
+                yes: false,
+                dry_run: false,
+                command: Command::Install {
+                    ids: vec!["git".to_string(), "node".to_string()],
+                    with_deps: false
+                }
+            }
+        );
+        assert_eq!(
+            Cli::parse(["install", "--with-deps", "gemini-cli"])
+                .unwrap()
+                .command,
+            Command::Install {
+                ids: vec!["gemini-cli".to_string()],
+                with_deps: true
+            }
+        );
+        assert!(Cli::parse(["-y", "install", "cursor-cli"]).unwrap().yes);
+        assert!(Cli::parse(["update", "--dry-run", "gh"]).unwrap().dry_run);
+        assert_eq!(
+            Cli::parse(["--json", "status"]).unwrap(),
+            Cli {
+                json: true,
+                yes: false,
+                dry_run: false,
+                command: Command::Status { ids: vec![] }
+            }
+        );
+        assert_eq!(
+            Cli::parse(["outdated", "--json"]).unwrap().command,
+            Command::Outdated
+        );
+        assert_eq!(
+            Cli::parse(["help", "gh"]).unwrap().command,
+            Command::Help {
+                id: "gh".to_string()
+            }
+        );
+        assert_eq!(
+            Cli::parse(["sync", "--check", "--manifest", "tools.toml"])
+                .unwrap()
+                .command,
+            Command::Sync {
+                manifest: Some("tools.toml".to_string()),
+                check: true
+            }
+        );
+        assert_eq!(
+            Cli::parse(["install", "--locked"]).unwrap().command,
+            Command::InstallLocked { ids: vec![] }
+        );
+        assert_eq!(
+            Cli::parse(Vec::<String>::new()).unwrap().command,
+            Command::Usage
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Cli::parse(["install"]).is_err());
+        assert!(Cli::parse(["list", "extra"]).is_err());
+        assert!(Cli::parse(["frobnicate"]).is_err());
+        assert!(Cli::parse(["--verbose", "list"]).is_err());
+        assert!(Cli::parse(["list", "--check"]).is_err());
+        assert!(Cli::parse(["sync", "--manifest"]).is_err());
+        assert!(Cli::parse(["status", "--manifest", "a.toml"]).is_err());
+        assert!(Cli::parse(["update", "--locked", "gh"]).is_err());
+        assert!(Cli::parse(["install", "--locked", "--with-deps"]).is_err());
+        assert!(Cli::parse(["install", "--locked", "--dry-run"]).is_err());
+        assert!(Cli::parse(["status", "-n"]).is_err());
+    }
+}
@@ -0,0 +1,822 @@
+//! Subcommand implementations driving the core ToolManager
+
+use crate::args::Command;
+use cliverge_core::{
+    InstallScript, JournalOperation, LockedTool, Lockfile, OperationPlan, ProjectManifest,
+    SyncAction, ToolError, ToolManager, ToolStatus, VersionCheckStrategy, LOCKFILE_NAME,
+    MANIFEST_FILE_NAME,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// 类型别名以减少复杂度警告
+type StringVec = Vec<String>;
+type ToolSpec<'a> = (&'a str, Option<&'a str>);
+
+pub const EXIT_SUCCESS: u8 = 0;
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_NOT_FOUND: u8 = 3;
+pub const EXIT_NOT_SUPPORTED: u8 = 4;
+pub const EXIT_UPDATES_AVAILABLE: u8 = 5;
+pub const EXIT_OUT_OF_SYNC: u8 = 6;
+
+#[derive(Debug, Serialize)]
+struct ToolSummary {
+    id: String,
+    name: String,
+    command: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    id: String,
+    status: &'static str,
+    version: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OperationReport {
+    id: String,
+    operation: &'static str,
+    success: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlanReport {
+    id: String,
+    operation: &'static str,
+    /// Plans of missing prerequisites (with `--with-deps`) come before the tool's own
+    plans: Vec<OperationPlan>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OutdatedReport {
+    id: String,
+    current: Option<String>,
+    latest: Option<String>,
+    changelog_url: Option<String>,
+    /// Set when the update check failed, so the tool is not taken for up to date
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct HelpReport {
+    id: String,
+    help: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct LockReport {
+    path: String,
+    /// False when a tool failed and the existing lockfile was left as it was
+    written: bool,
+    tools: BTreeMap<String, LockedTool>,
+    failed: Vec<OperationReport>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Install { with_deps: bool },
+    Uninstall,
+    Update,
+    Rollback,
+}
+
+impl Operation {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Install { .. } => "install",
+            Self::Uninstall => "uninstall",
+            Self::Update => "update",
+            Self::Rollback => "rollback",
+        }
+    }
+
+    fn journal_operation(self) -> JournalOperation {
+        match self {
+            Self::Install { .. } => JournalOperation::Install,
+            Self::Uninstall => JournalOperation::Uninstall,
+            Self::Update => JournalOperation::Update,
+            Self::Rollback => JournalOperation::Rollback,
+        }
+    }
+
+    fn past_tense(self) -> &'static str {
+        match self {
+            Self::Install { .. } => "installed",
+            Self::Uninstall => "uninstalled",
+            Self::Update => "updated",
+            Self::Rollback => "rolled back",
+        }
+    }
+}
+
+pub struct Runner {
+    tool_manager: ToolManager,
+    json: bool,
+    yes: bool,
+    dry_run: bool,
+}
+
+impl Runner {
+    pub fn new(tool_manager: ToolManager, json: bool, yes: bool, dry_run: bool) -> Self {
+        Self {
+            tool_manager,
+            json,
+            yes,
+            dry_run,
+        }
+    }
+
+    /// Run a command and return the process exit code
+    pub async fn run(&self, command: Command) -> u8 {
+        match command {
+            Command::List => self.list(),
+            Command::Status { ids } => self.status(ids).await,
+            Command::Install { ids, with_deps } => {
+                self.mutate(Operation::Install { with_deps }, ids).await
+            }
+            Command::Uninstall { ids } => self.mutate(Operation::Uninstall, ids).await,
+            Command::Update { ids } => self.mutate(Operation::Update, ids).await,
+            Command::Rollback { ids } => self.mutate(Operation::Rollback, ids).await,
+            Command::Outdated => self.outdated().await,
+            Command::Help { id } => self.help(&id).await,
+            Command::InstallLocked { ids } => self.install_locked(ids).await,
+            Command::Sync { manifest, check } => self.sync(manifest, check).await,
+            Command::Lock { manifest } => self.lock(manifest).await,
+            Command::Usage | Command::Version => {
+                unreachable!("usage and version are handled before loading the catalog")
+            }
+        }
+    }
+
+    fn list(&self) -> u8 {
+        let tools = match self.tool_manager.get_all_tools_configs() {
+            Ok(tools) => tools,
+            Err(e) => return self.fail(&e),
+        };
+
+        let summaries: Vec<ToolSummary> = tools
+            .into_iter()
+            .map(|tool| ToolSummary {
+                id: tool.config.id,
+                name: tool.config.name,
+                command: tool.config.command,
+                description: tool.config.description,
+            })
+            .collect();
+
+        if self.json {
+            print_json(&summaries);
+        } else {
+            let width = summaries.iter().map(|s| s.id.len()).max().unwrap_or(0);
+            for summary in &summaries {
+                println!("{:<width$}  {}", summary.id, summary.name);
+            }
+        }
+
+        EXIT_SUCCESS
+    }
+
+    async fn status(&self, ids: Vec<String>) -> u8 {
+        let ids = if ids.is_empty() {
+            match self.all_tool_ids() {
+                Ok(ids) => ids,
+                Err(e) => return self.fail(&e),
+            }
+        } else {
+            ids
+        };
+
+        let mut exit_code = EXIT_SUCCESS;
+        let mut reports = Vec::new();
+
+        for (id, result) in self.tool_manager.refresh_statuses(&ids, |_| {}).await {
+            let report = match result {
+                Ok(status) => status_report(id, &status),
+                Err(e) => {
+                    exit_code = first_failure(exit_code, exit_code_for(&e));
+                    StatusReport {
+                        id,
+                        status: "error",
+                        version: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+
+            if !self.json {
+                let detail = match (&report.version, &report.error) {
+                    (Some(version), _) => format!("{} ({version})", report.status),
+                    (None, Some(error)) => format!("{}: {error}", report.status),
+                    (None, None) => report.status.to_string(),
+                };
+                println!("{}: {detail}", report.id);
+            }
+            reports.push(report);
+        }
+
+        if self.json {
+            print_json(&reports);
+        }
+
+        exit_code
+    }
+
+    async fn mutate(&self, operation: Operation, ids: Vec<String>) -> u8 {
+        let mut exit_code = EXIT_SUCCESS;
+        let mut reports = Vec::new();
+
+        let ids = match operation {
+            Operation::Install { .. } => self.install_order(ids),
+            _ => ids,
+        };
+        if self.dry_run {
+            return self.plan(operation, ids).await;
+        }
+
+        for id in ids {
+            let result = match operation {
+                Operation::Install { with_deps } => self.install(&id, with_deps).await,
+                Operation::Uninstall => self.tool_manager.uninstall_tool(&id).await,
+                Operation::Update => self.update(&id).await,
+                Operation::Rollback => self.tool_manager.rollback_tool(&id).await.map(|_| ()),
+            };
+
+            reports.push(self.operation_report(operation, id, result, &mut exit_code));
+        }
+
+        if self.json {
+            print_json(&reports);
+        }
+
+        exit_code
+    }
+
+    /// Print what an operation would run on each tool instead of running it
+    async fn plan(&self, operation: Operation, ids: Vec<String>) -> u8 {
+        let mut exit_code = EXIT_SUCCESS;
+        let mut reports = Vec::new();
+
+        for id in ids {
+            let (plans, error) = match self.plan_tool(operation, &id).await {
+                Ok(plans) => {
+                    if !self.json {
+                        for plan in &plans {
+                            println!("{plan}");
+                        }
+                    }
+                    (plans, None)
+                }
+                Err(e) => {
+                    exit_code = first_failure(exit_code, exit_code_for(&e));
+                    if !self.json {
+                        eprintln!("{id}: {} failed: {e}", operation.name());
+                    }
+                    (Vec::new(), Some(e.to_string()))
+                }
+            };
+            reports.push(PlanReport {
+                id,
+                operation: operation.name(),
+                plans,
+                error,
+            });
+        }
+
+        if self.json {
+            print_json(&reports);
+        }
+
+        exit_code
+    }
+
+    async fn plan_tool(
+        &self,
+        operation: Operation,
+        spec: &str,
+    ) -> Result<Vec<OperationPlan>, ToolError> {
+        let mut plans = Vec::new();
+        match operation {
+            Operation::Install { with_deps } => {
+                let (tool_id, version) = split_spec(spec);
+                if with_deps {
+                    for dependency in self.tool_manager.missing_dependencies(tool_id).await? {
+                        let operation = if dependency.installed_version.is_some() {
+                            JournalOperation::Update
+                        } else {
+                            JournalOperation::Install
+                        };
+                        plans.push(
+                            self.tool_manager
+                                .plan_operation(&dependency.tool_id, operation)?,
+                        );
+                    }
+                }
+                plans.push(match version {
+                    Some(version) => self.tool_manager.plan_version_install(tool_id, version)?,
+                    None => self
+                        .tool_manager
+                        .plan_operation(tool_id, JournalOperation::Install)?,
+                });
+            }
+            _ => {
+                // The rollback target depends on the installed version
+                if matches!(operation, Operation::Rollback) {
+                    self.tool_manager.check_tool_status(spec).await?;
+                }
+                plans.push(
+                    self.tool_manager
+                        .plan_operation(spec, operation.journal_operation())?,
+                );
+            }
+        }
+        Ok(plans)
+    }
+
+    /// Install `<ID>[@VER]`, optionally installing missing prerequisites first
+    async fn install(&self, spec: &str, with_deps: bool) -> Result<(), ToolError> {
+        let (tool_id, version) = split_spec(spec);
+
+        if with_deps {
+            for dependency in self.tool_manager.install_dependencies(tool_id).await? {
+                if !self.json {
+                    println!(
+                        "{}: installed as a prerequisite of {tool_id}",
+                        dependency.tool_id
+                    );
+                }
+            }
+        }
+
+        match version {
+            Some(version) => {
+                self.tool_manager
+                    .install_tool_version(tool_id, version)
+                    .await
+            }
+            None if self.tool_manager.uses_install_script(tool_id) => {
+                self.install_script(tool_id).await
+            }
+            None => self.tool_manager.install_tool(tool_id).await,
+        }
+    }
+
+    /// Download a tool's install script, show it and run it once confirmed
+    async fn install_script(&self, tool_id: &str) -> Result<(), ToolError> {
+        let script = self.tool_manager.fetch_install_script(tool_id).await?;
+        self.review_script(&script, "installed")?;
+        self.tool_manager.install_script(&script).await
+    }
+
+    /// Update a tool, reviewing the script first when it updates by re-running one
+    async fn update(&self, tool_id: &str) -> Result<(), ToolError> {
+        if !self.tool_manager.uses_update_script(tool_id) {
+            return self.tool_manager.update_tool(tool_id).await;
+        }
+        let script = self.tool_manager.fetch_update_script(tool_id).await?;
+        self.review_script(&script, "updated")?;
+        self.tool_manager.update_script(&script).await
+    }
+
+    /// Show a downloaded script and ask before it runs, unless `--yes` was given
+    ///
+    /// A declined script is deleted.
+    fn review_script(&self, script: &InstallScript, action: &str) -> Result<(), ToolError> {
+        if !self.yes {
+            // Review goes to stderr so `--json` output on stdout stays parseable
+            eprintln!("{} is {action} by running {}", script.tool_id, script.url);
+            if script.verified {
+                eprintln!("sha256: {} (matches the catalog)", script.sha256);
+            } else {
+                eprintln!("sha256: {} (no checksum in the catalog)", script.sha256);
+            }
+            eprintln!("----- {} -----", script.path.display());
+            eprintln!("{}", script.contents.trim_end());
+            eprintln!("-----");
+            eprint!("Run this script? [y/N] ");
+
+            let mut answer = String::new();
+            let confirmed = std::io::stdin().read_line(&mut answer).is_ok()
+                && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+            if !confirmed {
+                script.discard();
+                return Err(ToolError::InstallationFailed(
+                    "install script not confirmed (pass --yes to run it without review)"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Order install specs so prerequisites named on the command line go first
+    fn install_order(&self, specs: StringVec) -> StringVec {
+        let tool_id = |spec: &str| spec.split('@').next().unwrap_or(spec).to_string();
+        let tool_ids: StringVec = specs.iter().map(|spec| tool_id(spec)).collect();
+
+        self.tool_manager
+            .dependency_order(&tool_ids)
+            .into_iter()
+            .filter_map(|id| specs.iter().find(|spec| tool_id(spec) == id).cloned())
+            .collect()
+    }
+
+    async fn install_locked(&self, ids: Vec<String>) -> u8 {
+        let found = std::env::current_dir()
+            .ok()
+            .and_then(|dir| Lockfile::find(&dir));
+        let Some(path) = found else {
+            return self.fail_message(&format!(
+                "No {LOCKFILE_NAME} found in the current directory or its parents"
+            ));
+        };
+        let lockfile = match Lockfile::load(&path) {
+            Ok(lockfile) => lockfile,
+            Err(e) => return self.fail_message(&format!("{}: {e}", path.display())),
+        };
+
+        let ids = if ids.is_empty() {
+            lockfile.tools.keys().cloned().collect()
+        } else {
+            ids
+        };
+        let ids = self.tool_manager.dependency_order(&ids);
+
+        let mut exit_code = EXIT_SUCCESS;
+        let mut reports = Vec::new();
+
+        for id in ids {
+            let result = match lockfile.tools.get(&id) {
+                Some(locked) => self.tool_manager.install_locked_tool(&id, locked).await,
+                None => Err(ToolError::NotFound(format!(
+                    "Tool {id} is not in {}",
+                    path.display()
+                ))),
+            };
+            reports.push(self.operation_report(
+                Operation::Install { with_deps: false },
+                id,
+                result,
+                &mut exit_code,
+            ));
+        }
+
+        if self.json {
+            print_json(&reports);
+        }
+
+        exit_code
+    }
+
+    fn operation_report(
+        &self,
+        operation: Operation,
+        id: String,
+        result: Result<(), ToolError>,
+        exit_code: &mut u8,
+    ) -> OperationReport {
+        match result {
+            Ok(()) => {
+                if !self.json {
+                    println!("{id}: {}", operation.past_tense());
+                }
+                OperationReport {
+                    id,
+                    operation: operation.name(),
+                    success: true,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                *exit_code = first_failure(*exit_code, exit_code_for(&e));
+                if !self.json {
+                    eprintln!("{id}: {} failed: {e}", operation.name());
+                    if matches!(e, ToolError::MissingDependencies(_)) {
+                        eprintln!("hint: rerun with --with-deps to install them first");
+                    }
+                }
+                OperationReport {
+                    id,
+                    operation: operation.name(),
+                    success: false,
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    async fn outdated(&self) -> u8 {
+        let ids = match self.all_tool_ids() {
+            Ok(ids) => ids,
+            Err(e) => return self.fail(&e),
+        };
+
+        let mut reports = Vec::new();
+        for id in ids {
+            match self
+                .tool_manager
+                .check_version_updates(&id, VersionCheckStrategy::Auto)
+                .await
+            {
+                Ok(info) if info.current.is_some() && info.update_available => {
+                    reports.push(OutdatedReport {
+                        id,
+                        current: info.current,
+                        latest: info.latest,
+                        changelog_url: info.changelog_url,
+                        error: None,
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    if !self.json {
+                        eprintln!("{id}: update check failed: {e}");
+                    }
+                    reports.push(OutdatedReport {
+                        id,
+                        current: None,
+                        latest: None,
+                        changelog_url: None,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+
+        let check_failed = reports.iter().any(|r| r.error.is_some());
+        let outdated: Vec<&OutdatedReport> = reports.iter().filter(|r| r.error.is_none()).collect();
+        if self.json {
+            print_json(&reports);
+        } else if reports.is_empty() {
+            println!("All installed tools are up to date");
+        } else {
+            let width = outdated.iter().map(|r| r.id.len()).max().unwrap_or(0);
+            for report in &outdated {
+                println!(
+                    "{:<width$}  {} -> {}{}",
+                    report.id,
+                    report.current.as_deref().unwrap_or("?"),
+                    report.latest.as_deref().unwrap_or("?"),
+                    report
+                        .changelog_url
+                        .as_deref()
+                        .map(|url| format!("  ({url})"))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        // 检查失败时不能报告为已是最新
+        if check_failed {
+            EXIT_FAILURE
+        } else if outdated.is_empty() {
+            EXIT_SUCCESS
+        } else {
+            EXIT_UPDATES_AVAILABLE
+        }
+    }
+
+    async fn help(&self, id: &str) -> u8 {
+        match self.tool_manager.get_tool_help(id).await {
+            Ok(help) => {
+                if self.json {
+                    print_json(&HelpReport {
+                        id: id.to_string(),
+                        help: Some(help),
+                        error: None,
+                    });
+                } else {
+                    print!("{help}");
+                }
+                EXIT_SUCCESS
+            }
+            Err(e) => {
+                if self.json {
+                    print_json(&HelpReport {
+                        id: id.to_string(),
+                        help: None,
+                        error: Some(e.to_string()),
+                    });
+                } else {
+                    eprintln!("{id}: {e}");
+                }
+                exit_code_for(&e)
+            }
+        }
+    }
+
+    async fn sync(&self, manifest: Option<String>, check: bool) -> u8 {
+        let Some(path) = manifest_path(manifest) else {
+            return self.fail_message(&format!(
+                "No {MANIFEST_FILE_NAME} found in the current directory or its parents"
+            ));
+        };
+
+        let manifest = match ProjectManifest::load(&path) {
+            Ok(manifest) => manifest,
+            Err(e) => return self.fail_message(&format!("{}: {e}", path.display())),
+        };
+
+        let report = self.tool_manager.sync_manifest(&manifest, !check).await;
+
+        if self.json {
+            print_json(&report);
+        } else {
+            for entry in &report.entries {
+                let version = entry
+                    .version_after
+                    .as_ref()
+                    .or(entry.version_before.as_ref())
+                    .map(|v| format!(" ({v})"))
+                    .unwrap_or_default();
+                let outcome = match &entry.action {
+                    SyncAction::Satisfied => format!("satisfied{version}"),
+                    SyncAction::Installed => format!("installed{version}"),
+                    SyncAction::Updated => format!("updated{version}"),
+                    SyncAction::Missing => "missing".to_string(),
+                    SyncAction::Outdated => format!("outdated{version}"),
+                    SyncAction::Unsatisfied(reason) => format!("unsatisfied: {reason}"),
+                    SyncAction::Failed(reason) => format!("failed: {reason}"),
+                };
+                println!("{} [{}]: {outcome}", entry.tool_id, entry.requirement);
+            }
+        }
+
+        if report.is_in_sync() {
+            EXIT_SUCCESS
+        } else if check {
+            EXIT_OUT_OF_SYNC
+        } else {
+            EXIT_FAILURE
+        }
+    }
+
+    async fn lock(&self, manifest: Option<String>) -> u8 {
+        // Lock the manifest's tools next to it, or every installed tool in the current directory
+        let (ids, from_manifest, lock_path) = match manifest_path(manifest) {
+            Some(path) => {
+                let manifest = match ProjectManifest::load(&path) {
+                    Ok(manifest) => manifest,
+                    Err(e) => return self.fail_message(&format!("{}: {e}", path.display())),
+                };
+                let lock_path = path.with_file_name(LOCKFILE_NAME);
+                (manifest.tools.into_keys().collect(), true, lock_path)
+            }
+            None => {
+                let ids = match self.all_tool_ids() {
+                    Ok(ids) => ids,
+                    Err(e) => return self.fail(&e),
+                };
+                (ids, false, PathBuf::from(LOCKFILE_NAME))
+            }
+        };
+
+        let mut exit_code = EXIT_SUCCESS;
+        let mut lockfile = Lockfile::default();
+        let mut failed = Vec::new();
+
+        for id in ids {
+            match self.tool_manager.lock_tool(&id).await {
+                Ok(locked) => {
+                    lockfile.tools.insert(id, locked);
+                }
+                // Without a manifest, tools that are not installed here are simply left out
+                Err(ToolError::NotFound(_) | ToolError::NotSupported(_)) if !from_manifest => {}
+                Err(e) => {
+                    exit_code = first_failure(exit_code, exit_code_for(&e));
+                    failed.push(OperationReport {
+                        id,
+                        operation: "lock",
+                        success: false,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+
+        // 有工具失败时不覆盖锁定文件，以免丢失该工具已有的锁定版本
+        let written = failed.is_empty();
+        if written {
+            if let Err(e) = lockfile.save(&lock_path) {
+                return self.fail_message(&format!("{}: {e}", lock_path.display()));
+            }
+        }
+
+        if self.json {
+            print_json(&LockReport {
+                path: lock_path.display().to_string(),
+                written,
+                tools: lockfile.tools,
+                failed,
+            });
+        } else {
+            for (id, locked) in &lockfile.tools {
+                println!("{id}: {} ({})", locked.version, locked.method);
+            }
+            for report in &failed {
+                eprintln!(
+                    "{}: lock failed: {}",
+                    report.id,
+                    report.error.as_deref().unwrap_or_default()
+                );
+            }
+            if written {
+                println!("Wrote {}", lock_path.display());
+            } else {
+                eprintln!("{} left unchanged", lock_path.display());
+            }
+        }
+
+        exit_code
+    }
+
+    fn all_tool_ids(&self) -> Result<StringVec, ToolError> {
+        Ok(self
+            .tool_manager
+            .get_all_tools_configs()?
+            .into_iter()
+            .map(|tool| tool.config.id)
+            .collect())
+    }
+
+    fn fail(&self, error: &ToolError) -> u8 {
+        self.fail_message(&error.to_string());
+        exit_code_for(error)
+    }
+
+    fn fail_message(&self, message: &str) -> u8 {
+        if self.json {
+            print_json(&serde_json::json!({ "error": message }));
+        } else {
+            eprintln!("Error: {message}");
+        }
+        EXIT_FAILURE
+    }
+}
+
+fn status_report(id: String, status: &ToolStatus) -> StatusReport {
+    let (status, version, error) = match status {
+        ToolStatus::Unknown => ("unknown", None, None),
+        ToolStatus::NotInstalled => ("not_installed", None, None),
+        ToolStatus::Installed { version } => ("installed", Some(version.clone()), None),
+        ToolStatus::Error(msg) => ("error", None, Some(msg.clone())),
+    };
+
+    StatusReport {
+        id,
+        status,
+        version,
+        error,
+    }
+}
+
+/// Split `<ID>[@VER]` into the tool id and the requested version
+fn split_spec(spec: &str) -> ToolSpec<'_> {
+    match spec.split_once('@') {
+        Some((tool_id, version)) => (tool_id, Some(version)),
+        None => (spec, None),
+    }
+}
+
+/// Use an explicit manifest path or search upwards from the current directory
+fn manifest_path(manifest: Option<String>) -> Option<PathBuf> {
+    match manifest {
+        Some(path) => Some(PathBuf::from(path)),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|dir| ProjectManifest::find(&dir)),
+    }
+}
+
+/// Map a core error onto the documented exit codes
+pub fn exit_code_for(error: &ToolError) -> u8 {
+    match error {
+        ToolError::NotFound(_) => EXIT_NOT_FOUND,
+        ToolError::NotSupported(_) => EXIT_NOT_SUPPORTED,
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// Keep the first non-zero exit code seen across a batch of tools
+fn first_failure(current: u8, new: u8) -> u8 {
+    if current == EXIT_SUCCESS {
+        new
+    } else {
+        current
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Failed to serialize output: {e}"),
+    }
+}
@@ -0,0 +1,63 @@
+//! Headless command-line frontend for CLIverge
+
+mod args;
+mod commands;
+
+use args::{Cli, Command, USAGE};
+use cliverge_core::{ConfigManager, Elevation, ToolManager};
+use commands::{Runner, EXIT_FAILURE, EXIT_USAGE};
+use std::io::IsTerminal;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+fn main() -> ExitCode {
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    // Usage and version output do not need the tool catalog
+    match cli.command {
+        Command::Usage => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Command::Version => {
+            println!("cliverge-cli {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        _ => {}
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to create async runtime: {e}");
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+
+    let code = runtime.block_on(async {
+        let config_manager = match ConfigManager::load().await {
+            Ok(config_manager) => config_manager,
+            Err(e) => {
+                eprintln!("Failed to load configuration: {e}");
+                return EXIT_FAILURE;
+            }
+        };
+
+        let tool_manager = ToolManager::new(Arc::new(Mutex::new(config_manager)));
+        // Without a terminal sudo cannot prompt, so use pkexec or fail fast instead of hanging
+        if !std::io::stdin().is_terminal() {
+            tool_manager.set_elevation(Elevation::without_terminal(None));
+        }
+        Runner::new(tool_manager, cli.json, cli.yes, cli.dry_run)
+            .run(cli.command)
+            .await
+    });
+
+    ExitCode::from(code)
+}
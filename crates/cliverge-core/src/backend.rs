@@ -0,0 +1,929 @@
+//! Install backends: one implementation per `InstallMethod.method`
+//!
+//! A backend only builds commands and parses their output; running them stays in
+//! `ToolManager` and `VersionChecker` so journaling and error handling are shared.
+
+use crate::{InstallMethod, ToolError, Version, BINARY_METHOD, SCRIPT_METHOD};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// 类型别名以减少复杂度警告
+type StringVec = Vec<String>;
+type CommandResult = Result<StringVec, ToolError>;
+pub type BackendRef = Arc<dyn InstallBackend>;
+pub type SharedBackends = Arc<Mutex<BackendRegistry>>;
+
+/// Method that only runs the commands given in the catalog
+pub const CUSTOM_METHOD: &str = "custom";
+
+/// Package manager or installer that can manage a tool
+pub trait InstallBackend: Send + Sync {
+    /// Method name matched against `InstallMethod.method`
+    fn name(&self) -> &str;
+
+    fn install_command(&self, install: &InstallMethod) -> CommandResult;
+
+    fn uninstall_command(&self, install: &InstallMethod) -> CommandResult;
+
+    fn update_command(&self, install: &InstallMethod) -> CommandResult;
+
+    /// Install an exact version
+    fn pinned_install_command(&self, _install: &InstallMethod, _version: &str) -> CommandResult {
+        Err(ToolError::NotSupported(format!(
+            "Installing an exact version is not supported for method '{}'",
+            self.name()
+        )))
+    }
+
+    /// Command printing the newest available version
+    fn latest_version_command(&self, _install: &InstallMethod) -> CommandResult {
+        Err(ToolError::NotSupported(format!(
+            "Latest version lookup is not supported for method '{}'",
+            self.name()
+        )))
+    }
+
+    fn parse_latest_version(&self, _install: &InstallMethod, _output: &str) -> Option<String> {
+        None
+    }
+
+    /// Command asking the package manager which version is installed
+    fn installed_version_command(&self, _install: &InstallMethod) -> CommandResult {
+        Err(ToolError::NotSupported(format!(
+            "Installed version lookup is not supported for method '{}'",
+            self.name()
+        )))
+    }
+
+    fn parse_installed_version(&self, _install: &InstallMethod, _output: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Backends keyed by method name
+#[derive(Clone)]
+pub struct BackendRegistry {
+    backends: HashMap<String, BackendRef>,
+}
+
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl BackendRegistry {
+    /// Registry without any backends
+    pub fn empty() -> Self {
+        Self {
+            backends: HashMap::new(),
+        }
+    }
+
+    /// Registry with the built-in package managers
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::empty();
+        for spec in BUILTIN_BACKENDS {
+            registry.register(Arc::new(TemplateBackend(spec)));
+        }
+        registry.register(Arc::new(SnapBackend));
+        registry
+    }
+
+    /// Add a backend, replacing any existing one with the same name
+    pub fn register(&mut self, backend: BackendRef) {
+        self.backends.insert(backend.name().to_string(), backend);
+    }
+
+    pub fn get(&self, method: &str) -> Result<BackendRef, ToolError> {
+        self.backends.get(method).cloned().ok_or_else(|| {
+            ToolError::NotSupported(format!("Install method '{method}' not supported"))
+        })
+    }
+
+    /// Whether a catalog entry with this method can be carried out
+    pub fn is_known_method(&self, method: &str) -> bool {
+        // script、binary 和 custom 由 ToolManager 直接处理
+        [SCRIPT_METHOD, BINARY_METHOD, CUSTOM_METHOD].contains(&method)
+            || self.backends.contains_key(method)
+    }
+
+    /// Registered method names, sorted
+    pub fn methods(&self) -> Vec<&str> {
+        let mut methods: Vec<&str> = self.backends.keys().map(String::as_str).collect();
+        methods.sort_unstable();
+        methods
+    }
+}
+
+/// Package name from the config, falling back to the last argument of its command
+pub fn package_name(install: &InstallMethod) -> Result<&str, ToolError> {
+    install
+        .package_name
+        .as_deref()
+        .or_else(|| install.command.as_ref()?.last().map(String::as_str))
+        .ok_or_else(|| ToolError::ConfigError(format!("{} requires package_name", install.method)))
+}
+
+type OutputParser = fn(&str, &str) -> Option<String>;
+type Template = &'static [&'static str];
+/// Command template and the parser for its output
+type Query = Option<(Template, OutputParser)>;
+
+/// Built-in backend described by command templates with `{package}` and `{version}` placeholders
+struct BackendSpec {
+    name: &'static str,
+    install: Template,
+    /// `None` when the package manager cannot remove what it installed
+    uninstall: Option<Template>,
+    update: Template,
+    pinned: Option<Template>,
+    latest: Query,
+    installed: Query,
+}
+
+struct TemplateBackend(&'static BackendSpec);
+
+impl TemplateBackend {
+    fn render(template: &[&str], package: &str, version: &str) -> StringVec {
+        template
+            .iter()
+            .map(|arg| {
+                arg.replace("{package}", package)
+                    .replace("{version}", version)
+            })
+            .collect()
+    }
+
+    fn command(&self, template: &[&str], install: &InstallMethod) -> CommandResult {
+        Ok(Self::render(template, package_name(install)?, ""))
+    }
+}
+
+impl InstallBackend for TemplateBackend {
+    fn name(&self) -> &str {
+        self.0.name
+    }
+
+    fn install_command(&self, install: &InstallMethod) -> CommandResult {
+        self.command(self.0.install, install)
+    }
+
+    fn uninstall_command(&self, install: &InstallMethod) -> CommandResult {
+        match self.0.uninstall {
+            Some(template) => self.command(template, install),
+            None => Err(ToolError::NotSupported(format!(
+                "Uninstall method '{}' not supported",
+                self.0.name
+            ))),
+        }
+    }
+
+    fn update_command(&self, install: &InstallMethod) -> CommandResult {
+        self.command(self.0.update, install)
+    }
+
+    fn pinned_install_command(&self, install: &InstallMethod, version: &str) -> CommandResult {
+        match self.0.pinned {
+            Some(template) => Ok(Self::render(template, package_name(install)?, version)),
+            None => Err(ToolError::NotSupported(format!(
+                "Installing an exact version is not supported for method '{}'",
+                self.0.name
+            ))),
+        }
+    }
+
+    fn latest_version_command(&self, install: &InstallMethod) -> CommandResult {
+        match self.0.latest {
+            Some((template, _)) => self.command(template, install),
+            None => Err(ToolError::NotSupported(format!(
+                "Latest version lookup is not supported for method '{}'",
+                self.0.name
+            ))),
+        }
+    }
+
+    fn parse_latest_version(&self, install: &InstallMethod, output: &str) -> Option<String> {
+        let (_, parse) = self.0.latest?;
+        parse(package_name(install).ok()?, output)
+    }
+
+    fn installed_version_command(&self, install: &InstallMethod) -> CommandResult {
+        match self.0.installed {
+            Some((template, _)) => self.command(template, install),
+            None => Err(ToolError::NotSupported(format!(
+                "Installed version lookup is not supported for method '{}'",
+                self.0.name
+            ))),
+        }
+    }
+
+    fn parse_installed_version(&self, install: &InstallMethod, output: &str) -> Option<String> {
+        let (_, parse) = self.0.installed?;
+        parse(package_name(install).ok()?, output)
+    }
+}
+
+const BUILTIN_BACKENDS: &[BackendSpec] = &[
+    BackendSpec {
+        name: "npm",
+        install: &["npm", "install", "-g", "{package}"],
+        uninstall: Some(&["npm", "uninstall", "-g", "{package}"]),
+        update: &["npm", "update", "-g", "{package}"],
+        pinned: Some(&["npm", "install", "-g", "{package}@{version}"]),
+        latest: Some((&["npm", "view", "{package}", "version"], parse_first_line)),
+        installed: Some((
+            &["npm", "ls", "-g", "{package}", "--depth=0", "--json"],
+            parse_npm_ls,
+        )),
+    },
+    BackendSpec {
+        name: "pip",
+        install: &["pip", "install", "{package}"],
+        uninstall: Some(&["pip", "uninstall", "-y", "{package}"]),
+        update: &["pip", "install", "--upgrade", "{package}"],
+        pinned: Some(&["pip", "install", "{package}=={version}"]),
+        latest: Some((&["pip", "index", "versions", "{package}"], parse_pip_index)),
+        installed: Some((&["pip", "show", "{package}"], parse_version_field)),
+    },
+    BackendSpec {
+        name: "brew",
+        install: &["brew", "install", "{package}"],
+        uninstall: Some(&["brew", "uninstall", "{package}"]),
+        update: &["brew", "upgrade", "{package}"],
+        // `pkg@version` 只对少数按主版本拆分的 formula 有效，锁文件中的 brew 版本只作参考
+        pinned: None,
+        latest: Some((&["brew", "info", "{package}", "--json=v1"], parse_brew_info)),
+        installed: Some((
+            &["brew", "list", "--versions", "{package}"],
+            parse_name_version,
+        )),
+    },
+    BackendSpec {
+        name: "apt",
+        install: &["sudo", "apt", "install", "-y", "{package}"],
+        uninstall: Some(&["sudo", "apt", "remove", "-y", "{package}"]),
+        update: &[
+            "sudo",
+            "apt",
+            "install",
+            "--only-upgrade",
+            "-y",
+            "{package}",
+        ],
+        pinned: Some(&["sudo", "apt", "install", "-y", "{package}={version}"]),
+        // 固定英文输出，字段名不随系统语言变化
+        latest: Some((
+            &["env", "LC_ALL=C", "apt-cache", "policy", "{package}"],
+            parse_apt_policy,
+        )),
+        installed: Some((
+            &["dpkg-query", "-W", "-f=${Version}", "{package}"],
+            parse_first_line,
+        )),
+    },
+    BackendSpec {
+        name: "yum",
+        install: &["sudo", "yum", "install", "-y", "{package}"],
+        uninstall: Some(&["sudo", "yum", "remove", "-y", "{package}"]),
+        update: &["sudo", "yum", "update", "-y", "{package}"],
+        pinned: None,
+        latest: Some((
+            &["env", "LC_ALL=C", "yum", "info", "{package}"],
+            parse_rpm_info,
+        )),
+        installed: Some((
+            &["rpm", "-q", "--qf", "%{VERSION}", "{package}"],
+            parse_first_line,
+        )),
+    },
+    BackendSpec {
+        name: "dnf",
+        install: &["sudo", "dnf", "install", "-y", "{package}"],
+        uninstall: Some(&["sudo", "dnf", "remove", "-y", "{package}"]),
+        update: &["sudo", "dnf", "upgrade", "-y", "{package}"],
+        pinned: None,
+        latest: Some((
+            &["env", "LC_ALL=C", "dnf", "info", "{package}"],
+            parse_rpm_info,
+        )),
+        installed: Some((
+            &["rpm", "-q", "--qf", "%{VERSION}", "{package}"],
+            parse_first_line,
+        )),
+    },
+    BackendSpec {
+        name: "pacman",
+        install: &["sudo", "pacman", "-S", "--noconfirm", "{package}"],
+        uninstall: Some(&["sudo", "pacman", "-R", "--noconfirm", "{package}"]),
+        // 只升级这一个包，已是最新时 --needed 跳过重装；不做整个系统的 -Syu 升级
+        update: &[
+            "sudo",
+            "pacman",
+            "-S",
+            "--needed",
+            "--noconfirm",
+            "{package}",
+        ],
+        pinned: None,
+        latest: Some((
+            &["env", "LC_ALL=C", "pacman", "-Si", "{package}"],
+            parse_pacman_info,
+        )),
+        installed: Some((&["pacman", "-Q", "{package}"], parse_name_version)),
+    },
+    BackendSpec {
+        name: "winget",
+        install: &[
+            "winget",
+            "install",
+            "{package}",
+            "--accept-source-agreements",
+            "--accept-package-agreements",
+        ],
+        uninstall: Some(&["winget", "uninstall", "{package}"]),
+        update: &[
+            "winget",
+            "upgrade",
+            "{package}",
+            "--accept-source-agreements",
+            "--accept-package-agreements",
+        ],
+        pinned: None,
+        latest: Some((
+            &[
+                "winget",
+                "show",
+                "{package}",
+                "--exact",
+                "--accept-source-agreements",
+            ],
+            parse_info_version,
+        )),
+        installed: None,
+    },
+    BackendSpec {
+        name: "choco",
+        install: &["choco", "install", "{package}", "-y"],
+        uninstall: Some(&["choco", "uninstall", "{package}", "-y"]),
+        update: &["choco", "upgrade", "{package}", "-y"],
+        pinned: None,
+        latest: Some((
+            &["choco", "search", "{package}", "--exact", "--limit-output"],
+            parse_choco_search,
+        )),
+        installed: None,
+    },
+    BackendSpec {
+        name: "scoop",
+        install: &["scoop", "install", "{package}"],
+        uninstall: Some(&["scoop", "uninstall", "{package}"]),
+        update: &["scoop", "update", "{package}"],
+        pinned: None,
+        latest: Some((&["scoop", "info", "{package}"], parse_info_version)),
+        installed: None,
+    },
+    BackendSpec {
+        name: "cargo",
+        install: &["cargo", "install", "{package}"],
+        uninstall: Some(&["cargo", "uninstall", "{package}"]),
+        // `cargo install` replaces an older installed version
+        update: &["cargo", "install", "{package}"],
+        pinned: Some(&["cargo", "install", "{package}", "--version", "{version}"]),
+        latest: Some((
+            &["cargo", "search", "{package}", "--limit", "1"],
+            parse_cargo_search,
+        )),
+        installed: None,
+    },
+    BackendSpec {
+        name: "go",
+        install: &["go", "install", "{package}"],
+        uninstall: None,
+        update: &["go", "install", "{package}"],
+        pinned: None,
+        latest: None,
+        installed: None,
+    },
+];
+
+/// Snapcraft; `InstallMethod.classic` adds `--classic` on install
+struct SnapBackend;
+
+impl SnapBackend {
+    fn command(args: &[&str], package: &str) -> StringVec {
+        let mut command: StringVec = args.iter().map(|arg| arg.to_string()).collect();
+        command.push(package.to_string());
+        command
+    }
+}
+
+impl InstallBackend for SnapBackend {
+    fn name(&self) -> &str {
+        "snap"
+    }
+
+    fn install_command(&self, install: &InstallMethod) -> CommandResult {
+        let mut command = Self::command(&["sudo", "snap", "install"], package_name(install)?);
+        if install.classic {
+            command.push("--classic".to_string());
+        }
+        Ok(command)
+    }
+
+    fn uninstall_command(&self, install: &InstallMethod) -> CommandResult {
+        Ok(Self::command(
+            &["sudo", "snap", "remove"],
+            package_name(install)?,
+        ))
+    }
+
+    fn update_command(&self, install: &InstallMethod) -> CommandResult {
+        Ok(Self::command(
+            &["sudo", "snap", "refresh"],
+            package_name(install)?,
+        ))
+    }
+
+    fn latest_version_command(&self, install: &InstallMethod) -> CommandResult {
+        Ok(Self::command(&["snap", "info"], package_name(install)?))
+    }
+
+    fn parse_latest_version(&self, _install: &InstallMethod, output: &str) -> Option<String> {
+        parse_snap_info(output)
+    }
+
+    fn installed_version_command(&self, install: &InstallMethod) -> CommandResult {
+        Ok(Self::command(&["snap", "list"], package_name(install)?))
+    }
+
+    fn parse_installed_version(&self, install: &InstallMethod, output: &str) -> Option<String> {
+        parse_snap_list(package_name(install).ok()?, output)
+    }
+}
+
+/// `snap info <pkg>`: version on the `latest/stable:` line of the `channels:` section
+fn parse_snap_info(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix("latest/stable:")?;
+        // 未发布的频道显示为 `^` 或 `--`
+        rest.split_whitespace()
+            .next()
+            .filter(|version| *version != "^" && *version != "--")
+            .map(str::to_string)
+    })
+}
+
+/// `snap list <pkg>`: `Name Version Rev Tracking Publisher Notes` table
+fn parse_snap_list(package: &str, output: &str) -> Option<String> {
+    output.lines().skip(1).find_map(|line| {
+        let mut columns = line.split_whitespace();
+        (columns.next()? == package).then(|| columns.next().map(str::to_string))?
+    })
+}
+
+fn parse_first_line(_package: &str, output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// `npm ls -g <pkg> --json`: `{"dependencies": {"<pkg>": {"version": "1.2.3"}}}`
+fn parse_npm_ls(package: &str, output: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(output).ok()?;
+    json.get("dependencies")?
+        .get(package)?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// `pip index versions <pkg>`: `<pkg> (1.2.3)` on the first line
+fn parse_pip_index(_package: &str, output: &str) -> Option<String> {
+    let line = output.lines().find(|line| line.contains('('))?;
+    let start = line.find('(')? + 1;
+    let end = line[start..].find(')')? + start;
+    Some(line[start..end].trim().to_string())
+}
+
+/// `pip show <pkg>`: a `Version: 1.2.3` line
+fn parse_version_field(_package: &str, output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Version:"))
+        .map(|version| version.trim().to_string())
+}
+
+/// `brew info <pkg> --json=v1`: `[{"versions": {"stable": "1.2.3"}}]`
+fn parse_brew_info(_package: &str, output: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(output).ok()?;
+    json.as_array()?
+        .first()?
+        .get("versions")?
+        .get("stable")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Values of `<key> : <value>` lines, as `dnf info`, `pacman -Si` and `winget show` print them
+fn field_values<'a>(output: &'a str, key: &'a str) -> impl Iterator<Item = &'a str> {
+    output.lines().filter_map(move |line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == key).then(|| value.trim())
+    })
+}
+
+/// Upstream part of a distribution version: `1:2.43.0-1ubuntu7` -> `2.43.0`
+fn upstream_version(version: &str) -> String {
+    let version = version.split_once(':').map_or(version, |(_, rest)| rest);
+    let version = version
+        .rsplit_once('-')
+        .map_or(version, |(upstream, _)| upstream);
+    version.to_string()
+}
+
+/// `apt-cache policy <pkg>`: `Candidate: 1:2.43.0-1ubuntu7`, `(none)` when no repository has it
+fn parse_apt_policy(_package: &str, output: &str) -> Option<String> {
+    field_values(output, "Candidate")
+        .find(|candidate| *candidate != "(none)")
+        .map(upstream_version)
+}
+
+/// `dnf info` / `yum info`: a `Version : 2.43.0` line for the installed and each available
+/// package; the highest one
+fn parse_rpm_info(_package: &str, output: &str) -> Option<String> {
+    field_values(output, "Version")
+        .filter_map(|version| Some((Version::parse(version)?, version)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, version)| version.to_string())
+}
+
+/// `pacman -Si <pkg>`: `Version : 2.43.0-1` from the first repository listing it
+fn parse_pacman_info(_package: &str, output: &str) -> Option<String> {
+    field_values(output, "Version").next().map(upstream_version)
+}
+
+/// `winget show` / `scoop info`: a `Version: 2.45.0` line
+fn parse_info_version(_package: &str, output: &str) -> Option<String> {
+    field_values(output, "Version")
+        .find(|version| !version.is_empty())
+        .map(str::to_string)
+}
+
+/// `choco search <pkg> --exact --limit-output`: `<pkg>|2.45.0`
+fn parse_choco_search(package: &str, output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (name, version) = line.trim().split_once('|')?;
+        name.eq_ignore_ascii_case(package)
+            .then(|| version.to_string())
+    })
+}
+
+/// `cargo search <pkg> --limit 1`: `<pkg> = "14.1.0"    # description`
+fn parse_cargo_search(package: &str, output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (name, rest) = line.split_once('=')?;
+        if name.trim() != package {
+            return None;
+        }
+        rest.trim()
+            .strip_prefix('"')?
+            .split('"')
+            .next()
+            .map(str::to_string)
+    })
+}
+
+/// `brew list --versions` / `pacman -Q`: `<pkg> 1.2.3`
+fn parse_name_version(package: &str, output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        (parts.next()? == package).then(|| parts.last().map(str::to_string))?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install(method: &str, package_name: Option<&str>) -> InstallMethod {
+        InstallMethod {
+            method: method.to_string(),
+            package_name: package_name.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn pinned(method: &str, package_name: Option<&str>, version: &str) -> CommandResult {
+        BackendRegistry::with_defaults()
+            .get(method)?
+            .pinned_install_command(&install(method, package_name), version)
+    }
+
+    #[test]
+    fn test_pinned_install_command() {
+        let command = pinned("npm", Some("@google/gemini-cli"), "0.1.18").unwrap();
+        assert_eq!(
+            command,
+            ["npm", "install", "-g", "@google/gemini-cli@0.1.18"]
+        );
+
+        let command = pinned("pip", Some("httpie"), "3.2.2").unwrap();
+        assert_eq!(command, ["pip", "install", "httpie==3.2.2"]);
+
+        let command = pinned("cargo", Some("ripgrep"), "14.1.0").unwrap();
+        assert_eq!(
+            command,
+            ["cargo", "install", "ripgrep", "--version", "14.1.0"]
+        );
+
+        let command = pinned("apt", Some("gh"), "2.45.0-1").unwrap();
+        assert_eq!(command, ["sudo", "apt", "install", "-y", "gh=2.45.0-1"]);
+
+        assert!(matches!(
+            pinned("brew", Some("gh"), "2.45.0"),
+            Err(ToolError::NotSupported(_))
+        ));
+        assert!(matches!(
+            pinned("winget", Some("Git.Git"), "2.45.0"),
+            Err(ToolError::NotSupported(_))
+        ));
+        assert!(matches!(
+            pinned("script", None, "1.0.0"),
+            Err(ToolError::NotSupported(_))
+        ));
+        assert!(matches!(
+            pinned("npm", None, "1.0.0"),
+            Err(ToolError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_every_backend_installs_and_updates() {
+        let registry = BackendRegistry::with_defaults();
+        for method in registry.methods() {
+            let backend = registry.get(method).unwrap();
+            let install = install(method, Some("pkg"));
+            assert!(backend.install_command(&install).is_ok(), "{method}");
+            assert!(backend.update_command(&install).is_ok(), "{method}");
+        }
+
+        let command = registry
+            .get("winget")
+            .unwrap()
+            .update_command(&install("winget", Some("GitHub.cli")))
+            .unwrap();
+        assert_eq!(command[..3], ["winget", "upgrade", "GitHub.cli"]);
+
+        let command = registry
+            .get("pacman")
+            .unwrap()
+            .update_command(&install("pacman", Some("github-cli")))
+            .unwrap();
+        assert_eq!(
+            command,
+            [
+                "sudo",
+                "pacman",
+                "-S",
+                "--needed",
+                "--noconfirm",
+                "github-cli"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_parsers() {
+        assert_eq!(
+            parse_npm_ls(
+                "@google/gemini-cli",
+                r#"{"dependencies": {"@google/gemini-cli": {"version": "0.1.18"}}}"#
+            ),
+            Some("0.1.18".to_string())
+        );
+        assert_eq!(
+            parse_pip_index(
+                "httpie",
+                "httpie (3.2.2)\nAvailable versions: 3.2.2, 3.2.1\n"
+            ),
+            Some("3.2.2".to_string())
+        );
+        assert_eq!(
+            parse_version_field("httpie", "Name: httpie\nVersion: 3.2.2\nSummary: HTTP"),
+            Some("3.2.2".to_string())
+        );
+        assert_eq!(
+            parse_brew_info(
+                "gh",
+                r#"[{"name": "gh", "versions": {"stable": "2.45.0"}}]"#
+            ),
+            Some("2.45.0".to_string())
+        );
+        assert_eq!(
+            parse_name_version("gh", "gh 2.44.1 2.45.0\n"),
+            Some("2.45.0".to_string())
+        );
+        assert_eq!(parse_name_version("gh", "git 2.44.0\n"), None);
+    }
+
+    #[test]
+    fn test_latest_version_parsers() {
+        let apt_policy = "\
+git:
+  Installed: 1:2.39.2-1.1
+  Candidate: 1:2.43.0-1ubuntu7
+  Version table:
+     1:2.43.0-1ubuntu7 500
+        500 http://archive.ubuntu.com/ubuntu noble/main amd64 Packages
+ *** 1:2.39.2-1.1 100
+        100 /var/lib/dpkg/status
+";
+        assert_eq!(
+            parse_apt_policy("git", apt_policy),
+            Some("2.43.0".to_string())
+        );
+        assert_eq!(
+            parse_apt_policy("gh", "gh:\n  Installed: (none)\n  Candidate: (none)\n"),
+            None
+        );
+
+        let dnf_info = "\
+Installed Packages
+Name         : git
+Version      : 2.43.0
+Release      : 1.fc39
+Architecture : x86_64
+
+Available Packages
+Name         : git
+Version      : 2.44.0
+Release      : 1.fc39
+Architecture : x86_64
+";
+        assert_eq!(parse_rpm_info("git", dnf_info), Some("2.44.0".to_string()));
+
+        let pacman_info = "\
+Repository      : extra
+Name            : github-cli
+Version         : 2.45.0-1
+Description     : The GitHub CLI
+";
+        assert_eq!(
+            parse_pacman_info("github-cli", pacman_info),
+            Some("2.45.0".to_string())
+        );
+
+        let winget_show = "\
+Found GitHub CLI [GitHub.cli]
+Version: 2.45.0
+Publisher: GitHub, Inc.
+Release Date: 2024-03-04
+";
+        assert_eq!(
+            parse_info_version("GitHub.cli", winget_show),
+            Some("2.45.0".to_string())
+        );
+        assert_eq!(
+            parse_info_version("git", "Name        : git\nVersion     : 2.43.0.windows.1\n"),
+            Some("2.43.0.windows.1".to_string())
+        );
+
+        assert_eq!(
+            parse_choco_search("gh", "gh|2.45.0\n"),
+            Some("2.45.0".to_string())
+        );
+        assert_eq!(parse_choco_search("gh", "ghostscript|10.02.1\n"), None);
+
+        let cargo_search = "\
+ripgrep = \"14.1.0\"    # ripgrep is a line-oriented search tool
+... and 112 crates more (use --limit N to see more)
+";
+        assert_eq!(
+            parse_cargo_search("ripgrep", cargo_search),
+            Some("14.1.0".to_string())
+        );
+        assert_eq!(parse_cargo_search("rg", cargo_search), None);
+    }
+
+    #[test]
+    fn test_latest_version_commands() {
+        let registry = BackendRegistry::with_defaults();
+        let latest = |method: &str| {
+            registry
+                .get(method)
+                .unwrap()
+                .latest_version_command(&install(method, Some("pkg")))
+        };
+        for method in [
+            "npm", "pip", "brew", "apt", "yum", "dnf", "pacman", "winget", "choco", "scoop",
+            "cargo", "snap",
+        ] {
+            assert!(latest(method).is_ok(), "{method}");
+        }
+        assert_eq!(
+            latest("apt").unwrap(),
+            ["env", "LC_ALL=C", "apt-cache", "policy", "pkg"]
+        );
+        assert!(matches!(latest("go"), Err(ToolError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_snap_backend() {
+        let registry = BackendRegistry::with_defaults();
+        let snap = registry.get("snap").unwrap();
+
+        let mut install = install("snap", Some("crush"));
+        assert_eq!(
+            snap.install_command(&install).unwrap(),
+            ["sudo", "snap", "install", "crush"]
+        );
+        install.classic = true;
+        assert_eq!(
+            snap.install_command(&install).unwrap(),
+            ["sudo", "snap", "install", "crush", "--classic"]
+        );
+        assert_eq!(
+            snap.update_command(&install).unwrap(),
+            ["sudo", "snap", "refresh", "crush"]
+        );
+        assert_eq!(
+            snap.uninstall_command(&install).unwrap(),
+            ["sudo", "snap", "remove", "crush"]
+        );
+
+        let list = "\
+Name   Version  Rev  Tracking       Publisher  Notes
+crush  0.7.4    42   latest/stable  charm      classic
+";
+        assert_eq!(
+            snap.parse_installed_version(&install, list),
+            Some("0.7.4".to_string())
+        );
+
+        let info = "\
+name:      crush
+summary:   Glamorous AI coding agent
+channels:
+  latest/stable:    0.7.6 2025-09-01 (45) 20MB classic
+  latest/candidate: ^
+  latest/edge:      0.8.0-dev 2025-09-03 (47) 21MB classic
+installed:          0.7.4            (42) 20MB classic
+";
+        assert_eq!(
+            snap.parse_latest_version(&install, info),
+            Some("0.7.6".to_string())
+        );
+        assert_eq!(parse_snap_info("channels:\n  latest/stable: --\n"), None);
+    }
+
+    #[test]
+    fn test_register_custom_backend() {
+        struct Mise;
+        impl InstallBackend for Mise {
+            fn name(&self) -> &str {
+                "mise"
+            }
+            fn install_command(&self, install: &InstallMethod) -> CommandResult {
+                Ok(vec![
+                    "mise".to_string(),
+                    "use".to_string(),
+                    "-g".to_string(),
+                    package_name(install)?.to_string(),
+                ])
+            }
+            fn uninstall_command(&self, install: &InstallMethod) -> CommandResult {
+                Ok(vec![
+                    "mise".to_string(),
+                    "uninstall".to_string(),
+                    package_name(install)?.to_string(),
+                ])
+            }
+            fn update_command(&self, install: &InstallMethod) -> CommandResult {
+                Ok(vec![
+                    "mise".to_string(),
+                    "upgrade".to_string(),
+                    package_name(install)?.to_string(),
+                ])
+            }
+        }
+
+        let mut registry = BackendRegistry::with_defaults();
+        assert!(registry.get("mise").is_err());
+        registry.register(Arc::new(Mise));
+
+        let backend = registry.get("mise").unwrap();
+        assert_eq!(
+            backend
+                .install_command(&install("mise", Some("node")))
+                .unwrap(),
+            ["mise", "use", "-g", "node"]
+        );
+        assert!(matches!(
+            backend.latest_version_command(&install("mise", Some("node"))),
+            Err(ToolError::NotSupported(_))
+        ));
+    }
+}
@@ -0,0 +1,411 @@
+//! `binary` install method: download a release archive, unpack it under the data directory and
+//! link its executable into a managed bin directory
+
+use crate::{download, verify_sha256, InstallMethod, ToolError};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, warn};
+
+pub const BINARY_METHOD: &str = "binary";
+
+/// Fill `{version}`, `{os}` and `{arch}` in a download URL template
+///
+/// `{os}` and `{arch}` use Rust's names for the running platform (`linux`, `macos`, `windows`;
+/// `x86_64`, `aarch64`).
+pub fn render_url(template: &str, version: &str) -> String {
+    template
+        .replace("{version}", version)
+        .replace("{os}", std::env::consts::OS)
+        .replace("{arch}", std::env::consts::ARCH)
+}
+
+/// Make sure a release version names exactly one directory, so it can be joined into store
+/// paths and URLs; versions come from the command line, lockfiles and history
+pub fn check_release_version(version: &str) -> Result<(), ToolError> {
+    let mut components = Path::new(version).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if name == version => Ok(()),
+        _ => Err(ToolError::ParseError(format!(
+            "Invalid release version '{version}'"
+        ))),
+    }
+}
+
+/// File name of a command's executable on this platform
+fn executable_name(command: &str) -> String {
+    if cfg!(windows) {
+        format!("{command}.exe")
+    } else {
+        command.to_string()
+    }
+}
+
+/// Release archives installed by the `binary` method
+///
+/// Each version is unpacked to `<root>/tools/<id>/<version>` and the active one is linked as
+/// `<root>/bin/<command>`. Unpacked versions are kept, so going back to one skips the download.
+#[derive(Debug, Clone)]
+pub struct BinaryStore {
+    root: PathBuf,
+}
+
+impl BinaryStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory holding the links to installed executables
+    pub fn bin_dir(&self) -> PathBuf {
+        self.root.join("bin")
+    }
+
+    pub fn tool_dir(&self, tool_id: &str) -> PathBuf {
+        self.root.join("tools").join(tool_id)
+    }
+
+    pub fn version_dir(&self, tool_id: &str, version: &str) -> PathBuf {
+        self.tool_dir(tool_id).join(version)
+    }
+
+    /// Link that puts a tool's command on PATH
+    pub fn link_path(&self, command: &str) -> PathBuf {
+        self.bin_dir().join(executable_name(command))
+    }
+
+    /// Whether `path` is one of the links or lies inside an unpacked release
+    pub fn contains(&self, path: &Path) -> bool {
+        [self.bin_dir(), self.root.join("tools")].iter().any(|dir| {
+            path.starts_with(dir) || dir.canonicalize().is_ok_and(|real| path.starts_with(real))
+        })
+    }
+
+    /// Put the bin directory first on this process's PATH so commands it starts find the links
+    pub fn add_bin_dir_to_path(&self) {
+        let bin_dir = self.bin_dir();
+        let current = std::env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = std::env::split_paths(&current).collect();
+        if paths.contains(&bin_dir) {
+            return;
+        }
+        paths.insert(0, bin_dir);
+        if let Ok(joined) = std::env::join_paths(paths) {
+            std::env::set_var("PATH", joined);
+        }
+    }
+
+    /// Install `version` of a tool and link its executable, returning the executable's path
+    ///
+    /// The catalog's `sha256` belongs to the release named by its `version`; other versions are
+    /// installed unverified.
+    pub async fn install(
+        &self,
+        tool_id: &str,
+        command: &str,
+        install: &InstallMethod,
+        version: &str,
+    ) -> Result<PathBuf, ToolError> {
+        check_release_version(version)?;
+        let template = install.url.as_deref().ok_or_else(|| {
+            ToolError::ConfigError(format!("{tool_id}: binary install requires url"))
+        })?;
+        let version_dir = self.version_dir(tool_id, version);
+
+        let bytes = if version_dir.is_dir() {
+            debug!(
+                "Reusing {} {} from {}",
+                tool_id,
+                version,
+                version_dir.display()
+            );
+            None
+        } else {
+            let url = render_url(template, version);
+            let bytes = download(&url).await?;
+            match &install.sha256 {
+                Some(expected) if install.version.as_deref() == Some(version) => {
+                    verify_sha256(&bytes, expected).map_err(|e| match e {
+                        ToolError::ChecksumMismatch(detail) => {
+                            ToolError::ChecksumMismatch(format!("{url}: {detail}"))
+                        }
+                        e => e,
+                    })?;
+                }
+                _ => warn!(
+                    "No checksum declared for {} {}; installing unverified",
+                    tool_id, version
+                ),
+            }
+            Some((url, bytes))
+        };
+
+        let store = self.clone();
+        let tool_id = tool_id.to_string();
+        let command = command.to_string();
+        let bin = install.bin.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Some((url, bytes)) = bytes {
+                store.unpack(&tool_id, &url, &bytes, &version_dir, &command)?;
+            }
+            let executable = find_executable(&version_dir, bin.as_deref(), &command)?;
+            store.link(&executable, &command)?;
+            Ok(executable)
+        })
+        .await
+        .map_err(|e| ToolError::InstallationFailed(format!("Install task failed: {e}")))?
+    }
+
+    /// Remove every unpacked version of a tool and its link
+    pub fn uninstall(&self, tool_id: &str, command: &str) -> Result<(), ToolError> {
+        let tool_dir = self.tool_dir(tool_id);
+        let link = self.link_path(command);
+
+        // 只删除指向本工具目录的链接，避免误删同名的其他程序
+        let owned_link = if cfg!(unix) {
+            fs::read_link(&link).is_ok_and(|target| target.starts_with(&tool_dir))
+        } else {
+            link.is_file()
+        };
+        if owned_link {
+            fs::remove_file(&link)?;
+        }
+        if tool_dir.exists() {
+            fs::remove_dir_all(&tool_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Extract a download into `version_dir`, via a staging directory so a failed unpack
+    /// leaves nothing behind
+    fn unpack(
+        &self,
+        tool_id: &str,
+        url: &str,
+        bytes: &[u8],
+        version_dir: &Path,
+        command: &str,
+    ) -> Result<(), ToolError> {
+        let file_name = version_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let staging = self.tool_dir(tool_id).join(format!(".{file_name}.partial"));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging)?;
+
+        let result = extract(url, bytes, &staging, command)
+            .and_then(|()| fs::rename(&staging, version_dir).map_err(ToolError::from));
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        result
+    }
+
+    fn link(&self, executable: &Path, command: &str) -> Result<(), ToolError> {
+        fs::create_dir_all(self.bin_dir())?;
+        let link = self.link_path(command);
+        if link.symlink_metadata().is_ok() {
+            fs::remove_file(&link)?;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = fs::metadata(executable)?.permissions();
+            permissions.set_mode(permissions.mode() | 0o755);
+            fs::set_permissions(executable, permissions)?;
+            std::os::unix::fs::symlink(executable, &link)?;
+        }
+        // Windows 创建符号链接需要管理员权限，直接复制
+        #[cfg(not(unix))]
+        fs::copy(executable, &link)?;
+
+        Ok(())
+    }
+}
+
+/// Unpack `.tar.gz`/`.tgz` and `.zip` archives; anything else is the executable itself
+fn extract(url: &str, bytes: &[u8], dest: &Path, command: &str) -> Result<(), ToolError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        ToolError::InstallationFailed(format!("{url}: invalid archive: {e}"))
+    };
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .to_ascii_lowercase();
+
+    if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+        // tar 会拒绝解压到目标目录之外的条目
+        tar::Archive::new(flate2::read::GzDecoder::new(bytes))
+            .unpack(dest)
+            .map_err(|e| invalid(&e))
+    } else if path.ends_with(".zip") {
+        zip::ZipArchive::new(Cursor::new(bytes))
+            .and_then(|mut archive| archive.extract(dest))
+            .map_err(|e| invalid(&e))
+    } else {
+        fs::write(dest.join(executable_name(command)), bytes).map_err(ToolError::from)
+    }
+}
+
+/// Locate the executable: `bin` if the catalog names one, else the shallowest file named like
+/// the command
+fn find_executable(dir: &Path, bin: Option<&str>, command: &str) -> Result<PathBuf, ToolError> {
+    if let Some(bin) = bin {
+        let relative = Path::new(bin);
+        let contained = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        let path = dir.join(relative);
+        return if contained && path.is_file() {
+            Ok(path)
+        } else {
+            Err(ToolError::InstallationFailed(format!(
+                "'{bin}' not found in the release archive"
+            )))
+        };
+    }
+
+    let name = executable_name(command);
+    let mut pending = VecDeque::from([dir.to_path_buf()]);
+    while let Some(dir) = pending.pop_front() {
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push_back(path);
+            } else if entry.file_name().to_string_lossy() == name {
+                return Ok(path);
+            }
+        }
+    }
+    Err(ToolError::InstallationFailed(format!(
+        "No executable named '{name}' in the release archive (set bin to its path)"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::test_server;
+    use crate::sha256_hex;
+    use std::io::Write;
+
+    fn tar_gz(path: &str, contents: &[u8]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn zip(path: &str, contents: &[u8]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(path, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_render_url() {
+        let url = render_url(
+            "https://example.com/v{version}/demo-{os}-{arch}.tar.gz",
+            "1.2.0",
+        );
+        assert_eq!(
+            url,
+            format!(
+                "https://example.com/v1.2.0/demo-{}-{}.tar.gz",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            )
+        );
+    }
+
+    #[test]
+    fn test_check_release_version() {
+        assert!(check_release_version("1.2.0").is_ok());
+        assert!(check_release_version("v2.0.0-rc.1").is_ok());
+        for version in ["", ".", "..", "../../..", "1.0/../..", "/tmp", "1.0/"] {
+            assert!(check_release_version(version).is_err(), "{version}");
+        }
+    }
+
+    #[test]
+    fn test_install_switch_and_uninstall() {
+        let old_release = tar_gz("demo-1.0/bin/demo", b"#!/bin/sh\necho 1.0\n");
+        let new_release = zip("demo", b"#!/bin/sh\necho 2.0\n");
+        let digest = sha256_hex(&old_release);
+        let base_url = test_server::serve(vec![
+            ("/1.0/demo.tar.gz", 200, old_release),
+            ("/2.0/demo.zip", 200, new_release),
+        ]);
+        let root = std::env::temp_dir().join(format!("cliverge-binary-{}", std::process::id()));
+        let store = BinaryStore::new(&root);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let install = |url: &str, sha256: Option<String>| InstallMethod {
+            method: BINARY_METHOD.to_string(),
+            url: Some(format!("{base_url}/{{version}}/{url}")),
+            version: Some("1.0".to_string()),
+            sha256,
+            ..Default::default()
+        };
+
+        let mismatch = runtime.block_on(store.install(
+            "demo",
+            "demo",
+            &install("demo.tar.gz", Some(sha256_hex(b"other"))),
+            "1.0",
+        ));
+        assert!(matches!(mismatch, Err(ToolError::ChecksumMismatch(_))));
+        assert!(!store.version_dir("demo", "1.0").exists());
+
+        // 版本号不能跳出工具目录
+        let escape = runtime.block_on(store.install(
+            "demo",
+            "demo",
+            &install("demo.tar.gz", None),
+            "../../..",
+        ));
+        assert!(matches!(escape, Err(ToolError::ParseError(_))));
+
+        let executable = runtime
+            .block_on(store.install("demo", "demo", &install("demo.tar.gz", Some(digest)), "1.0"))
+            .unwrap();
+        assert_eq!(
+            executable,
+            store.version_dir("demo", "1.0").join("demo-1.0/bin/demo")
+        );
+        assert!(store.link_path("demo").exists());
+
+        // 未声明校验和的其他版本仍可安装，链接切换到新版本
+        let executable = runtime
+            .block_on(store.install("demo", "demo", &install("demo.zip", None), "2.0"))
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(store.link_path("demo")).unwrap(),
+            fs::read_to_string(&executable).unwrap()
+        );
+
+        // 已解压的版本无需重新下载
+        assert!(runtime
+            .block_on(store.install("demo", "demo", &install("missing.tar.gz", None), "1.0"))
+            .is_ok());
+        assert!(fs::read_to_string(store.link_path("demo"))
+            .unwrap()
+            .contains("1.0"));
+
+        store.uninstall("demo", "demo").unwrap();
+        assert!(!store.tool_dir("demo").exists());
+        assert!(store.link_path("demo").symlink_metadata().is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,22 +1,32 @@
 //! Configuration management for CLIverge
 
-use crate::ConfigError;
+use crate::{BackendRegistry, ConfigError, OutputStream, BINARY_METHOD, CUSTOM_METHOD};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 // 类型别名以减少复杂度警告
 type PlatformCommands = HashMap<String, Vec<String>>;
-type PlatformMethods = HashMap<String, InstallMethod>;
+type PlatformMethods = HashMap<String, MethodCandidates>;
 type ConfigSchema = HashMap<String, ConfigField>;
 type VersionCheckResult<E> = Result<PlatformCommands, E>;
 type UpdateCheckResult<E> = Result<Option<PlatformCommands>, E>;
+type DependencyList = Vec<ToolDependency>;
+type CommandSteps = Vec<Vec<String>>;
+type MethodList = Vec<InstallMethod>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
     pub appearance: AppearanceSettings,
     pub behavior: BehaviorSettings,
     pub paths: PathSettings,
+    #[serde(default)]
+    pub timeouts: TimeoutSettings,
+    #[serde(default)]
+    pub registries: RegistrySettings,
+    /// Install method chosen by the user per tool id, used instead of the catalog order
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub preferred_methods: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +42,38 @@ pub struct BehaviorSettings {
     pub check_interval_minutes: u32,
     pub show_notifications: bool,
     pub auto_refresh_on_startup: bool,
+    /// Status checks run at the same time during a refresh
+    #[serde(default = "default_refresh_concurrency")]
+    pub refresh_concurrency: usize,
+}
+
+fn default_refresh_concurrency() -> usize {
+    8
+}
+
+/// How long commands may run before they are killed, in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutSettings {
+    pub version_check_secs: u64,
+    pub help_secs: u64,
+    pub install_secs: u64,
+}
+
+/// Base URLs of the package registries asked for latest versions; point them at mirrors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySettings {
+    /// npm registry, e.g. `https://registry.npmmirror.com`
+    pub npm: String,
+    /// PyPI, serving the JSON API under `/pypi/<package>/json`
+    pub pypi: String,
+    /// crates.io sparse index
+    pub crates: String,
+    /// Homebrew formula API, serving `/formula/<name>.json`
+    pub homebrew: String,
+    /// GitHub REST API, serving `/repos/<owner>/<name>/releases/latest`
+    pub github: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,14 +103,312 @@ pub struct ToolConfig {
     pub uninstall: Option<PlatformMethods>, // 新增卸载配置
     pub update: Option<PlatformMethods>,    // 新增更新配置
     pub config_schema: Option<ConfigSchema>,
+    /// Catalog tools that must be installed first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: DependencyList,
+    /// How to find the tool's own version in the version check output, instead of guessing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_parse: Option<VersionParse>,
+    /// GitHub repository (`owner/name`) whose releases give the latest version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_repo: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where the tool's own version sits in its version check output
+///
+/// For tools that print other versions too, such as the Node.js or API version they run
+/// against. The output is narrowed step by step: the chosen stream, then either the value at
+/// `json_pointer` or the lines starting with `line_prefix` and the text after `anchor` in them,
+/// and the `group`-th version number found there is taken. Prefix and anchor match case.
+///
+/// ```json
+/// "version_parse": {"line_prefix": "mytool", "anchor": "v", "group": 1}
+/// "version_parse": {"json_pointer": "/client/version"}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionParse {
+    /// Output to read, stdout unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<OutputStream>,
+    /// Only read lines starting with this text, ignoring leading whitespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_prefix: Option<String>,
+    /// Only read what follows this token on a line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// JSON pointer (`/version`) into output that is a JSON document, for `--version --json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_pointer: Option<String>,
+    /// Which version number to take, counting from 1 in output order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+}
+
+impl VersionParse {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let empty = [("line_prefix", &self.line_prefix), ("anchor", &self.anchor)]
+            .into_iter()
+            .find(|(_, value)| value.as_deref() == Some(""));
+        if let Some((field, _)) = empty {
+            return Err(ConfigError::Invalid(format!("{field} must not be empty")));
+        }
+        if let Some(pointer) = &self.json_pointer {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(ConfigError::Invalid(format!(
+                    "json_pointer '{pointer}' must be empty or start with '/'"
+                )));
+            }
+            if self.line_prefix.is_some() || self.anchor.is_some() {
+                return Err(ConfigError::Invalid(
+                    "json_pointer cannot be combined with line_prefix or anchor".to_string(),
+                ));
+            }
+        }
+        if self.group == Some(0) {
+            return Err(ConfigError::Invalid("group counts from 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Prerequisite tool, optionally with a minimum version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolDependency {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+}
+
+impl ToolDependency {
+    /// Parse a comma-separated list such as `node>=20, git`
+    pub fn parse_list(input: &str) -> Result<DependencyList, ConfigError> {
+        input
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let (id, min_version) = match part.split_once(">=") {
+                    Some((id, version)) => (id.trim(), Some(version.trim().to_string())),
+                    None => (part, None),
+                };
+                if id.is_empty() || min_version.as_deref() == Some("") {
+                    return Err(ConfigError::Invalid(format!("Invalid dependency '{part}'")));
+                }
+                Ok(Self {
+                    id: id.to_string(),
+                    min_version,
+                })
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ToolDependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.min_version {
+            Some(version) => write!(f, "{}>={version}", self.id),
+            None => f.write_str(&self.id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InstallMethod {
     pub method: String,
     pub command: Option<Vec<String>>,
     pub url: Option<String>,
     pub package_name: Option<String>,
+    /// Expected SHA-256 of the file at `url`, checked before it is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// snap: install with classic confinement (`--classic`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub classic: bool,
+    /// binary: release installed when no version is pinned, substituted for `{version}` in `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// script: program that runs the downloaded script (defaults to `sh`, PowerShell on Windows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    /// binary: path of the executable inside the archive (defaults to a file named like the command)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin: Option<String>,
+    /// Explicit commands per operation, taking precedence over `command` and the method's backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_command: Option<CommandSequence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uninstall_command: Option<CommandSequence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_command: Option<CommandSequence>,
+}
+
+/// Install methods offered on one platform, in the catalog's order of preference
+///
+/// Written in JSON as one method object or a list of them
+/// (`[{"method": "apt", ...}, {"method": "npm", ...}]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "MethodCandidatesRepr")]
+pub struct MethodCandidates(pub MethodList);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MethodCandidatesRepr {
+    Single(Box<InstallMethod>),
+    List(MethodList),
+}
+
+impl From<MethodCandidatesRepr> for MethodCandidates {
+    fn from(repr: MethodCandidatesRepr) -> Self {
+        match repr {
+            MethodCandidatesRepr::Single(method) => Self(vec![*method]),
+            MethodCandidatesRepr::List(methods) => Self(methods),
+        }
+    }
+}
+
+impl From<InstallMethod> for MethodCandidates {
+    fn from(method: InstallMethod) -> Self {
+        Self(vec![method])
+    }
+}
+
+impl Serialize for MethodCandidates {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.as_slice() {
+            [method] => method.serialize(serializer),
+            methods => methods.serialize(serializer),
+        }
+    }
+}
+
+/// Why `MethodCandidates::select` picked a method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionReason {
+    /// The user chose this method
+    Preferred,
+    /// The first method whose package manager is installed
+    Available,
+    /// No listed package manager is installed, so the first method is used
+    NoneAvailable,
+}
+
+/// The method an operation uses out of a platform's candidates
+#[derive(Debug, Clone)]
+pub struct MethodSelection {
+    pub candidates: MethodList,
+    /// Index of the chosen method in `candidates`
+    pub selected: usize,
+    pub reason: SelectionReason,
+}
+
+impl MethodSelection {
+    pub fn method(&self) -> &InstallMethod {
+        &self.candidates[self.selected]
+    }
+}
+
+impl MethodCandidates {
+    pub fn methods(&self) -> &[InstallMethod] {
+        &self.0
+    }
+
+    /// First method listed
+    pub fn primary(&self) -> Option<&InstallMethod> {
+        self.0.first()
+    }
+
+    /// Pick the `preferred` method when it is listed, otherwise the first one `available`
+    /// accepts, otherwise the first one
+    pub fn select(
+        &self,
+        preferred: Option<&str>,
+        available: impl Fn(&InstallMethod) -> bool,
+    ) -> Option<MethodSelection> {
+        let preferred = preferred.and_then(|preferred| {
+            self.0
+                .iter()
+                .position(|candidate| candidate.method == preferred)
+        });
+        let (selected, reason) = match preferred {
+            Some(index) => (index, SelectionReason::Preferred),
+            None => match self.0.iter().position(available) {
+                Some(index) => (index, SelectionReason::Available),
+                None if self.0.is_empty() => return None,
+                None => (0, SelectionReason::NoneAvailable),
+            },
+        };
+        Some(MethodSelection {
+            candidates: self.0.clone(),
+            selected,
+            reason,
+        })
+    }
+}
+
+/// Commands run in order, stopping at the first failure
+///
+/// Written in JSON as one argv (`["sudo", "rm", "-rf", "/opt/x"]`) or a list of them
+/// (`[["make"], ["sudo", "make", "install"]]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "CommandSequenceRepr")]
+pub struct CommandSequence(pub CommandSteps);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CommandSequenceRepr {
+    Single(Vec<String>),
+    Steps(CommandSteps),
+}
+
+impl From<CommandSequenceRepr> for CommandSequence {
+    fn from(repr: CommandSequenceRepr) -> Self {
+        match repr {
+            CommandSequenceRepr::Single(command) => Self(vec![command]),
+            CommandSequenceRepr::Steps(steps) => Self(steps),
+        }
+    }
+}
+
+impl Serialize for CommandSequence {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.as_slice() {
+            [command] => command.serialize(serializer),
+            steps => steps.serialize(serializer),
+        }
+    }
+}
+
+impl CommandSequence {
+    pub fn steps(&self) -> &CommandSteps {
+        &self.0
+    }
+
+    /// Split one argv into steps at literal `&&` arguments, as older catalogs wrote them
+    pub fn from_argv(argv: &[String]) -> Self {
+        let steps = argv
+            .split(|arg| arg == "&&")
+            .filter(|step| !step.is_empty())
+            .map(<[String]>::to_vec)
+            .collect();
+        Self(steps)
+    }
+
+    /// Parse steps separated by `&&`, each split on whitespace
+    pub fn parse(input: &str) -> Option<Self> {
+        let steps: CommandSteps = input
+            .split("&&")
+            .map(|step| step.split_whitespace().map(str::to_string).collect())
+            .filter(|step: &Vec<String>| !step.is_empty())
+            .collect();
+        (!steps.is_empty()).then_some(Self(steps))
+    }
+}
+
+impl std::fmt::Display for CommandSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps: Vec<String> = self.0.iter().map(|step| step.join(" ")).collect();
+        f.write_str(&steps.join(" && "))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,12 +434,132 @@ impl Default for AppSettings {
                 check_interval_minutes: 30,
                 show_notifications: true,
                 auto_refresh_on_startup: true,
+                refresh_concurrency: default_refresh_concurrency(),
             },
             paths: PathSettings {
                 tools_config_path: "tools.json".to_string(),
                 data_directory: "~/.cliverge".to_string(),
             },
+            timeouts: TimeoutSettings::default(),
+            registries: RegistrySettings::default(),
+            preferred_methods: HashMap::new(),
+        }
+    }
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        Self {
+            version_check_secs: 15,
+            help_secs: 15,
+            // 安装可能需要下载和编译，给足时间
+            install_secs: 30 * 60,
+        }
+    }
+}
+
+impl Default for RegistrySettings {
+    fn default() -> Self {
+        Self {
+            npm: "https://registry.npmjs.org".to_string(),
+            pypi: "https://pypi.org".to_string(),
+            crates: "https://index.crates.io".to_string(),
+            homebrew: "https://formulae.brew.sh/api".to_string(),
+            github: "https://api.github.com".to_string(),
+        }
+    }
+}
+
+impl ToolsConfig {
+    /// Reject `version_parse` blocks that can never match
+    pub fn validate_version_parse(&self) -> Result<(), ConfigError> {
+        for tool in &self.tools {
+            if let Some(rules) = &tool.version_parse {
+                rules.validate().map_err(|e| match e {
+                    ConfigError::Invalid(message) => {
+                        ConfigError::Invalid(format!("{} version_parse: {message}", tool.id))
+                    }
+                    e => e,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject `depends_on` entries naming unknown tools or forming a cycle
+    pub fn validate_dependencies(&self) -> Result<(), ConfigError> {
+        crate::validate_dependencies(&self.tools).map_err(|e| ConfigError::Invalid(e.to_string()))
+    }
+
+    /// Reject install, uninstall and update methods that `registry` cannot carry out
+    pub fn validate_methods(&self, registry: &BackendRegistry) -> Result<(), ConfigError> {
+        for tool in &self.tools {
+            let sections = [
+                ("install", Some(&tool.install)),
+                ("uninstall", tool.uninstall.as_ref()),
+                ("update", tool.update.as_ref()),
+            ];
+            for (section, methods) in sections {
+                for (platform, candidates) in methods.into_iter().flatten() {
+                    let context = format!("{} {section} ({platform})", tool.id);
+                    if candidates.methods().is_empty() {
+                        return Err(ConfigError::Invalid(format!(
+                            "{context}: no install methods listed"
+                        )));
+                    }
+                    for method in candidates.methods() {
+                        Self::validate_method(&context, section, method, registry)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_method(
+        context: &str,
+        section: &str,
+        method: &InstallMethod,
+        registry: &BackendRegistry,
+    ) -> Result<(), ConfigError> {
+        if !registry.is_known_method(&method.method) {
+            return Err(ConfigError::Invalid(format!(
+                "{context}: unknown method '{}'",
+                method.method
+            )));
+        }
+
+        let sequences = [
+            &method.install_command,
+            &method.uninstall_command,
+            &method.update_command,
+        ];
+        let has_empty_step = sequences
+            .into_iter()
+            .flatten()
+            .any(|sequence| sequence.0.is_empty() || sequence.0.iter().any(Vec::is_empty));
+        if has_empty_step {
+            return Err(ConfigError::Invalid(format!(
+                "{context}: empty command in a command sequence"
+            )));
+        }
+
+        if section == "install" && method.method == BINARY_METHOD && method.url.is_none() {
+            return Err(ConfigError::Invalid(format!(
+                "{context}: method 'binary' requires url"
+            )));
+        }
+
+        if section == "install"
+            && method.method == CUSTOM_METHOD
+            && method.install_command.is_none()
+            && method.command.is_none()
+        {
+            return Err(ConfigError::Invalid(format!(
+                "{context}: method 'custom' requires install_command or command"
+            )));
         }
+        Ok(())
     }
 }
 
@@ -155,6 +615,17 @@ impl ConfigManager {
         &self.tools_config
     }
 
+    /// Data directory from settings, with a leading `~` expanded to the home directory
+    pub fn get_data_dir(&self) -> PathBuf {
+        let configured = &self.app_settings.paths.data_directory;
+        match configured.strip_prefix('~') {
+            Some(rest) => dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(rest.trim_start_matches(['/', '\\'])),
+            None => PathBuf::from(configured),
+        }
+    }
+
     pub fn get_tool_config(&self, id: &str) -> Option<&ToolConfig> {
         self.tools_config.tools.iter().find(|t| t.id == id)
     }
@@ -195,13 +666,18 @@ impl ConfigManager {
     async fn load_tools_config(config_dir: &Path) -> Result<ToolsConfig, ConfigError> {
         let tools_path = config_dir.join("tools.json");
 
-        if tools_path.exists() {
+        let tools_config: ToolsConfig = if tools_path.exists() {
             let content = tokio::fs::read_to_string(&tools_path).await?;
-            Ok(serde_json::from_str(&content)?)
+            serde_json::from_str(&content)?
         } else {
             // Load default tools configuration from embedded data or create empty
-            Ok(Self::create_default_tools_config())
-        }
+            Self::create_default_tools_config()
+        };
+
+        tools_config.validate_methods(&BackendRegistry::with_defaults())?;
+        tools_config.validate_dependencies()?;
+        tools_config.validate_version_parse()?;
+        Ok(tools_config)
     }
 
     async fn save_app_settings(&self) -> Result<(), ConfigError> {
@@ -439,6 +915,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_data_dir_expansion() {
+        let mut settings = AppSettings::default();
+        let config_manager = ConfigManager::new_with_settings(settings.clone());
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        assert_eq!(config_manager.get_data_dir(), home.join(".cliverge"));
+
+        settings.paths.data_directory = "/var/lib/cliverge".to_string();
+        let config_manager = ConfigManager::new_with_settings(settings);
+        assert_eq!(
+            config_manager.get_data_dir(),
+            PathBuf::from("/var/lib/cliverge")
+        );
+    }
+
     #[test]
     fn test_new_format_compatibility() {
         // 测试新格式的配置
@@ -481,4 +972,218 @@ mod tests {
             Some(&vec!["upgrade".to_string()])
         );
     }
+
+    #[test]
+    fn test_command_sequences() {
+        let method: InstallMethod = serde_json::from_str(
+            r#"{
+                "method": "script",
+                "uninstall_command": ["sudo", "rm", "-rf", "/usr/local/bin/cursor"],
+                "update_command": [["cursor", "--stop"], ["cursor", "--upgrade"]]
+            }"#,
+        )
+        .expect("Should parse command sequences");
+
+        let uninstall = method.uninstall_command.as_ref().unwrap();
+        assert_eq!(uninstall.steps().len(), 1);
+        assert_eq!(uninstall.to_string(), "sudo rm -rf /usr/local/bin/cursor");
+        let update = method.update_command.as_ref().unwrap();
+        assert_eq!(update.to_string(), "cursor --stop && cursor --upgrade");
+        assert_eq!(
+            CommandSequence::parse(&update.to_string()).as_ref(),
+            Some(update)
+        );
+        assert!(CommandSequence::parse("  ").is_none());
+
+        let argv = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+        let chained = argv(&[
+            "sudo", "apt", "update", "&&", "sudo", "apt", "upgrade", "-y", "gh",
+        ]);
+        assert_eq!(
+            CommandSequence::from_argv(&chained).steps(),
+            &vec![
+                argv(&["sudo", "apt", "update"]),
+                argv(&["sudo", "apt", "upgrade", "-y", "gh"])
+            ]
+        );
+
+        // 单步命令保持扁平数组格式
+        let json = serde_json::to_value(&method).unwrap();
+        assert_eq!(json["uninstall_command"][0], "sudo");
+        assert_eq!(json["update_command"][1][0], "cursor");
+    }
+
+    #[test]
+    fn test_shipped_catalog_commands() {
+        let content = std::fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/tools.json"),
+        )
+        .unwrap();
+        let tools_config: ToolsConfig = serde_json::from_str(&content).unwrap();
+        tools_config
+            .validate_methods(&BackendRegistry::with_defaults())
+            .unwrap();
+        tools_config.validate_dependencies().unwrap();
+
+        for tool in &tools_config.tools {
+            let sections = [
+                Some(&tool.install),
+                tool.uninstall.as_ref(),
+                tool.update.as_ref(),
+            ];
+            for candidates in sections.into_iter().flatten().flat_map(|m| m.values()) {
+                for method in candidates.methods() {
+                    let sequences = [
+                        &method.install_command,
+                        &method.uninstall_command,
+                        &method.update_command,
+                    ];
+                    let steps = sequences
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|sequence| sequence.steps().iter())
+                        .chain(method.command.iter());
+                    for step in steps {
+                        assert!(
+                            !step.iter().any(|arg| arg == "&&"),
+                            "{}: {step:?} chains commands with &&",
+                            tool.id
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_method_candidates() {
+        let single: MethodCandidates =
+            serde_json::from_str(r#"{"method": "apt", "package_name": "gh"}"#).unwrap();
+        assert_eq!(single.methods().len(), 1);
+        assert!(serde_json::to_value(&single).unwrap().is_object());
+
+        let candidates: MethodCandidates = serde_json::from_str(
+            r#"[{"method": "apt", "package_name": "gh"},
+                {"method": "brew", "package_name": "gh"},
+                {"method": "binary", "url": "https://example.com/gh.tar.gz"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&candidates).unwrap()[2]["method"],
+            "binary"
+        );
+
+        let on_path = |method: &InstallMethod| method.method == "brew";
+        let selection = candidates.select(None, on_path).unwrap();
+        assert_eq!(selection.method().method, "brew");
+        assert_eq!(selection.reason, SelectionReason::Available);
+
+        // 用户指定的方式优先，即使其包管理器不在 PATH 上
+        let selection = candidates.select(Some("apt"), on_path).unwrap();
+        assert_eq!(selection.selected, 0);
+        assert_eq!(selection.reason, SelectionReason::Preferred);
+        let selection = candidates.select(Some("snap"), on_path).unwrap();
+        assert_eq!(selection.method().method, "brew");
+
+        let selection = candidates.select(None, |_| false).unwrap();
+        assert_eq!(selection.method().method, "apt");
+        assert_eq!(selection.reason, SelectionReason::NoneAvailable);
+        assert!(MethodCandidates::default().select(None, |_| true).is_none());
+    }
+
+    #[test]
+    fn test_validate_methods() {
+        let tools_config = |install: &str| -> ToolsConfig {
+            serde_json::from_str(&format!(
+                r#"{{"version": "1.0", "tools": [{{
+                    "id": "demo", "name": "Demo", "description": "Demo",
+                    "website": "https://example.com", "command": "demo",
+                    "version_check": ["--version"],
+                    "install": {{"linux": {install}}}
+                }}]}}"#
+            ))
+            .unwrap()
+        };
+        let registry = BackendRegistry::with_defaults();
+
+        assert!(tools_config(r#"{"method": "npm", "package_name": "demo"}"#)
+            .validate_methods(&registry)
+            .is_ok());
+        assert!(tools_config(
+            r#"{"method": "custom", "install_command": [["make"], ["make", "install"]]}"#
+        )
+        .validate_methods(&registry)
+        .is_ok());
+        assert!(matches!(
+            tools_config(r#"{"method": "flatpak", "package_name": "demo"}"#)
+                .validate_methods(&registry),
+            Err(ConfigError::Invalid(message)) if message.contains("unknown method 'flatpak'")
+        ));
+        assert!(tools_config(r#"{"method": "custom"}"#)
+            .validate_methods(&registry)
+            .is_err());
+        assert!(
+            tools_config(r#"{"method": "custom", "install_command": [[]]}"#)
+                .validate_methods(&registry)
+                .is_err()
+        );
+        assert!(tools_config(
+            r#"[{"method": "apt", "package_name": "demo"}, {"method": "npm", "package_name": "demo"}]"#
+        )
+        .validate_methods(&registry)
+        .is_ok());
+        assert!(
+            tools_config(r#"[{"method": "npm"}, {"method": "flatpak"}]"#)
+                .validate_methods(&registry)
+                .is_err()
+        );
+        assert!(tools_config("[]").validate_methods(&registry).is_err());
+    }
+
+    #[test]
+    fn test_validate_dependencies() {
+        let tools_config = |depends_on: &str| -> ToolsConfig {
+            serde_json::from_str(&format!(
+                r#"{{"version": "1.0", "tools": [
+                    {{"id": "node", "name": "Node", "description": "Node",
+                      "website": "https://example.com", "command": "node",
+                      "version_check": ["--version"], "install": {{}},
+                      "depends_on": {depends_on}}},
+                    {{"id": "demo", "name": "Demo", "description": "Demo",
+                      "website": "https://example.com", "command": "demo",
+                      "version_check": ["--version"], "install": {{}},
+                      "depends_on": [{{"id": "node"}}]}}
+                ]}}"#
+            ))
+            .unwrap()
+        };
+
+        assert!(tools_config("[]").validate_dependencies().is_ok());
+        assert!(matches!(
+            tools_config(r#"[{"id": "demo"}]"#).validate_dependencies(),
+            Err(ConfigError::Invalid(message)) if message.contains("cycle")
+        ));
+        assert!(tools_config(r#"[{"id": "missing"}]"#)
+            .validate_dependencies()
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_version_parse() {
+        let rules = |json: &str| -> VersionParse { serde_json::from_str(json).unwrap() };
+
+        assert!(rules(r#"{"line_prefix": "gh", "group": 1}"#)
+            .validate()
+            .is_ok());
+        assert!(rules(r#"{"stream": "stderr", "json_pointer": "/version"}"#)
+            .validate()
+            .is_ok());
+        assert!(rules(r#"{"json_pointer": "version"}"#).validate().is_err());
+        assert!(rules(r#"{"json_pointer": "/v", "anchor": "x"}"#)
+            .validate()
+            .is_err());
+        assert!(rules(r#"{"anchor": ""}"#).validate().is_err());
+        assert!(rules(r#"{"group": 0}"#).validate().is_err());
+        assert!(serde_json::from_str::<VersionParse>(r#"{"stream": "both"}"#).is_err());
+    }
 }
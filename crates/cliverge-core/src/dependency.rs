@@ -0,0 +1,149 @@
+//! Dependency graph between catalog tools (`ToolConfig.depends_on`)
+
+use crate::{ToolConfig, ToolError};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+// 类型别名以减少复杂度警告
+type ToolIds = Vec<String>;
+
+/// Prerequisite that is not installed or older than required
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingDependency {
+    pub tool_id: String,
+    /// Highest minimum version required by any dependent, if any
+    pub min_version: Option<String>,
+    /// `None` when the prerequisite is not installed
+    pub installed_version: Option<String>,
+}
+
+impl fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tool_id)?;
+        if let Some(min) = &self.min_version {
+            write!(f, " >={min}")?;
+        }
+        match &self.installed_version {
+            Some(version) => write!(f, " (found {version})"),
+            None => f.write_str(" (not installed)"),
+        }
+    }
+}
+
+/// Tools to install for `tool_id`, prerequisites first and `tool_id` last
+pub fn install_order(tools: &[ToolConfig], tool_id: &str) -> Result<ToolIds, ToolError> {
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut path = Vec::new();
+    visit(tools, tool_id, &mut path, &mut done, &mut order)?;
+    Ok(order)
+}
+
+/// Check every tool's dependencies for unknown ids and cycles
+pub fn validate_dependencies(tools: &[ToolConfig]) -> Result<(), ToolError> {
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    for tool in tools {
+        visit(tools, &tool.id, &mut Vec::new(), &mut done, &mut order)?;
+    }
+    Ok(())
+}
+
+/// Depth-first post-order walk; `path` holds the tools currently being visited
+fn visit(
+    tools: &[ToolConfig],
+    tool_id: &str,
+    path: &mut ToolIds,
+    done: &mut HashSet<String>,
+    order: &mut ToolIds,
+) -> Result<(), ToolError> {
+    if done.contains(tool_id) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|id| id == tool_id) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(tool_id.to_string());
+        return Err(ToolError::DependencyCycle(cycle.join(" -> ")));
+    }
+
+    let tool = tools
+        .iter()
+        .find(|t| t.id == tool_id)
+        .ok_or_else(|| match path.last() {
+            Some(dependent) => {
+                ToolError::NotFound(format!("Tool {tool_id} required by {dependent} not found"))
+            }
+            None => ToolError::NotFound(format!("Tool {tool_id} not found")),
+        })?;
+
+    path.push(tool_id.to_string());
+    for dependency in &tool.depends_on {
+        visit(tools, &dependency.id, path, done, order)?;
+    }
+    path.pop();
+
+    done.insert(tool_id.to_string());
+    order.push(tool_id.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolDependency;
+
+    fn tool(id: &str, depends_on: &[&str]) -> ToolConfig {
+        let mut config: ToolConfig = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "description": "",
+            "website": "",
+            "command": id,
+            "version_check": ["--version"],
+            "install": {}
+        }))
+        .unwrap();
+        config.depends_on = depends_on
+            .iter()
+            .map(|dep| ToolDependency {
+                id: dep.to_string(),
+                min_version: None,
+            })
+            .collect();
+        config
+    }
+
+    #[test]
+    fn test_install_order() {
+        let tools = vec![
+            tool("gemini-cli", &["node", "git"]),
+            tool("node", &[]),
+            tool("git", &[]),
+            tool("claude-code", &["node"]),
+        ];
+
+        assert_eq!(
+            install_order(&tools, "gemini-cli").unwrap(),
+            vec!["node", "git", "gemini-cli"]
+        );
+        assert_eq!(install_order(&tools, "node").unwrap(), vec!["node"]);
+        assert!(validate_dependencies(&tools).is_ok());
+    }
+
+    #[test]
+    fn test_cycles_and_unknown_dependencies() {
+        let tools = vec![tool("a", &["b"]), tool("b", &["c"]), tool("c", &["a"])];
+        match install_order(&tools, "a") {
+            Err(ToolError::DependencyCycle(cycle)) => assert_eq!(cycle, "a -> b -> c -> a"),
+            other => panic!("expected cycle, got {other:?}"),
+        }
+        assert!(validate_dependencies(&tools).is_err());
+
+        let tools = vec![tool("a", &["missing"])];
+        assert!(matches!(
+            install_order(&tools, "a"),
+            Err(ToolError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,195 @@
+//! HTTP downloads and SHA-256 verification
+
+use crate::ToolError;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::time::Duration;
+
+/// Downloads larger than this are rejected
+const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+// 类型别名以减少复杂度警告
+type Bytes = Vec<u8>;
+
+/// Fetch `url` into memory; plain `http://` URLs are accepted so a local server can stand in
+pub async fn download(url: &str) -> Result<Bytes, ToolError> {
+    let url = url.to_string();
+    tokio::task::spawn_blocking(move || download_blocking(&url))
+        .await
+        .map_err(|e| ToolError::DownloadFailed(format!("Download task failed: {e}")))?
+}
+
+fn download_blocking(url: &str) -> Result<Bytes, ToolError> {
+    let response = http_agent()
+        .get(url)
+        .call()
+        .map_err(|e| http_error(url, e))?;
+    read_body(url, response)
+}
+
+/// HTTP client with CLIverge's timeouts and user agent
+pub(crate) fn http_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .user_agent(concat!("cliverge/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+pub(crate) fn http_error(url: &str, error: ureq::Error) -> ToolError {
+    match error {
+        ureq::Error::Status(code, _) => ToolError::DownloadFailed(format!("{url}: HTTP {code}")),
+        e => ToolError::DownloadFailed(format!("{url}: {e}")),
+    }
+}
+
+/// Response body, refusing anything over the download size limit
+pub(crate) fn read_body(url: &str, response: ureq::Response) -> Result<Bytes, ToolError> {
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| ToolError::DownloadFailed(format!("{url}: {e}")))?;
+    if body.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err(ToolError::DownloadFailed(format!(
+            "{url}: larger than {MAX_DOWNLOAD_BYTES} bytes"
+        )));
+    }
+
+    Ok(body)
+}
+
+/// Lowercase hex SHA-256 digest
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Compare against a digest from the catalog, ignoring case and surrounding whitespace
+pub fn verify_sha256(bytes: &[u8], expected: &str) -> Result<(), ToolError> {
+    let actual = sha256_hex(bytes);
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(ToolError::ChecksumMismatch(format!(
+            "expected sha256 {}, got {actual}",
+            expected.trim()
+        )))
+    }
+}
+
+/// Minimal single-threaded HTTP server for tests: serves fixed `(path, status, body)` routes
+///
+/// Successful responses carry an `ETag` derived from the body, and a request whose
+/// `If-None-Match` matches it gets an empty `304 Not Modified`.
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    pub(crate) type Route = (&'static str, u16, Vec<u8>);
+    /// `"<path> <status>"` for every request served, in order
+    pub(crate) type RequestLog = Arc<Mutex<Vec<String>>>;
+
+    /// Start serving on a free local port and return its base URL
+    pub(crate) fn serve(routes: Vec<Route>) -> String {
+        serve_logged(routes).0
+    }
+
+    /// Like `serve`, also returning the log of requests served
+    pub(crate) fn serve_logged(routes: Vec<Route>) -> (String, RequestLog) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let log = RequestLog::default();
+        let served = Arc::clone(&log);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                // 读完请求头，记下 If-None-Match
+                let mut if_none_match = None;
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok() && header.trim() != "" {
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("if-none-match") {
+                            if_none_match = Some(value.trim().to_string());
+                        }
+                    }
+                    header.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (mut status, mut body) = routes
+                    .iter()
+                    .find(|(route, _, _)| *route == path)
+                    .map(|(_, status, body)| (*status, body.as_slice()))
+                    .unwrap_or((404, b"not found".as_slice()));
+
+                let etag = format!("\"{}\"", &crate::sha256_hex(body)[..16]);
+                if status == 200 && if_none_match.as_deref() == Some(etag.as_str()) {
+                    status = 304;
+                    body = b"";
+                }
+                served.lock().unwrap().push(format!("{path} {status}"));
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(body);
+            }
+        });
+
+        (base_url, log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(verify_sha256(
+            b"abc",
+            " BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD\n"
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_sha256(b"abd", &sha256_hex(b"abc")),
+            Err(ToolError::ChecksumMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_download_from_local_server() {
+        let base_url = test_server::serve(vec![("/install.sh", 200, b"echo hi\n".to_vec())]);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let body = runtime
+            .block_on(download(&format!("{base_url}/install.sh")))
+            .unwrap();
+        assert_eq!(body, b"echo hi\n");
+
+        assert!(matches!(
+            runtime.block_on(download(&format!("{base_url}/missing"))),
+            Err(ToolError::DownloadFailed(message)) if message.contains("404")
+        ));
+    }
+}
@@ -25,6 +25,30 @@ pub enum ToolError {
     #[error("Feature not supported: {0}")]
     NotSupported(String),
 
+    #[error("Missing prerequisites: {0}")]
+    MissingDependencies(String),
+
+    #[error("Dependency cycle: {0}")]
+    DependencyCycle(String),
+
+    #[error("Install script needs review: {0}")]
+    ReviewRequired(String),
+
+    #[error("Download failed: {0}")]
+    DownloadFailed(String),
+
+    #[error("Checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Operation timed out: {0}")]
+    TimedOut(String),
+
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -37,6 +61,12 @@ pub enum ConfigError {
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("TOML parse error: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("TOML serialization error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+
     #[error("Configuration not found: {0}")]
     NotFound(String),
 
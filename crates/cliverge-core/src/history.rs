@@ -0,0 +1,140 @@
+//! Persistent history of previously installed tool versions, used for rollback
+
+use crate::ToolError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const VERSION_HISTORY_FILE: &str = "version_history.json";
+
+/// Older entries beyond this are dropped
+const MAX_ENTRIES_PER_TOOL: usize = 10;
+
+// 类型别名以减少复杂度警告
+type HistoryMap = HashMap<String, Vec<VersionHistoryEntry>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionHistoryEntry {
+    pub version: String,
+    pub replaced_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Versions that were replaced by an update or pinned install, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionHistory {
+    #[serde(default)]
+    tools: HistoryMap,
+}
+
+impl VersionHistory {
+    pub fn load(path: &Path) -> Result<Self, ToolError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ToolError::ConfigError(format!("Failed to read version history: {e}")))?;
+        serde_json::from_str(&content)
+            .map_err(|e| ToolError::ConfigError(format!("Failed to parse version history: {e}")))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ToolError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ToolError::ConfigError(format!("Failed to create data directory: {e}"))
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            ToolError::ConfigError(format!("Failed to serialize version history: {e}"))
+        })?;
+
+        std::fs::write(path, json)
+            .map_err(|e| ToolError::ConfigError(format!("Failed to write version history: {e}")))
+    }
+
+    pub fn entries(&self, tool_id: &str) -> &[VersionHistoryEntry] {
+        self.tools
+            .get(tool_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Remember a version that is about to be replaced
+    pub fn record(&mut self, tool_id: &str, version: &str) {
+        let entries = self.tools.entry(tool_id.to_string()).or_default();
+        if entries.last().map(|e| e.version.as_str()) == Some(version) {
+            return;
+        }
+
+        entries.push(VersionHistoryEntry {
+            version: version.to_string(),
+            replaced_at: chrono::Utc::now(),
+        });
+        if entries.len() > MAX_ENTRIES_PER_TOOL {
+            entries.remove(0);
+        }
+    }
+
+    /// Most recent recorded version that differs from the current one
+    pub fn previous_version(&self, tool_id: &str, current: Option<&str>) -> Option<&str> {
+        self.entries(tool_id)
+            .iter()
+            .rev()
+            .map(|e| e.version.as_str())
+            .find(|v| Some(*v) != current)
+    }
+
+    /// Forget a version that has been rolled back to, along with anything recorded after it
+    pub fn remove_rolled_back(&mut self, tool_id: &str, version: &str) {
+        if let Some(entries) = self.tools.get_mut(tool_id) {
+            if let Some(pos) = entries.iter().rposition(|e| e.version == version) {
+                entries.truncate(pos);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_version_and_rollback() {
+        let mut history = VersionHistory::default();
+        assert_eq!(history.previous_version("gh", Some("2.40.0")), None);
+
+        history.record("gh", "2.38.0");
+        history.record("gh", "2.39.1");
+        history.record("gh", "2.39.1");
+        assert_eq!(history.entries("gh").len(), 2);
+
+        assert_eq!(
+            history.previous_version("gh", Some("2.40.0")),
+            Some("2.39.1")
+        );
+        // An update that did not change the version leaves the current one on top
+        assert_eq!(
+            history.previous_version("gh", Some("2.39.1")),
+            Some("2.38.0")
+        );
+
+        history.remove_rolled_back("gh", "2.39.1");
+        assert_eq!(
+            history.previous_version("gh", Some("2.39.1")),
+            Some("2.38.0")
+        );
+        assert_eq!(history.entries("gh").len(), 1);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = VersionHistory::default();
+        for i in 0..15 {
+            history.record("node", &format!("20.{i}.0"));
+        }
+
+        let entries = history.entries("node");
+        assert_eq!(entries.len(), MAX_ENTRIES_PER_TOOL);
+        assert_eq!(entries[0].version, "20.5.0");
+    }
+}
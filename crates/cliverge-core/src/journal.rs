@@ -0,0 +1,239 @@
+//! Append-only journal (JSONL) of install, uninstall, update and rollback operations
+
+use crate::ToolError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+pub const OPERATION_JOURNAL_FILE: &str = "operations.jsonl";
+
+/// Captured stdout/stderr is cut down to its last bytes, where errors usually are
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+// 类型别名以减少复杂度警告
+type JournalEntries = Vec<JournalEntry>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalOperation {
+    Install,
+    Uninstall,
+    Update,
+    Rollback,
+}
+
+impl JournalOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Install => "install",
+            Self::Uninstall => "uninstall",
+            Self::Update => "update",
+            Self::Rollback => "rollback",
+        }
+    }
+}
+
+/// One executed operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tool_id: String,
+    pub operation: JournalOperation,
+    /// Account that ran the operation
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Exact argv of the last command executed for the operation
+    pub command: Vec<String>,
+    /// `None` when the command could not be started or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub version_before: Option<String>,
+    pub version_after: Option<String>,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+impl JournalEntry {
+    pub fn command_line(&self) -> String {
+        self.command.join(" ")
+    }
+}
+
+/// Filter for reading the journal; the default matches everything
+#[derive(Debug, Clone, Default)]
+pub struct JournalQuery {
+    pub tool_id: Option<String>,
+    pub operation: Option<JournalOperation>,
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum number of entries, newest first
+    pub limit: Option<usize>,
+}
+
+impl JournalQuery {
+    fn matches(&self, entry: &JournalEntry) -> bool {
+        self.tool_id.as_ref().is_none_or(|id| *id == entry.tool_id)
+            && self.operation.is_none_or(|op| op == entry.operation)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OperationJournal {
+    path: PathBuf,
+}
+
+impl OperationJournal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), ToolError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ToolError::ConfigError(format!("Failed to create data directory: {e}"))
+            })?;
+        }
+
+        let mut line = serde_json::to_string(entry).map_err(|e| {
+            ToolError::ConfigError(format!("Failed to serialize journal entry: {e}"))
+        })?;
+        line.push('\n');
+
+        // 单次写入整行，避免并发追加时交错
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| ToolError::ConfigError(format!("Failed to write journal: {e}")))
+    }
+
+    /// Read matching entries, newest first; unreadable lines are skipped
+    pub fn query(&self, query: &JournalQuery) -> Result<JournalEntries, ToolError> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(ToolError::ConfigError(format!(
+                    "Failed to read journal: {e}"
+                )))
+            }
+        };
+
+        let mut entries: JournalEntries = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .filter(|entry| query.matches(entry))
+            .collect();
+
+        entries.reverse();
+        if let Some(limit) = query.limit {
+            entries.truncate(limit);
+        }
+        Ok(entries)
+    }
+}
+
+/// Keep the tail of command output within `MAX_OUTPUT_BYTES`
+pub(crate) fn truncate_output(output: &str) -> String {
+    let output = output.trim_end();
+    if output.len() <= MAX_OUTPUT_BYTES {
+        return output.to_string();
+    }
+
+    let mut start = output.len() - MAX_OUTPUT_BYTES;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("...{}", &output[start..])
+}
+
+/// Name of the account running cliverge
+pub(crate) fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tool_id: &str, operation: JournalOperation) -> JournalEntry {
+        JournalEntry {
+            timestamp: Utc::now(),
+            tool_id: tool_id.to_string(),
+            operation,
+            user: Some("alice".to_string()),
+            command: vec!["npm".to_string(), "install".to_string(), "-g".to_string()],
+            exit_code: Some(0),
+            duration_ms: 1200,
+            version_before: None,
+            version_after: Some("1.0.0".to_string()),
+            success: true,
+            error: None,
+            stdout: String::new(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let path = std::env::temp_dir().join(format!("cliverge-journal-{}", std::process::id()));
+        let journal = OperationJournal::new(path.join(OPERATION_JOURNAL_FILE));
+        assert!(journal.query(&JournalQuery::default()).unwrap().is_empty());
+
+        journal
+            .append(&entry("gh", JournalOperation::Install))
+            .unwrap();
+        journal
+            .append(&entry("node", JournalOperation::Install))
+            .unwrap();
+        journal
+            .append(&entry("gh", JournalOperation::Update))
+            .unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .and_then(|mut file| file.write_all(b"not json\n"))
+            .unwrap();
+
+        let all = journal.query(&JournalQuery::default()).unwrap();
+        let gh = journal
+            .query(&JournalQuery {
+                tool_id: Some("gh".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        std::fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].operation, JournalOperation::Update);
+        assert_eq!(gh.len(), 1);
+        assert_eq!(gh[0].command_line(), "npm install -g");
+    }
+
+    #[test]
+    fn test_truncate_output_keeps_tail() {
+        assert_eq!(truncate_output("ok\n"), "ok");
+
+        let long = format!("{}é{}", "a".repeat(MAX_OUTPUT_BYTES), "error: failed");
+        let truncated = truncate_output(&long);
+        assert!(truncated.starts_with("..."));
+        assert!(truncated.ends_with("error: failed"));
+        assert!(truncated.len() <= MAX_OUTPUT_BYTES + 3);
+    }
+}
@@ -1,15 +1,47 @@
 //! Core engine and business logic for CLIverge
 
+pub mod backend;
+pub mod binary;
 pub mod cache;
 pub mod config;
+pub mod dependency;
+pub mod download;
 pub mod error;
+pub mod history;
+pub mod journal;
+pub mod location;
+pub mod lockfile;
+pub mod manifest;
+pub mod plan;
+pub mod platform;
+pub mod privilege;
+pub mod process;
+pub mod registry;
+pub mod script;
+pub mod semver;
 pub mod tool;
 pub mod version;
 
 // Re-export main types for convenience
+pub use backend::*;
+pub use binary::*;
 pub use cache::*;
 pub use config::*;
+pub use dependency::*;
+pub use download::*;
 pub use error::*;
+pub use history::*;
+pub use journal::*;
+pub use location::*;
+pub use lockfile::*;
+pub use manifest::*;
+pub use plan::*;
+pub use platform::*;
+pub use privilege::*;
+pub use process::*;
+pub use registry::*;
+pub use script::*;
+pub use semver::*;
 pub use tool::*;
 pub use version::*;
 
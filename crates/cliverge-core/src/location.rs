@@ -0,0 +1,244 @@
+//! Where a tool's command resolves on PATH, and which package manager put each copy there
+
+use crate::{find_all_in, find_on_path, BinaryStore, BINARY_METHOD};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// 类型别名以减少复杂度警告
+type LocationList = Vec<CommandLocation>;
+type ManagerMarker = (&'static str, &'static str);
+
+/// Directories a Linux distribution's own packages install commands into
+const SYSTEM_DIRS: &[&str] = &["/usr/bin", "/usr/sbin", "/bin", "/sbin"];
+
+/// Package managers of Linux distributions, tried in order to name the owner of system files
+const NATIVE_MANAGERS: &[&str] = &["apt", "dnf", "yum", "pacman"];
+
+/// Path fragments, lowercase with `/` separators, that give away where a file came from
+const MANAGER_MARKERS: &[ManagerMarker] = &[
+    ("/node_modules/", "npm"),
+    ("/appdata/roaming/npm/", "npm"),
+    ("/cellar/", "brew"),
+    ("/homebrew/", "brew"),
+    ("/linuxbrew/", "brew"),
+    ("/site-packages/", "pip"),
+    ("/.cargo/bin/", "cargo"),
+    ("/snap/", "snap"),
+    ("/winget/", "winget"),
+    ("/scoop/", "scoop"),
+    ("/chocolatey/", "choco"),
+];
+
+/// One copy of a command found on PATH
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandLocation {
+    /// Where PATH lookup finds it
+    pub path: PathBuf,
+    /// The file it resolves to once symlinks are followed
+    pub real_path: PathBuf,
+    /// Install method that put it there, when the location gives it away
+    pub manager: Option<String>,
+}
+
+/// Every copy of a tool's command on PATH, and the install method CLIverge uses for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolLocation {
+    pub method: String,
+    /// In PATH order, so the first one is what runs
+    pub copies: LocationList,
+}
+
+impl ToolLocation {
+    /// Copy that runs when the command is typed
+    pub fn active(&self) -> Option<&CommandLocation> {
+        self.copies.first()
+    }
+
+    /// First copy installed with the tool's install method
+    pub fn managed(&self) -> Option<&CommandLocation> {
+        self.copies
+            .iter()
+            .find(|copy| copy.manager.as_deref() == Some(self.method.as_str()))
+    }
+
+    /// Copy that runs instead of the managed one, if the managed one is not first on PATH
+    pub fn shadowed_by(&self) -> Option<&CommandLocation> {
+        let managed = self.managed()?;
+        self.active().filter(|active| *active != managed)
+    }
+}
+
+/// Every copy of `command` on PATH, in the order a shell would pick them
+///
+/// Entries resolving to the same file, such as `/bin/gh` and `/usr/bin/gh` on systems where
+/// `/bin` links to `/usr/bin`, are listed once.
+pub fn locate_command(command: &str, binaries: &BinaryStore) -> LocationList {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    locate_in(command, std::env::split_paths(&path), binaries)
+}
+
+fn locate_in(
+    command: &str,
+    dirs: impl IntoIterator<Item = PathBuf>,
+    binaries: &BinaryStore,
+) -> LocationList {
+    let native = native_manager();
+    let mut copies: LocationList = Vec::new();
+    for path in find_all_in(command, dirs) {
+        let real_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if copies.iter().any(|copy| copy.real_path == real_path) {
+            continue;
+        }
+        let manager = owning_manager(&path, &real_path, binaries, native);
+        copies.push(CommandLocation {
+            path,
+            real_path,
+            manager,
+        });
+    }
+    copies
+}
+
+/// Install method a file most likely came from
+///
+/// This goes by where the file lives: CLIverge's own release store, tell-tale directories
+/// such as `node_modules` or Homebrew's `Cellar`, the system directories owned by the
+/// distribution's package manager, and finally a Python shebang for pip entry points.
+fn owning_manager(
+    path: &Path,
+    real_path: &Path,
+    binaries: &BinaryStore,
+    native: Option<&str>,
+) -> Option<String> {
+    if binaries.contains(path) || binaries.contains(real_path) {
+        return Some(BINARY_METHOD.to_string());
+    }
+    if let Some(manager) = [real_path, path].into_iter().find_map(marker_manager) {
+        return Some(manager.to_string());
+    }
+    let in_system_dir = real_path
+        .parent()
+        .is_some_and(|dir| SYSTEM_DIRS.iter().any(|system| dir == Path::new(system)));
+    if cfg!(target_os = "linux") && in_system_dir {
+        return native.map(str::to_string);
+    }
+    is_python_script(real_path).then(|| "pip".to_string())
+}
+
+fn marker_manager(path: &Path) -> Option<&'static str> {
+    let normalized = path.to_string_lossy().replace('\\', "/").to_lowercase();
+    MANAGER_MARKERS
+        .iter()
+        .find(|(marker, _)| normalized.contains(marker))
+        .map(|(_, manager)| *manager)
+}
+
+fn native_manager() -> Option<&'static str> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    NATIVE_MANAGERS
+        .iter()
+        .copied()
+        .find(|manager| find_on_path(manager).is_some())
+}
+
+fn is_python_script(path: &Path) -> bool {
+    let mut head = [0u8; 128];
+    let Ok(read) = std::fs::File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    let first_line = head[..read]
+        .split(|&b| b == b'\n')
+        .next()
+        .unwrap_or_default();
+    first_line.starts_with(b"#!") && String::from_utf8_lossy(first_line).contains("python")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    fn executable(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_marker_manager() {
+        let manager = |path: &str| marker_manager(Path::new(path));
+        assert_eq!(
+            manager("/usr/local/lib/node_modules/@google/gemini-cli/dist/index.js"),
+            Some("npm")
+        );
+        assert_eq!(
+            manager("/opt/homebrew/Cellar/gh/2.40.0/bin/gh"),
+            Some("brew")
+        );
+        assert_eq!(
+            manager(r"C:\Users\me\AppData\Roaming\npm\claude.cmd"),
+            Some("npm")
+        );
+        assert_eq!(manager("/home/me/.cargo/bin/rg"), Some("cargo"));
+        assert_eq!(manager("/usr/local/bin/gh"), None);
+    }
+
+    #[test]
+    fn test_locate_finds_every_copy_in_path_order() {
+        let root = std::env::temp_dir().join(format!("cliverge-location-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let binaries = BinaryStore::new(root.join("data"));
+
+        // 托管的 binary 安装被前面 npm 的同名命令遮蔽
+        let npm_dir = root.join("npm/bin");
+        let npm_target = root.join("npm/lib/node_modules/demo/cli.js");
+        executable(&npm_target, "#!/usr/bin/env node\n");
+        fs::create_dir_all(&npm_dir).unwrap();
+        symlink(&npm_target, npm_dir.join("demo")).unwrap();
+
+        let release = binaries.version_dir("demo", "1.0.0").join("demo");
+        executable(&release, "#!/bin/sh\n");
+        fs::create_dir_all(binaries.bin_dir()).unwrap();
+        symlink(&release, binaries.link_path("demo")).unwrap();
+
+        let pip_dir = root.join("home/.local/bin");
+        executable(&pip_dir.join("demo"), "#!/usr/bin/python3\nimport sys\n");
+
+        let dirs = [
+            npm_dir.clone(),
+            root.join("missing"),
+            binaries.bin_dir(),
+            npm_dir.clone(),
+            pip_dir.clone(),
+        ];
+        let copies = locate_in("demo", dirs, &binaries);
+        let managers: Vec<_> = copies.iter().map(|c| c.manager.as_deref()).collect();
+        assert_eq!(managers, [Some("npm"), Some("binary"), Some("pip")]);
+        assert_eq!(copies[0].path, npm_dir.join("demo"));
+        assert_eq!(copies[0].real_path, npm_target.canonicalize().unwrap());
+
+        let location = ToolLocation {
+            method: BINARY_METHOD.to_string(),
+            copies,
+        };
+        assert_eq!(location.managed().unwrap().path, binaries.link_path("demo"));
+        assert_eq!(location.shadowed_by().unwrap().path, npm_dir.join("demo"));
+
+        let location = ToolLocation {
+            method: "npm".to_string(),
+            ..location
+        };
+        assert_eq!(location.shadowed_by(), None);
+        let location = ToolLocation {
+            method: "apt".to_string(),
+            ..location
+        };
+        assert_eq!(location.managed(), None);
+        assert_eq!(location.shadowed_by(), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
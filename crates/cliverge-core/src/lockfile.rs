@@ -0,0 +1,112 @@
+//! Lockfile (`cliverge.lock`) recording the exact resolved version of each tool
+
+use crate::ConfigError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const LOCKFILE_NAME: &str = "cliverge.lock";
+
+/// Current lockfile format version
+pub const LOCKFILE_VERSION: u32 = 1;
+
+const LOCKFILE_HEADER: &str = "# This file is generated by cliverge. Do not edit it by hand.\n\n";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default)]
+    pub tools: BTreeMap<String, LockedTool>,
+}
+
+/// Resolved version and the install method used to obtain it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedTool {
+    pub version: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<String>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        let lockfile: Self = toml::from_str(content)?;
+        if lockfile.version > LOCKFILE_VERSION {
+            return Err(ConfigError::Invalid(format!(
+                "Lockfile version {} is newer than supported version {LOCKFILE_VERSION}",
+                lockfile.version
+            )));
+        }
+        Ok(lockfile)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        std::fs::write(path, self.to_toml_string()?)?;
+        Ok(())
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, ConfigError> {
+        Ok(format!("{LOCKFILE_HEADER}{}", toml::to_string(self)?))
+    }
+
+    /// Find the closest lockfile in `start` or one of its parent directories
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(LOCKFILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let mut lockfile = Lockfile::default();
+        lockfile.tools.insert(
+            "gemini-cli".to_string(),
+            LockedTool {
+                version: "0.1.18".to_string(),
+                method: "npm".to_string(),
+                package_name: Some("@google/gemini-cli".to_string()),
+            },
+        );
+        lockfile.tools.insert(
+            "docker".to_string(),
+            LockedTool {
+                version: "27.1.1".to_string(),
+                method: "script".to_string(),
+                package_name: None,
+            },
+        );
+
+        let content = lockfile.to_toml_string().expect("Should serialize");
+        assert!(content.starts_with("# This file is generated"));
+        assert!(content.contains("[tools.gemini-cli]"));
+
+        let parsed = Lockfile::parse(&content).expect("Should parse");
+        assert_eq!(parsed, lockfile);
+    }
+
+    #[test]
+    fn test_rejects_newer_lockfile_version() {
+        assert!(Lockfile::parse("version = 99\n").is_err());
+        assert!(Lockfile::parse("version = 1\n").unwrap().tools.is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+//! Project manifest (`.cliverge.toml`) listing the tools a project requires
+
+use crate::{ConfigError, Version};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File name looked up in the project directory and its ancestors
+pub const MANIFEST_FILE_NAME: &str = ".cliverge.toml";
+
+/// Tools a project expects, keyed by `ToolConfig.id`
+///
+/// ```toml
+/// [tools]
+/// gh = "*"
+/// node = ">=18"
+/// claude-code = "^1.0"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProjectManifest {
+    #[serde(default)]
+    pub tools: BTreeMap<String, VersionRequirement>,
+}
+
+impl ProjectManifest {
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Find the closest manifest in `start` or one of its parent directories
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(MANIFEST_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    bound: Version,
+}
+
+/// Version constraint such as `*`, `>=18`, `^1.2`, `~0.4.1` or `>=1.0, <2.0`
+///
+/// A bare version (`"20"`, `"1.2"`) matches any version starting with those components.
+/// Versions compare the SemVer way, so a pre-release such as `1.0.0-beta` is lower than
+/// `1.0.0` and satisfies neither `>=1.0.0`, `1.0.0` nor `^1.0.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    raw: String,
+    comparators: Vec<Comparator>,
+}
+
+impl VersionRequirement {
+    pub fn any() -> Self {
+        Self {
+            raw: "*".to_string(),
+            comparators: Vec::new(),
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ConfigError> {
+        let raw = input.trim().to_string();
+        let mut comparators = Vec::new();
+
+        for part in raw.split(',').map(str::trim) {
+            if part.is_empty() {
+                return Err(ConfigError::Invalid(format!(
+                    "Empty version constraint in '{raw}'"
+                )));
+            }
+            if part == "*" {
+                continue;
+            }
+
+            let (op, version) = [
+                (">=", Op::GreaterEq),
+                ("<=", Op::LessEq),
+                (">", Op::Greater),
+                ("<", Op::Less),
+                ("=", Op::Exact),
+                ("^", Op::Caret),
+                ("~", Op::Tilde),
+            ]
+            .iter()
+            .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| (*op, rest)))
+            .unwrap_or((Op::Exact, part));
+
+            let bound = Self::parse_bound(version.trim()).ok_or_else(|| {
+                ConfigError::Invalid(format!("Invalid version constraint '{part}'"))
+            })?;
+            comparators.push(Comparator { op, bound });
+        }
+
+        Ok(Self { raw, comparators })
+    }
+
+    /// Check whether an installed version satisfies every constraint
+    pub fn matches(&self, version: &str) -> bool {
+        if self.comparators.is_empty() {
+            return true;
+        }
+        let Some(version) = Self::parse_installed(version) else {
+            return false;
+        };
+        self.comparators.iter().all(|c| c.matches(&version))
+    }
+
+    /// Whether the version fails only because it is too old, so updating may satisfy it
+    pub fn is_outdated(&self, version: &str) -> bool {
+        let Some(version) = Self::parse_installed(version) else {
+            return false;
+        };
+        let mut failing = self
+            .comparators
+            .iter()
+            .filter(|c| !c.matches(&version))
+            .peekable();
+
+        failing.peek().is_some()
+            && failing.all(|c| !matches!(c.op, Op::Less | Op::LessEq) && version < c.bound)
+    }
+
+    /// Numeric components only (`18`, `v1.2.3`); constraints name no pre-releases
+    fn parse_bound(version: &str) -> Option<Version> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        if version.is_empty() || !version.split('.').all(|p| p.parse::<u64>().is_ok()) {
+            return None;
+        }
+        Version::parse(version)
+    }
+
+    /// Installed version, pre-release included ("v1.2.3-beta" sorts before "1.2.3")
+    fn parse_installed(version: &str) -> Option<Version> {
+        Version::parse(version)
+    }
+}
+
+impl Comparator {
+    fn matches(&self, version: &Version) -> bool {
+        // 预发布版本低于同号的正式版本，因此达不到以该版本为下限的前缀和 ^ 约束
+        let ordering = version.cmp(&self.bound);
+        let (release, parts) = (version.release(), self.bound.release());
+        match self.op {
+            Op::Exact => ordering != Ordering::Less && Self::prefix_eq(release, parts, parts.len()),
+            Op::Greater => ordering == Ordering::Greater,
+            Op::GreaterEq => ordering != Ordering::Less,
+            Op::Less => ordering == Ordering::Less,
+            Op::LessEq => ordering != Ordering::Greater,
+            Op::Caret => {
+                // Components up to and including the first non-zero one must match
+                let fixed = parts
+                    .iter()
+                    .position(|&p| p != 0)
+                    .unwrap_or(parts.len() - 1)
+                    + 1;
+                ordering != Ordering::Less && Self::prefix_eq(release, parts, fixed)
+            }
+            Op::Tilde => {
+                let fixed = parts.len().min(2);
+                ordering != Ordering::Less && Self::prefix_eq(release, parts, fixed)
+            }
+        }
+    }
+
+    fn prefix_eq(version: &[u64], parts: &[u64], len: usize) -> bool {
+        (0..len).all(|i| version.get(i).copied().unwrap_or(0) == parts[i])
+    }
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionRequirement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Outcome of reconciling one manifest entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "reason", rename_all = "snake_case")]
+pub enum SyncAction {
+    /// Installed version already satisfies the requirement
+    Satisfied,
+    /// Tool was missing and has been installed
+    Installed,
+    /// Tool was too old and has been updated
+    Updated,
+    /// Tool is not installed (check only)
+    Missing,
+    /// Installed version is older than required (check only)
+    Outdated,
+    /// Requirement cannot be met automatically and needs attention
+    Unsatisfied(String),
+    /// Install or update command failed
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncEntry {
+    pub tool_id: String,
+    pub requirement: String,
+    pub version_before: Option<String>,
+    pub version_after: Option<String>,
+    #[serde(flatten)]
+    pub action: SyncAction,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub entries: Vec<SyncEntry>,
+}
+
+impl SyncReport {
+    /// True when every tool in the manifest ends up satisfied
+    pub fn is_in_sync(&self) -> bool {
+        self.entries.iter().all(|entry| {
+            matches!(
+                entry.action,
+                SyncAction::Satisfied | SyncAction::Installed | SyncAction::Updated
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionRequirement {
+        VersionRequirement::parse(s).expect("valid requirement")
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = ProjectManifest::parse(
+            r#"
+            [tools]
+            gh = "*"
+            node = ">=18, <23"
+            claude-code = "^1.0"
+            "#,
+        )
+        .expect("Should parse manifest");
+
+        assert_eq!(manifest.tools.len(), 3);
+        assert!(manifest.tools["gh"].matches("unknown"));
+        assert_eq!(manifest.tools["node"].to_string(), ">=18, <23");
+
+        assert!(ProjectManifest::parse("[tools]\nnode = \">=abc\"").is_err());
+        assert!(ProjectManifest::parse("").unwrap().tools.is_empty());
+    }
+
+    #[test]
+    fn test_requirement_matching() {
+        assert!(req("20").matches("v20.11.1"));
+        assert!(!req("20").matches("18.19.0"));
+        assert!(req("=1.2").matches("1.2.9"));
+
+        assert!(req(">=18").matches("18.0.0"));
+        assert!(!req(">18").matches("18.0.0"));
+        assert!(req("<2").matches("1.99.0"));
+        assert!(req(">=1.0, <2.0").matches("1.5.0"));
+        assert!(!req(">=1.0, <2.0").matches("2.0.0"));
+
+        assert!(req("^1.2").matches("1.9.0"));
+        assert!(!req("^1.2").matches("2.0.0"));
+        assert!(!req("^0.4.1").matches("0.5.0"));
+        assert!(req("~0.4.1").matches("0.4.7"));
+        assert!(!req("~0.4.1").matches("0.5.0"));
+
+        // 预发布版本低于同号的正式版本
+        assert!(!req("1.0.0").matches("1.0.0-beta"));
+        assert!(!req(">=1.0.0").matches("1.0.0-beta"));
+        assert!(!req("^1.0").matches("1.0.0-rc.1"));
+        assert!(!req("20").matches("20.0.0-rc1"));
+        assert!(req("1.0").matches("1.0.1-beta"));
+        assert!(req("<2.0").matches("2.0.0-alpha"));
+        assert!(req(">=1.0.0").matches("1.0.1-beta"));
+        assert!(!req(">=1").matches("unknown"));
+    }
+
+    #[test]
+    fn test_requirement_outdated() {
+        assert!(req(">=18").is_outdated("16.20.0"));
+        assert!(req("^2.1").is_outdated("2.0.5"));
+        assert!(!req("^2.1").is_outdated("3.0.0"));
+        assert!(!req("<2").is_outdated("2.1.0"));
+        assert!(!req(">=1").is_outdated("1.0.0"));
+        assert!(req(">=1.0.0").is_outdated("1.0.0-beta"));
+    }
+
+    #[test]
+    fn test_find_manifest_in_ancestors() {
+        let root = std::env::temp_dir().join(format!("cliverge-manifest-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(MANIFEST_FILE_NAME), "[tools]\ngh = \"*\"\n").unwrap();
+
+        let found = ProjectManifest::find(&nested);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found, Some(root.join(MANIFEST_FILE_NAME)));
+    }
+}
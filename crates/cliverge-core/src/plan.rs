@@ -0,0 +1,126 @@
+//! Dry-run plans: what an install, uninstall, update or rollback would execute
+
+use crate::JournalOperation;
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+
+// 类型别名以减少复杂度警告
+type StringVec = Vec<String>;
+type PlanSteps = Vec<PlanStep>;
+
+/// Steps an operation would take, resolved exactly as the operation would resolve them
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationPlan {
+    pub tool_id: String,
+    pub operation: JournalOperation,
+    /// Step sequences tried in order until one succeeds; most operations have a single one
+    pub alternatives: Vec<PlanSteps>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlanStep {
+    /// Run a command; `privileged` when it asks for an administrator password
+    Run {
+        command: StringVec,
+        privileged: bool,
+    },
+    /// Download the installer script, let the user review it, then run it with `interpreter`
+    Script { url: String, interpreter: StringVec },
+    /// Download a release archive and link its executable
+    InstallBinary { url: String, link: PathBuf },
+    /// Delete unpacked releases and the link to them
+    RemoveBinary { path: PathBuf, link: PathBuf },
+}
+
+impl OperationPlan {
+    /// Whether any step would ask for an administrator password
+    pub fn is_privileged(&self) -> bool {
+        self.alternatives.iter().flatten().any(|step| {
+            matches!(
+                step,
+                PlanStep::Run {
+                    privileged: true,
+                    ..
+                }
+            )
+        })
+    }
+}
+
+impl fmt::Display for PlanStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Run { command, .. } => write!(f, "{}", command.join(" ")),
+            Self::Script { url, interpreter } => {
+                write!(
+                    f,
+                    "download {url}, review it, then run it with {}",
+                    interpreter.join(" ")
+                )
+            }
+            Self::InstallBinary { url, link } => {
+                write!(f, "download {url} and link {}", link.display())
+            }
+            Self::RemoveBinary { path, link } => {
+                write!(f, "remove {} and {}", path.display(), link.display())
+            }
+        }
+    }
+}
+
+impl fmt::Display for OperationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}:", self.operation.as_str(), self.tool_id)?;
+        for (index, steps) in self.alternatives.iter().enumerate() {
+            if index > 0 {
+                write!(f, "\n  if that fails:")?;
+            }
+            if steps.is_empty() {
+                write!(f, "\n    (nothing to run)")?;
+            }
+            for step in steps {
+                write!(f, "\n    {step}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: &[&str], privileged: bool) -> PlanStep {
+        PlanStep::Run {
+            command: command.iter().map(|s| s.to_string()).collect(),
+            privileged,
+        }
+    }
+
+    #[test]
+    fn test_plan_display_and_json() {
+        let plan = OperationPlan {
+            tool_id: "gh".to_string(),
+            operation: JournalOperation::Update,
+            alternatives: vec![
+                vec![run(&["gh", "update", "--update"], false)],
+                vec![
+                    run(&["sudo", "apt", "update"], true),
+                    run(&["sudo", "apt", "upgrade", "-y", "gh"], true),
+                ],
+            ],
+        };
+
+        assert!(plan.is_privileged());
+        assert_eq!(
+            plan.to_string(),
+            "update gh:\n    gh update --update\n  if that fails:\n    sudo apt update\n    sudo apt upgrade -y gh"
+        );
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["alternatives"][1][0]["kind"], "run");
+        assert_eq!(json["alternatives"][1][0]["privileged"], true);
+    }
+}
@@ -0,0 +1,159 @@
+//! Platform keys for catalog lookups: operating system, Linux distribution and CPU architecture
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// 类型别名以减少复杂度警告
+type KeyList = Vec<String>;
+
+const OS_RELEASE_PATHS: &[&str] = &["/etc/os-release", "/usr/lib/os-release"];
+
+/// Keys under which the catalog may describe this machine, most specific first
+///
+/// On Linux these are `linux-<id>-<arch>` and `linux-<id>` for the distribution's `ID` and
+/// each of its `ID_LIKE` entries, then `linux-<arch>` and `linux`; Ubuntu on aarch64 gives
+/// `linux-ubuntu-aarch64`, `linux-ubuntu`, `linux-debian-aarch64`, `linux-debian`,
+/// `linux-aarch64`, `linux`. Elsewhere they are `<os>-<arch>` and `<os>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    keys: KeyList,
+}
+
+impl Platform {
+    /// Platform of the running machine, read once
+    pub fn current() -> &'static Self {
+        static CURRENT: OnceLock<Platform> = OnceLock::new();
+        CURRENT.get_or_init(|| {
+            let os_release = if cfg!(target_os = "linux") {
+                OS_RELEASE_PATHS
+                    .iter()
+                    .find_map(|path| std::fs::read_to_string(path).ok())
+            } else {
+                None
+            };
+            Self::new(
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                os_release.as_deref(),
+            )
+        })
+    }
+
+    /// Platform for an operating system and architecture as named by `std::env::consts`,
+    /// with the distribution taken from the contents of an `os-release` file
+    pub fn new(os: &str, arch: &str, os_release: Option<&str>) -> Self {
+        let mut keys = Vec::new();
+        for distribution in os_release.map(distribution_ids).unwrap_or_default() {
+            keys.push(format!("{os}-{distribution}-{arch}"));
+            keys.push(format!("{os}-{distribution}"));
+        }
+        keys.push(format!("{os}-{arch}"));
+        keys.push(os.to_string());
+        keys.dedup();
+        Self { keys }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Most specific key, naming this machine in messages
+    pub fn name(&self) -> &str {
+        &self.keys[0]
+    }
+
+    /// Entry for the most specific key `map` has
+    pub fn lookup<'a, T>(&self, map: &'a HashMap<String, T>) -> Option<&'a T> {
+        self.keys.iter().find_map(|key| map.get(key))
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// `ID` followed by the `ID_LIKE` entries of an os-release file, lowercased
+fn distribution_ids(os_release: &str) -> KeyList {
+    let value = |name: &str| {
+        os_release.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == name).then(|| value.trim().trim_matches(['"', '\'']).to_lowercase())
+        })
+    };
+
+    let mut ids: KeyList = value("ID").into_iter().collect();
+    if let Some(like) = value("ID_LIKE") {
+        for id in like.split_whitespace() {
+            if !ids.iter().any(|existing| existing == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    ids.retain(|id| !id.is_empty());
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UBUNTU: &str = r#"NAME="Ubuntu"
+VERSION_ID="24.04"
+ID=ubuntu
+ID_LIKE=debian
+"#;
+
+    #[test]
+    fn test_platform_keys() {
+        let ubuntu = Platform::new("linux", "aarch64", Some(UBUNTU));
+        assert_eq!(
+            ubuntu.keys(),
+            [
+                "linux-ubuntu-aarch64",
+                "linux-ubuntu",
+                "linux-debian-aarch64",
+                "linux-debian",
+                "linux-aarch64",
+                "linux"
+            ]
+        );
+
+        let rocky = Platform::new(
+            "linux",
+            "x86_64",
+            Some("ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n"),
+        );
+        assert_eq!(rocky.keys()[2], "linux-rhel-x86_64");
+        assert_eq!(rocky.keys()[7], "linux-fedora");
+
+        let macos = Platform::new("macos", "aarch64", None);
+        assert_eq!(macos.keys(), ["macos-aarch64", "macos"]);
+        assert_eq!(macos.to_string(), "macos-aarch64");
+    }
+
+    #[test]
+    fn test_lookup_prefers_most_specific_key() {
+        let platform = Platform::new("linux", "x86_64", Some(UBUNTU));
+        let mut map = HashMap::from([
+            ("linux".to_string(), "generic"),
+            ("windows".to_string(), "other"),
+        ]);
+        assert_eq!(platform.lookup(&map), Some(&"generic"));
+
+        map.insert("linux-debian".to_string(), "debian family");
+        assert_eq!(platform.lookup(&map), Some(&"debian family"));
+        map.insert("linux-ubuntu".to_string(), "ubuntu");
+        assert_eq!(platform.lookup(&map), Some(&"ubuntu"));
+
+        // 架构只在没有发行版专用配置时起作用
+        map.insert("linux-x86_64".to_string(), "x86_64");
+        assert_eq!(platform.lookup(&map), Some(&"ubuntu"));
+        assert_eq!(
+            Platform::new("linux", "x86_64", None).lookup(&map),
+            Some(&"x86_64")
+        );
+        assert_eq!(Platform::new("freebsd", "x86_64", None).lookup(&map), None);
+    }
+}
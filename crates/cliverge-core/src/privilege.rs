@@ -0,0 +1,197 @@
+//! Running `sudo` commands without a terminal: an askpass helper, pkexec, or failing fast
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use tokio::process::Command;
+
+/// Argument that makes the GUI executable act as the askpass helper
+pub const ASKPASS_FLAG: &str = "--askpass";
+/// Script written to the data directory that `SUDO_ASKPASS` points at
+pub const ASKPASS_HELPER_FILE: &str = "askpass.sh";
+
+const SUDO: &str = "sudo";
+const PKEXEC: &str = "pkexec";
+
+/// sudo 在密码错误、未提供密码或需要终端时的提示
+const SUDO_AUTH_MESSAGES: &[&str] = &[
+    "incorrect password",
+    "no password was provided",
+    "a password is required",
+    "a terminal is required",
+    "no askpass program specified",
+    "Sorry, try again",
+];
+/// pkexec 在对话框被关闭 (126) 或未获授权 (127) 时的退出码
+const PKEXEC_AUTH_EXIT_CODES: &[i32] = &[126, 127];
+
+/// How commands starting with `sudo` get their password
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Elevation {
+    /// Run `sudo` as written; it prompts on the controlling terminal
+    #[default]
+    Terminal,
+    /// `sudo -A` with `SUDO_ASKPASS` pointing at a helper that prints the password
+    Askpass(PathBuf),
+    /// `pkexec` instead of `sudo`; the desktop's polkit agent asks for the password
+    Pkexec,
+    /// `sudo -n`: fail at once instead of waiting for a password nobody can type
+    NonInteractive,
+}
+
+impl Elevation {
+    /// Best choice for a process without a terminal: the askpass helper when there is one,
+    /// otherwise pkexec when installed
+    pub fn without_terminal(askpass_helper: Option<PathBuf>) -> Self {
+        match askpass_helper {
+            Some(helper) => Self::Askpass(helper),
+            None if crate::find_on_path(PKEXEC).is_some() => Self::Pkexec,
+            None => Self::NonInteractive,
+        }
+    }
+
+    /// Command that runs `command`, a `sudo` invocation, with this kind of elevation
+    pub fn command(&self, command: &[String]) -> Command {
+        let rest = &command[1..];
+        let mut cmd = match self {
+            Self::Terminal => {
+                let mut cmd = Command::new(&command[0]);
+                cmd.args(rest);
+                cmd
+            }
+            Self::Askpass(helper) => {
+                let mut cmd = Command::new(&command[0]);
+                cmd.arg("-A").args(rest).env("SUDO_ASKPASS", helper);
+                cmd
+            }
+            Self::Pkexec => {
+                let mut cmd = Command::new(PKEXEC);
+                cmd.args(rest);
+                cmd
+            }
+            Self::NonInteractive => {
+                let mut cmd = Command::new(&command[0]);
+                cmd.arg("-n").args(rest);
+                cmd
+            }
+        };
+        // 避免 sudo 的本地化提示导致认证失败无法识别
+        cmd.env("LC_MESSAGES", "C");
+        cmd
+    }
+
+    /// Whether a failed run of `command` failed because no valid password was given
+    pub fn authentication_failed(&self, output: &Output) -> bool {
+        if output.status.success() {
+            return false;
+        }
+        match self {
+            Self::Pkexec => output
+                .status
+                .code()
+                .is_some_and(|code| PKEXEC_AUTH_EXIT_CODES.contains(&code)),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                SUDO_AUTH_MESSAGES
+                    .iter()
+                    .any(|message| stderr.contains(message))
+            }
+        }
+    }
+}
+
+/// Whether `command` needs root through `sudo`
+pub fn is_privileged(command: &[String]) -> bool {
+    command
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .is_some_and(|name| name == SUDO)
+}
+
+/// Write the script `SUDO_ASKPASS` points at, which runs `executable --askpass <prompt>`
+///
+/// sudo only runs askpass programs without arguments, so the flag has to come from a script.
+#[cfg(unix)]
+pub fn write_askpass_helper(dir: &Path, executable: &Path) -> std::io::Result<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(ASKPASS_HELPER_FILE);
+    let quoted = executable.to_string_lossy().replace('\'', r"'\''");
+    std::fs::write(
+        &path,
+        format!("#!/bin/sh\nexec '{quoted}' {ASKPASS_FLAG} \"$@\"\n"),
+    )?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700))?;
+    Ok(path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::argv;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn output(code: i32, stderr: &str) -> Output {
+        Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_elevated_commands() {
+        let command: Vec<String> = ["sudo", "apt", "install", "-y", "gh"]
+            .map(String::from)
+            .to_vec();
+        assert!(is_privileged(&command));
+        assert!(is_privileged(&["/usr/bin/sudo".to_string()]));
+        assert!(!is_privileged(&["npm".to_string(), "sudo".to_string()]));
+
+        assert_eq!(argv(&Elevation::Terminal.command(&command)), command);
+        assert_eq!(
+            argv(&Elevation::Pkexec.command(&command)),
+            ["pkexec", "apt", "install", "-y", "gh"]
+        );
+        assert_eq!(
+            argv(&Elevation::NonInteractive.command(&command)),
+            ["sudo", "-n", "apt", "install", "-y", "gh"]
+        );
+
+        let askpass = Elevation::Askpass(PathBuf::from("/data/askpass.sh")).command(&command);
+        assert_eq!(argv(&askpass), ["sudo", "-A", "apt", "install", "-y", "gh"]);
+        assert!(askpass
+            .as_std()
+            .get_envs()
+            .any(|(key, value)| key == "SUDO_ASKPASS"
+                && value == Some(std::ffi::OsStr::new("/data/askpass.sh"))));
+    }
+
+    #[test]
+    fn test_authentication_failed() {
+        let sudo = Elevation::Askpass(PathBuf::from("/data/askpass.sh"));
+        assert!(sudo.authentication_failed(&output(1, "sudo: 3 incorrect password attempts\n")));
+        assert!(sudo.authentication_failed(&output(1, "sudo: no password was provided\n")));
+        assert!(!sudo.authentication_failed(&output(100, "E: Unable to locate package gh\n")));
+        assert!(!sudo.authentication_failed(&output(0, "")));
+
+        assert!(Elevation::Pkexec.authentication_failed(&output(126, "")));
+        assert!(!Elevation::Pkexec.authentication_failed(&output(100, "")));
+    }
+
+    #[test]
+    fn test_write_askpass_helper() {
+        let dir = std::env::temp_dir().join(format!("cliverge-askpass-{}", std::process::id()));
+        let helper = write_askpass_helper(&dir, Path::new("/bin/echo")).unwrap();
+
+        let output = std::process::Command::new(&helper)
+            .arg("[sudo] password for me:")
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "--askpass [sudo] password for me:\n"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,415 @@
+//! Running external commands: live output, cancellation and time limits
+
+use crate::{JournalOperation, ToolError};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::Notify;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// One line printed by an operation's command, without its line terminator
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub tool_id: String,
+    pub operation: JournalOperation,
+    pub stream: OutputStream,
+    pub text: String,
+}
+
+// 类型别名以减少复杂度警告
+pub type OutputSink = Arc<dyn Fn(OutputLine) + Send + Sync>;
+pub type LineCallback = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+type PipeRead = std::io::Result<Vec<u8>>;
+
+/// Shared flag that stops the commands of an operation; clones cancel together
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kill the running command, if any, and make later commands fail immediately
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel` has been called
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.state.notify.notified();
+            let mut notified = std::pin::pin!(notified);
+            // 先注册再检查标志，避免错过在两者之间发出的通知
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Run `work`, giving up on it with `ToolError::Cancelled` once `cancel` is called
+    pub async fn guard<T>(
+        &self,
+        what: &str,
+        work: impl Future<Output = Result<T, ToolError>>,
+    ) -> Result<T, ToolError> {
+        tokio::select! {
+            result = work => result,
+            () = self.cancelled() => Err(ToolError::Cancelled(what.to_string())),
+        }
+    }
+}
+
+/// Locate `command` the way a shell would, without starting anything
+///
+/// Names containing a path separator are checked as given. On Windows the extensions in
+/// `PATHEXT` are tried as well, so `npm` finds `npm.cmd`.
+pub fn find_on_path(command: &str) -> Option<PathBuf> {
+    find_all_on_path(command).into_iter().next()
+}
+
+/// Every executable `command` names on PATH, in the order a shell would try them
+///
+/// The first entry is what `find_on_path` returns; later ones are shadowed by it.
+pub fn find_all_on_path(command: &str) -> Vec<PathBuf> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    find_all_in(command, std::env::split_paths(&path))
+}
+
+/// `find_all_on_path` over the given directories instead of PATH
+pub(crate) fn find_all_in(command: &str, dirs: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let candidate = Path::new(command);
+    if candidate.components().count() > 1 {
+        return is_executable(candidate)
+            .then(|| candidate.to_path_buf())
+            .into_iter()
+            .collect();
+    }
+
+    let extensions = executable_extensions();
+    dirs.into_iter()
+        .filter_map(|dir| {
+            extensions
+                .iter()
+                .map(|extension| dir.join(format!("{command}{extension}")))
+                .find(|path| is_executable(path))
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn executable_extensions() -> Vec<String> {
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    std::iter::once(String::new())
+        .chain(
+            pathext
+                .split(';')
+                .filter(|e| !e.is_empty())
+                .map(str::to_string),
+        )
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn executable_extensions() -> Vec<String> {
+    vec![String::new()]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Command for `program args`, started without a console window on Windows
+pub fn hidden_command(program: &str, args: &[String]) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", &format!("{} {}", program, args.join(" "))]);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd
+    }
+}
+
+/// Program and arguments of `cmd`, as they would be passed to the operating system
+pub fn argv(cmd: &Command) -> Vec<String> {
+    let cmd = cmd.as_std();
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Run a command to completion like `Command::output`, calling `on_line` for every line of
+/// stdout and stderr as soon as it is printed
+///
+/// The command and everything it started are killed when `cancel` fires or `timeout` passes.
+pub async fn run_command(
+    cmd: &mut Command,
+    on_line: Option<LineCallback>,
+    cancel: &CancellationToken,
+    timeout: Duration,
+) -> Result<Output, ToolError> {
+    let program = format!("{:?}", cmd.as_std().get_program());
+    if cancel.is_cancelled() {
+        return Err(ToolError::Cancelled(program));
+    }
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {e}")))?;
+
+    // 两个管道分别在独立任务中读取，避免任一管道写满时阻塞子进程
+    let stdout = tokio::spawn(read_lines(
+        child.stdout.take(),
+        OutputStream::Stdout,
+        on_line.clone(),
+    ));
+    let stderr = tokio::spawn(read_lines(
+        child.stderr.take(),
+        OutputStream::Stderr,
+        on_line,
+    ));
+
+    let stopped = tokio::select! {
+        status = child.wait() => Ok(status),
+        () = cancel.cancelled() => Err(ToolError::Cancelled(program)),
+        () = tokio::time::sleep(timeout) => Err(ToolError::TimedOut(format!(
+            "{program} did not finish within {}s",
+            timeout.as_secs()
+        ))),
+    };
+    let status = match stopped {
+        Ok(status) => status
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {e}")))?,
+        Err(e) => {
+            if let Some(pid) = child.id() {
+                kill_tree(pid).await;
+            }
+            let _ = child.kill().await;
+            stdout.abort();
+            stderr.abort();
+            return Err(e);
+        }
+    };
+
+    let collect = |joined: Result<PipeRead, tokio::task::JoinError>| {
+        joined
+            .map_err(std::io::Error::other)
+            .and_then(|read| read)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read command output: {e}")))
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout.await)?,
+        stderr: collect(stderr.await)?,
+    })
+}
+
+/// Forward lines from a pipe and return everything read
+async fn read_lines<R: AsyncRead + Unpin>(
+    pipe: Option<R>,
+    stream: OutputStream,
+    on_line: Option<LineCallback>,
+) -> PipeRead {
+    let Some(pipe) = pipe else {
+        return Ok(Vec::new());
+    };
+
+    let mut reader = BufReader::new(pipe);
+    let mut collected = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            break;
+        }
+        collected.extend_from_slice(&line);
+        if let Some(on_line) = &on_line {
+            on_line(
+                stream,
+                String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r']),
+            );
+        }
+    }
+    Ok(collected)
+}
+
+/// Kill a process and all of its descendants
+async fn kill_tree(pid: u32) {
+    let _ = tokio::task::spawn_blocking(move || {
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            let _ = std::process::Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .creation_flags(CREATE_NO_WINDOW)
+                .output();
+        }
+        #[cfg(not(windows))]
+        {
+            // 先收集整棵进程树再统一结束，避免子进程在父进程退出后被 init 接管而漏掉
+            let mut tree = vec![pid];
+            let mut index = 0;
+            while index < tree.len() {
+                tree.extend(child_pids(tree[index]));
+                index += 1;
+            }
+            let pids: Vec<String> = tree.iter().map(u32::to_string).collect();
+            let _ = std::process::Command::new("kill")
+                .arg("-KILL")
+                .args(&pids)
+                .output();
+        }
+    })
+    .await;
+}
+
+#[cfg(not(windows))]
+fn child_pids(pid: u32) -> Vec<u32> {
+    std::process::Command::new("pgrep")
+        .args(["-P", &pid.to_string()])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    const LONG: Duration = Duration::from_secs(30);
+
+    fn shell(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script]);
+        cmd
+    }
+
+    #[test]
+    fn test_run_command_forwards_lines() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&lines);
+        let on_line: LineCallback = Arc::new(move |stream, text| {
+            collected.lock().unwrap().push((stream, text.to_string()));
+        });
+        let mut cmd = shell("echo one; echo oops >&2; printf two; exit 3");
+
+        let output = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(run_command(
+                &mut cmd,
+                Some(on_line),
+                &CancellationToken::new(),
+                LONG,
+            ))
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"one\ntwo");
+        assert_eq!(output.stderr, b"oops\n");
+        let lines = lines.lock().unwrap();
+        assert!(lines.contains(&(OutputStream::Stdout, "one".to_string())));
+        assert!(lines.contains(&(OutputStream::Stdout, "two".to_string())));
+        assert!(lines.contains(&(OutputStream::Stderr, "oops".to_string())));
+    }
+
+    #[test]
+    fn test_find_on_path() {
+        let sh = find_on_path("sh").expect("sh is on PATH");
+        assert!(sh.is_absolute());
+        assert_eq!(find_on_path(sh.to_str().unwrap()), Some(sh.clone()));
+        assert_eq!(find_on_path("cliverge-no-such-command"), None);
+        assert_eq!(find_on_path("/nonexistent/sh"), None);
+        assert_eq!(find_all_on_path("sh").first(), Some(&sh));
+    }
+
+    #[test]
+    fn test_cancel_and_timeout_kill_the_process_tree() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        // 子 shell 里的 sleep 持有输出管道，进程树没有被结束时调用会一直挂起
+        let started = Instant::now();
+        let result = runtime.block_on(run_command(
+            &mut shell("sh -c 'sleep 30' & wait"),
+            None,
+            &CancellationToken::new(),
+            Duration::from_millis(300),
+        ));
+        assert!(matches!(result, Err(ToolError::TimedOut(_))));
+
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            trigger.cancel();
+        });
+        let result = runtime.block_on(run_command(
+            &mut shell("sleep 30 & wait"),
+            None,
+            &cancel,
+            LONG,
+        ));
+        assert!(matches!(result, Err(ToolError::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // 已取消的令牌不再启动新命令
+        let result = runtime.block_on(run_command(&mut shell("true"), None, &cancel, LONG));
+        assert!(matches!(result, Err(ToolError::Cancelled(_))));
+    }
+}
@@ -0,0 +1,479 @@
+//! Latest versions read straight from package registry metadata and GitHub releases
+//!
+//! Asking the npm registry, PyPI, crates.io or the Homebrew API over HTTP is faster than running
+//! `npm view`, `pip index versions` or `brew info`, and works without those tools installed.
+
+use crate::download::{http_agent, http_error, read_body};
+use crate::{find_version, RegistrySettings, ToolError, ToolVersionInfo, Version};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// File in the data directory remembering ETags between runs
+pub const REGISTRY_CACHE_FILE: &str = "registry_cache.json";
+
+// 类型别名以减少复杂度警告
+type CacheEntries = HashMap<String, CachedLatest>;
+type CrateVersion = (Version, String);
+type LatestResult = Result<Option<String>, ToolError>;
+type ReleaseResult = Result<Option<ToolVersionInfo>, ToolError>;
+
+/// Package registry with a metadata API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registry {
+    Npm,
+    PyPi,
+    Crates,
+    Homebrew,
+}
+
+impl Registry {
+    /// Registry holding the packages of an install method
+    pub fn for_method(method: &str) -> Option<Self> {
+        match method {
+            "npm" => Some(Self::Npm),
+            "pip" => Some(Self::PyPi),
+            "cargo" => Some(Self::Crates),
+            "brew" => Some(Self::Homebrew),
+            _ => None,
+        }
+    }
+
+    /// Metadata document of a package
+    pub fn package_url(self, settings: &RegistrySettings, package: &str) -> String {
+        let base = |url: &str| url.trim_end_matches('/').to_string();
+        match self {
+            // 带作用域的包名中的 / 需要转义：@scope%2Fname
+            Self::Npm => format!("{}/{}", base(&settings.npm), package.replace('/', "%2F")),
+            Self::PyPi => format!("{}/pypi/{package}/json", base(&settings.pypi)),
+            Self::Crates => format!("{}/{}", base(&settings.crates), sparse_index_path(package)),
+            Self::Homebrew => format!("{}/formula/{package}.json", base(&settings.homebrew)),
+        }
+    }
+
+    fn accept(self) -> &'static str {
+        match self {
+            // 精简版元数据，比完整文档小得多
+            Self::Npm => "application/vnd.npm.install-v1+json",
+            Self::Crates => "text/plain",
+            Self::PyPi | Self::Homebrew => "application/json",
+        }
+    }
+
+    /// Newest version named in a metadata document
+    fn parse_latest(self, body: &str) -> Option<String> {
+        let pointer = match self {
+            Self::Npm => "/dist-tags/latest",
+            Self::PyPi => "/info/version",
+            Self::Homebrew => "/versions/stable",
+            Self::Crates => return newest_crate_version(body),
+        };
+        let document: serde_json::Value = serde_json::from_str(body).ok()?;
+        document.pointer(pointer)?.as_str().map(str::to_string)
+    }
+}
+
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Npm => "npm registry",
+            Self::PyPi => "PyPI",
+            Self::Crates => "crates.io",
+            Self::Homebrew => "Homebrew API",
+        })
+    }
+}
+
+/// Path of a crate's file in the sparse index: `1/a`, `2/ab`, `3/a/abc`, `se/rd/serde`
+fn sparse_index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Sparse index file, one JSON object per published version: the highest version that is not
+/// yanked, preferring releases over pre-releases
+fn newest_crate_version(body: &str) -> Option<String> {
+    let versions: Vec<CrateVersion> = body
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|entry| !entry["yanked"].as_bool().unwrap_or(false))
+        .filter_map(|entry| {
+            let raw = entry["vers"].as_str()?;
+            Some((Version::parse(raw)?, raw.to_string()))
+        })
+        .collect();
+    versions
+        .iter()
+        .filter(|(version, _)| !version.is_prerelease())
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .or_else(|| versions.iter().max_by(|a, b| a.0.cmp(&b.0)))
+        .map(|(_, raw)| raw.clone())
+}
+
+/// `releases/latest` of the GitHub API: the release's tag as a version, with its date and page
+fn parse_github_release(body: &str) -> Found {
+    let release = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .filter(|release| {
+            let flag = |name: &str| release[name].as_bool().unwrap_or(false);
+            !flag("draft") && !flag("prerelease")
+        })
+        .and_then(|release| {
+            Some(ToolVersionInfo {
+                latest_version: tag_version(release["tag_name"].as_str()?)?,
+                release_date: release["published_at"].as_str()?.parse().ok()?,
+                download_url: None,
+                changelog_url: release["html_url"].as_str().map(str::to_string),
+            })
+        });
+    Found {
+        latest: release
+            .as_ref()
+            .map(|release| release.latest_version.clone()),
+        release,
+    }
+}
+
+/// Version in a release tag: `v2.45.0` -> `2.45.0`, `cli-v1.2.3` -> `1.2.3`, `v20` -> `20`
+fn tag_version(tag: &str) -> Option<String> {
+    match Version::parse(tag) {
+        Some(_) => Some(tag.trim().trim_start_matches(['v', 'V']).to_string()),
+        None => find_version(tag).map(str::to_string),
+    }
+}
+
+/// What a metadata document says about the newest release
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Found {
+    latest: Option<String>,
+    /// Release details, only for GitHub releases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    release: Option<ToolVersionInfo>,
+}
+
+/// Result of a response, kept with the response's ETag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLatest {
+    etag: String,
+    #[serde(flatten)]
+    found: Found,
+}
+
+/// Registry metadata client that sends back ETags, so an unchanged package costs a
+/// `304 Not Modified` instead of the whole document
+#[derive(Debug, Clone)]
+pub struct RegistryClient {
+    settings: RegistrySettings,
+    cache: Arc<Mutex<CacheEntries>>,
+    cache_path: Option<PathBuf>,
+}
+
+impl RegistryClient {
+    /// Client remembering ETags for as long as it lives
+    pub fn new(settings: RegistrySettings) -> Self {
+        Self {
+            settings,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache_path: None,
+        }
+    }
+
+    /// Keep ETags in `path` between runs, starting with the ones already there
+    pub fn with_cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match Self::load_cache(&path) {
+            Ok(entries) => *self.cache.lock().unwrap() = entries,
+            Err(e) => warn!("Ignoring registry cache {}: {}", path.display(), e),
+        }
+        self.cache_path = Some(path);
+        self
+    }
+
+    pub fn settings(&self) -> &RegistrySettings {
+        &self.settings
+    }
+
+    /// Newest published version of `package`, or `None` when the metadata names none
+    pub async fn latest_version(&self, registry: Registry, package: &str) -> LatestResult {
+        let url = registry.package_url(&self.settings, package);
+        self.blocking(move |client| {
+            let request = http_agent().get(&url).set("Accept", registry.accept());
+            let found = client.fetch(request, |body| Found {
+                latest: registry.parse_latest(body),
+                release: None,
+            })?;
+            Ok(found.latest)
+        })
+        .await
+    }
+
+    /// Latest release of a GitHub repository given as `owner/name`, or `None` when the latest
+    /// release is a pre-release or its tag holds no version
+    ///
+    /// A `GITHUB_TOKEN` in the environment is sent along, which raises GitHub's rate limit.
+    pub async fn latest_github_release(&self, repo: &str) -> ReleaseResult {
+        let valid = matches!(repo.split_once('/'), Some((owner, name))
+            if !owner.is_empty() && !name.is_empty() && !name.contains('/'));
+        if !valid {
+            return Err(ToolError::ConfigError(format!(
+                "github_repo must look like owner/name, got '{repo}'"
+            )));
+        }
+
+        let url = format!(
+            "{}/repos/{repo}/releases/latest",
+            self.settings.github.trim_end_matches('/')
+        );
+        self.blocking(move |client| {
+            let mut request = http_agent()
+                .get(&url)
+                .set("Accept", "application/vnd.github+json");
+            if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+            Ok(client.fetch(request, parse_github_release)?.release)
+        })
+        .await
+    }
+
+    /// Run blocking HTTP work off the async runtime
+    async fn blocking<T: Send + 'static>(
+        &self,
+        work: impl FnOnce(Self) -> Result<T, ToolError> + Send + 'static,
+    ) -> Result<T, ToolError> {
+        let client = self.clone();
+        tokio::task::spawn_blocking(move || work(client))
+            .await
+            .map_err(|e| ToolError::DownloadFailed(format!("Registry lookup task failed: {e}")))?
+    }
+
+    /// Send `request` with the ETag of the last response to its URL, and parse the body only
+    /// when it changed
+    fn fetch(
+        &self,
+        mut request: ureq::Request,
+        parse: impl FnOnce(&str) -> Found,
+    ) -> Result<Found, ToolError> {
+        let url = request.url().to_string();
+        let cached = self.cache.lock().unwrap().get(&url).cloned();
+        if let Some(cached) = &cached {
+            request = request.set("If-None-Match", &cached.etag);
+        }
+        let response = request.call().map_err(|e| http_error(&url, e))?;
+
+        if let (304, Some(cached)) = (response.status(), cached) {
+            debug!("{} unchanged, latest {:?}", url, cached.found.latest);
+            return Ok(cached.found);
+        }
+
+        let etag = response.header("ETag").map(str::to_string);
+        let body = read_body(&url, response)?;
+        let found = parse(&String::from_utf8_lossy(&body));
+        if let Some(etag) = etag {
+            self.remember(
+                url,
+                CachedLatest {
+                    etag,
+                    found: found.clone(),
+                },
+            );
+        }
+        Ok(found)
+    }
+
+    fn remember(&self, url: String, entry: CachedLatest) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.insert(url, entry);
+        if let Some(path) = &self.cache_path {
+            if let Err(e) = Self::save_cache(path, &cache) {
+                warn!("Failed to save registry cache: {}", e);
+            }
+        }
+    }
+
+    fn load_cache(path: &Path) -> Result<CacheEntries, ToolError> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| ToolError::ParseError(e.to_string()))
+    }
+
+    fn save_cache(path: &Path, cache: &CacheEntries) -> Result<(), ToolError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json =
+            serde_json::to_string(cache).map_err(|e| ToolError::ParseError(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::test_server;
+
+    fn settings(base_url: &str) -> RegistrySettings {
+        RegistrySettings {
+            npm: format!("{base_url}/npm/"),
+            pypi: format!("{base_url}/pypi-mirror"),
+            crates: format!("{base_url}/index"),
+            homebrew: format!("{base_url}/brew/api"),
+            github: format!("{base_url}/github/"),
+        }
+    }
+
+    #[test]
+    fn test_sparse_index_path() {
+        assert_eq!(sparse_index_path("a"), "1/a");
+        assert_eq!(sparse_index_path("gh"), "2/gh");
+        assert_eq!(sparse_index_path("bat"), "3/b/bat");
+        assert_eq!(sparse_index_path("Ripgrep"), "ri/pg/ripgrep");
+    }
+
+    #[test]
+    fn test_latest_versions_from_each_registry() {
+        let crate_index = [
+            r#"{"name":"ripgrep","vers":"13.0.0","yanked":false}"#,
+            r#"{"name":"ripgrep","vers":"14.1.0","yanked":false}"#,
+            r#"{"name":"ripgrep","vers":"14.2.0","yanked":true}"#,
+            r#"{"name":"ripgrep","vers":"15.0.0-beta.1","yanked":false}"#,
+        ]
+        .join("\n");
+        let base_url = test_server::serve(vec![
+            (
+                "/npm/@anthropic-ai%2Fclaude-code",
+                200,
+                br#"{"name":"@anthropic-ai/claude-code","dist-tags":{"latest":"1.0.51","next":"1.1.0-beta"}}"#.to_vec(),
+            ),
+            (
+                "/pypi-mirror/pypi/httpie/json",
+                200,
+                br#"{"info":{"name":"httpie","version":"3.2.4"},"releases":{}}"#.to_vec(),
+            ),
+            ("/index/ri/pg/ripgrep", 200, crate_index.into_bytes()),
+            (
+                "/brew/api/formula/gh.json",
+                200,
+                br#"{"name":"gh","versions":{"stable":"2.62.0","head":"HEAD"}}"#.to_vec(),
+            ),
+        ]);
+        let client = RegistryClient::new(settings(&base_url));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let latest = |registry, package| {
+            runtime
+                .block_on(client.latest_version(registry, package))
+                .unwrap()
+        };
+
+        assert_eq!(
+            latest(Registry::Npm, "@anthropic-ai/claude-code").as_deref(),
+            Some("1.0.51")
+        );
+        assert_eq!(latest(Registry::PyPi, "httpie").as_deref(), Some("3.2.4"));
+        assert_eq!(
+            latest(Registry::Crates, "ripgrep").as_deref(),
+            Some("14.1.0")
+        );
+        assert_eq!(latest(Registry::Homebrew, "gh").as_deref(), Some("2.62.0"));
+
+        assert!(matches!(
+            runtime.block_on(client.latest_version(Registry::Npm, "no-such-package")),
+            Err(ToolError::DownloadFailed(message)) if message.contains("404")
+        ));
+    }
+
+    #[test]
+    fn test_tag_version() {
+        assert_eq!(tag_version("v2.45.0").as_deref(), Some("2.45.0"));
+        assert_eq!(tag_version("cli-v1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(tag_version("docker-v27.3.1").as_deref(), Some("27.3.1"));
+        assert_eq!(tag_version("v20").as_deref(), Some("20"));
+        assert_eq!(tag_version("nightly"), None);
+    }
+
+    #[test]
+    fn test_latest_github_release() {
+        let base_url = test_server::serve(vec![
+            (
+                "/github/repos/cli/cli/releases/latest",
+                200,
+                br#"{"tag_name":"v2.45.0","name":"GitHub CLI 2.45.0","draft":false,"prerelease":false,
+                    "published_at":"2024-03-04T17:05:23Z","html_url":"https://github.com/cli/cli/releases/tag/v2.45.0"}"#
+                    .to_vec(),
+            ),
+            (
+                "/github/repos/acme/tool/releases/latest",
+                200,
+                br#"{"tag_name":"v3.0.0-rc.1","prerelease":true,"published_at":"2024-03-04T17:05:23Z"}"#
+                    .to_vec(),
+            ),
+        ]);
+        let client = RegistryClient::new(settings(&base_url));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let release = runtime
+            .block_on(client.latest_github_release("cli/cli"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(release.latest_version, "2.45.0");
+        assert_eq!(
+            release.changelog_url.as_deref(),
+            Some("https://github.com/cli/cli/releases/tag/v2.45.0")
+        );
+        assert_eq!(
+            release.release_date.to_rfc3339(),
+            "2024-03-04T17:05:23+00:00"
+        );
+
+        let prerelease = runtime.block_on(client.latest_github_release("acme/tool"));
+        assert!(prerelease.unwrap().is_none());
+        assert!(matches!(
+            runtime.block_on(client.latest_github_release("acme/missing")),
+            Err(ToolError::DownloadFailed(message)) if message.contains("404")
+        ));
+        assert!(matches!(
+            runtime.block_on(client.latest_github_release("https://github.com/cli/cli")),
+            Err(ToolError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_etag_cache_survives_restart() {
+        let (base_url, log) = test_server::serve_logged(vec![(
+            "/npm/gemini",
+            200,
+            br#"{"dist-tags":{"latest":"0.1.9"}}"#.to_vec(),
+        )]);
+        let cache_file =
+            std::env::temp_dir().join(format!("cliverge-registry-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&cache_file);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let client = RegistryClient::new(settings(&base_url)).with_cache_file(&cache_file);
+        let first = runtime.block_on(client.latest_version(Registry::Npm, "gemini"));
+        let second = runtime.block_on(client.latest_version(Registry::Npm, "gemini"));
+        assert_eq!(first.unwrap().as_deref(), Some("0.1.9"));
+        assert_eq!(second.unwrap().as_deref(), Some("0.1.9"));
+
+        // 新的客户端从缓存文件中读取 ETag
+        let restarted = RegistryClient::new(settings(&base_url)).with_cache_file(&cache_file);
+        let third = runtime.block_on(restarted.latest_version(Registry::Npm, "gemini"));
+        assert_eq!(third.unwrap().as_deref(), Some("0.1.9"));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["/npm/gemini 200", "/npm/gemini 304", "/npm/gemini 304"]
+        );
+        std::fs::remove_file(&cache_file).unwrap();
+    }
+}
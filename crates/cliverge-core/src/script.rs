@@ -0,0 +1,216 @@
+//! `script` install method: download an installer script, let the user review it, then run it
+
+use crate::{download, sha256_hex, verify_sha256, InstallMethod, ToolError};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const SCRIPT_METHOD: &str = "script";
+
+/// Installer script downloaded to a temporary file, waiting to be reviewed and run
+#[derive(Debug, Clone)]
+pub struct InstallScript {
+    pub tool_id: String,
+    pub url: String,
+    pub path: PathBuf,
+    /// Program and arguments the script is run with, before its path
+    pub interpreter: Vec<String>,
+    /// Script text as shown for confirmation (invalid UTF-8 is replaced)
+    pub contents: String,
+    /// SHA-256 of the downloaded file
+    pub sha256: String,
+    /// Whether `sha256` was checked against a digest declared in the catalog
+    pub verified: bool,
+}
+
+impl InstallScript {
+    /// Download the script named by a `script` install method, verify its declared checksum
+    /// and save it under `dir` for review
+    ///
+    /// `dir` is created readable only by the current user and the file gets a fresh name, so
+    /// other local users cannot swap the script between review and execution.
+    pub async fn fetch(
+        tool_id: &str,
+        install: &InstallMethod,
+        dir: &Path,
+    ) -> Result<Self, ToolError> {
+        let url = install.url.as_deref().ok_or_else(|| {
+            ToolError::ConfigError(format!("{tool_id}: script install requires url"))
+        })?;
+
+        let bytes = download(url).await?;
+        if let Some(expected) = &install.sha256 {
+            verify_sha256(&bytes, expected).map_err(|e| match e {
+                ToolError::ChecksumMismatch(detail) => {
+                    ToolError::ChecksumMismatch(format!("{url}: {detail}"))
+                }
+                e => e,
+            })?;
+        }
+
+        let path = write_private(dir, tool_id, &bytes)?;
+        Ok(Self {
+            tool_id: tool_id.to_string(),
+            url: url.to_string(),
+            path,
+            interpreter: Self::interpreter(install),
+            contents: String::from_utf8_lossy(&bytes).into_owned(),
+            sha256: sha256_hex(&bytes),
+            verified: install.sha256.is_some(),
+        })
+    }
+
+    /// Interpreter invocation that runs the downloaded file
+    pub fn command(&self) -> Vec<String> {
+        let mut command = self.interpreter.clone();
+        command.push(self.path.display().to_string());
+        command
+    }
+
+    /// Interpreter a method's script is run with, without the script path: the method's
+    /// `interpreter`, otherwise `sh` (PowerShell on Windows)
+    pub fn interpreter(install: &InstallMethod) -> Vec<String> {
+        if let Some(interpreter) = &install.interpreter {
+            return vec![interpreter.clone()];
+        }
+        if cfg!(windows) {
+            [
+                "powershell",
+                "-NoProfile",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+            ]
+            .map(String::from)
+            .to_vec()
+        } else {
+            vec!["sh".to_string()]
+        }
+    }
+
+    /// Make sure the file on disk is still the one that was reviewed
+    pub fn check_unchanged(&self) -> Result<(), ToolError> {
+        let bytes = std::fs::read(&self.path)?;
+        verify_sha256(&bytes, &self.sha256).map_err(|_| {
+            ToolError::ChecksumMismatch(format!(
+                "{} changed after it was reviewed",
+                self.path.display()
+            ))
+        })
+    }
+
+    /// Remove the temporary file
+    pub fn discard(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Create `dir` for the current user only and write `bytes` to a new file in it
+fn write_private(dir: &Path, tool_id: &str, bytes: &[u8]) -> Result<PathBuf, ToolError> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700);
+        builder.create(dir)?;
+        // 目录可能由旧版本以默认权限创建
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    #[cfg(not(unix))]
+    builder.create(dir)?;
+
+    let extension = if cfg!(windows) { "ps1" } else { "sh" };
+    loop {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let path = dir.join(format!(
+            "{tool_id}-{}-{nanos}.{extension}",
+            std::process::id()
+        ));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(bytes)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::test_server;
+
+    fn script_method(url: String, sha256: Option<String>) -> InstallMethod {
+        InstallMethod {
+            method: SCRIPT_METHOD.to_string(),
+            url: Some(url),
+            sha256,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fetch_verifies_checksum() {
+        let body = b"#!/bin/sh\necho installing\n".to_vec();
+        let digest = sha256_hex(&body);
+        let base_url = test_server::serve(vec![("/install.sh", 200, body)]);
+        let url = format!("{base_url}/install.sh");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let dir = std::env::temp_dir().join(format!("cliverge-script-test-{}", std::process::id()));
+
+        let script = runtime
+            .block_on(InstallScript::fetch(
+                "demo",
+                &script_method(url.clone(), Some(digest.to_uppercase())),
+                &dir,
+            ))
+            .unwrap();
+        assert!(script.verified);
+        assert_eq!(script.sha256, digest);
+        assert!(script.contents.contains("echo installing"));
+        assert!(script.check_unchanged().is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&dir), 0o700);
+            assert_eq!(mode(&script.path), 0o600);
+        }
+
+        std::fs::write(&script.path, "echo tampered\n").unwrap();
+        assert!(script.check_unchanged().is_err());
+        script.discard();
+        assert!(!script.path.exists());
+
+        let mismatch = runtime.block_on(InstallScript::fetch(
+            "demo",
+            &script_method(url.clone(), Some(sha256_hex(b"other"))),
+            &dir,
+        ));
+        assert!(matches!(mismatch, Err(ToolError::ChecksumMismatch(_))));
+
+        let mut bash = script_method(url, None);
+        bash.interpreter = Some("bash".to_string());
+        let unverified = runtime
+            .block_on(InstallScript::fetch("demo", &bash, &dir))
+            .unwrap();
+        assert!(!unverified.verified);
+        assert_eq!(unverified.command()[0], "bash");
+        unverified.discard();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,335 @@
+//! Version numbers as tools print them, ordered the SemVer way
+
+use std::cmp::Ordering;
+use std::fmt;
+
+// 类型别名以减少复杂度警告
+type ReleaseParts = Vec<u64>;
+type Identifiers = Vec<Identifier>;
+type Scanned = (Version, usize);
+
+/// A version such as `1.2.3`, `2.43`, `10.0.19045.3803`, `1.0.0-beta.2+exp.5` or `3.13.0rc1`
+///
+/// Release components compare numerically and missing ones count as zero, so `2.43` equals
+/// `2.43.0`. A pre-release sorts before its release; its identifiers compare numerically when
+/// both are numbers and in ASCII order otherwise, with numbers first. A suffix attached without
+/// a hyphen (`rc1`) is a pre-release split into `rc` and `1`. Build metadata is kept for
+/// display but ignored when comparing.
+#[derive(Debug, Clone)]
+pub struct Version {
+    release: ReleaseParts,
+    pre: Identifiers,
+    build: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl Version {
+    /// Parse a whole version string; a leading `v` and any number of release components are
+    /// accepted, so `v20` and `1.2.3.4` parse but `1.2.3 (beta)` does not
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix(['v', 'V']).unwrap_or(input);
+        let (version, len) = Self::scan(input)?;
+        (len == input.len()).then_some(version)
+    }
+
+    pub fn release(&self) -> &[u64] {
+        &self.release
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Longest version at the start of `text`, with its length in bytes
+    fn scan(text: &str) -> Option<Scanned> {
+        let bytes = text.as_bytes();
+        let digits_at = |pos: usize| {
+            bytes[pos.min(bytes.len())..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+        };
+        let alphanumeric_at = |pos: usize| {
+            bytes[pos.min(bytes.len())..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric())
+                .count()
+        };
+
+        let mut release = Vec::new();
+        let mut pos = 0;
+        loop {
+            let len = digits_at(pos);
+            if len == 0 {
+                return None;
+            }
+            release.push(text[pos..pos + len].parse().ok()?);
+            pos += len;
+            if bytes.get(pos) == Some(&b'.') && digits_at(pos + 1) > 0 {
+                pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        let mut pre = Vec::new();
+        match bytes.get(pos) {
+            Some(b'-') if alphanumeric_at(pos + 1) > 0 => {
+                let len = dotted_len(&bytes[pos + 1..]);
+                pre = text[pos + 1..pos + 1 + len]
+                    .split('.')
+                    .map(Identifier::parse)
+                    .collect();
+                pos += 1 + len;
+            }
+            Some(b) if b.is_ascii_alphabetic() => {
+                let len = alphanumeric_at(pos);
+                pre = split_letters_and_digits(&text[pos..pos + len]);
+                pos += len;
+            }
+            _ => {}
+        }
+
+        let mut build = None;
+        if bytes.get(pos) == Some(&b'+') && alphanumeric_at(pos + 1) > 0 {
+            let len = dotted_len(&bytes[pos + 1..]);
+            build = Some(text[pos + 1..pos + 1 + len].to_string());
+            pos += 1 + len;
+        }
+
+        Some((
+            Self {
+                release,
+                pre,
+                build,
+            },
+            pos,
+        ))
+    }
+}
+
+impl Identifier {
+    fn parse(part: &str) -> Self {
+        match part.parse() {
+            Ok(n) if part.bytes().all(|b| b.is_ascii_digit()) => Self::Numeric(n),
+            _ => Self::Alphanumeric(part.to_string()),
+        }
+    }
+}
+
+/// Length of the dot-separated `[0-9A-Za-z-]` identifiers at the start of `bytes`
+fn dotted_len(bytes: &[u8]) -> usize {
+    let is_word = |b: &u8| b.is_ascii_alphanumeric() || *b == b'-';
+    let mut len = 0;
+    loop {
+        let word = bytes[len..].iter().take_while(|b| is_word(b)).count();
+        if word == 0 {
+            // 不包含末尾的点
+            return len.saturating_sub(1);
+        }
+        len += word;
+        if bytes.get(len) == Some(&b'.') {
+            len += 1;
+        } else {
+            return len;
+        }
+    }
+}
+
+/// `rc10` -> `rc`, `10`
+fn split_letters_and_digits(suffix: &str) -> Identifiers {
+    let mut parts = Vec::new();
+    let mut rest = suffix;
+    while let Some(first) = rest.chars().next() {
+        let digit = first.is_ascii_digit();
+        let len = rest
+            .find(|c: char| c.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+        parts.push(Identifier::parse(&rest[..len]));
+        rest = &rest[len..];
+    }
+    parts
+}
+
+/// First version in free text such as `git version 2.43.0.windows.1` or `Python 3.12`
+///
+/// Only numbers with at least two components that do not continue a word count, so `x86_64`
+/// and the `3` in `python3` are skipped. A `v` directly in front is allowed and left out.
+pub fn find_version(text: &str) -> Option<&str> {
+    find_versions(text).next()
+}
+
+/// Every version in free text, in order, as `find_version` finds them
+pub fn find_versions(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    let starts_word = move |pos: usize| {
+        pos == 0 || !(bytes[pos - 1].is_ascii_alphanumeric() || bytes[pos - 1] == b'.')
+    };
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        while pos < bytes.len() {
+            let start = pos;
+            pos += 1;
+            if !bytes[start].is_ascii_digit() {
+                continue;
+            }
+            let after_v =
+                start > 0 && matches!(bytes[start - 1], b'v' | b'V') && starts_word(start - 1);
+            if !(starts_word(start) || after_v) {
+                continue;
+            }
+            if let Some((version, len)) = Version::scan(&text[start..]) {
+                if version.release.len() >= 2 {
+                    pos = start + len;
+                    return Some(&text[start..start + len]);
+                }
+            }
+        }
+        None
+    })
+}
+
+/// Order two version strings, or `None` if either is not a version
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(Version::parse(a)?.cmp(&Version::parse(b)?))
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        let component = |release: &[u64], i: usize| release.get(i).copied().unwrap_or(0);
+        (0..len)
+            .map(|i| component(&self.release, i).cmp(&component(&other.release, i)))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeric(n) => write!(f, "{n}"),
+            Self::Alphanumeric(s) => f.write_str(s),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let release: Vec<String> = self.release.iter().map(u64::to_string).collect();
+        f.write_str(&release.join("."))?;
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self.pre.iter().map(Identifier::to_string).collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).expect("valid version")
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(v("v1.2.3").release(), [1, 2, 3]);
+        assert_eq!(v("20").release(), [20]);
+        assert_eq!(v("10.0.19045.3803").release(), [10, 0, 19045, 3803]);
+        assert_eq!(
+            v("1.0.0-beta.2+exp.sha.5").to_string(),
+            "1.0.0-beta.2+exp.sha.5"
+        );
+        assert_eq!(v("3.13.0rc1").to_string(), "3.13.0-rc.1");
+        assert!(v("1.0.0-alpha").is_prerelease());
+        assert!(!v("1.0.0+build").is_prerelease());
+
+        assert!(Version::parse("").is_none());
+        assert!(Version::parse("unknown").is_none());
+        assert!(Version::parse("1.2.3 (beta)").is_none());
+        assert!(Version::parse("1.2.").is_none());
+    }
+
+    #[test]
+    fn test_semver_ordering() {
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.2",
+            "1.10.0",
+            "2.0.0.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        assert_eq!(v("2.43"), v("2.43.0"));
+        assert_eq!(v("1.0.0+linux"), v("1.0.0+darwin"));
+        assert!(v("3.13.0rc2") < v("3.13.0rc10"));
+        assert_eq!(
+            compare_versions("1.0.0-beta", "1.0.0"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare_versions("unknown", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_find_version() {
+        assert_eq!(find_version("git version 2.43.0.windows.1"), Some("2.43.0"));
+        assert_eq!(find_version("git version 2.43"), Some("2.43"));
+        assert_eq!(find_version("Python 3.12"), Some("3.12"));
+        assert_eq!(find_version("python3 3.12.1"), Some("3.12.1"));
+        assert_eq!(find_version("node v20.11.1"), Some("20.11.1"));
+        assert_eq!(
+            find_version("foo 1.0.0-beta (2024-01-02)"),
+            Some("1.0.0-beta")
+        );
+        assert_eq!(
+            find_version("aws-cli/2.15.0 Python/3.11.6 Linux/6.5.0-1-generic"),
+            Some("2.15.0")
+        );
+        assert_eq!(find_version("release 1.2.3."), Some("1.2.3"));
+        assert_eq!(find_version("x86_64 build 42"), None);
+
+        let versions: Vec<_> = find_versions("mytool 1.4.2 (node v20.11.1, api 2023.10)").collect();
+        assert_eq!(versions, ["1.4.2", "20.11.1", "2023.10"]);
+    }
+}
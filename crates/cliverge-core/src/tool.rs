@@ -1,24 +1,42 @@
 //! Tool management functionality
 
+use crate::journal::{current_user, truncate_output};
 use crate::{
-    ConfigManager, ToolConfig, ToolError, VersionCheckStrategy, VersionChecker, VersionInfo,
+    argv, check_release_version, compare_versions, find_on_path, hidden_command, install_order,
+    is_privileged, locate_command, render_url, run_command, BackendRef, BackendRegistry,
+    BinaryStore, CancellationToken, CommandSequence, ConfigManager, Elevation, InstallMethod,
+    InstallScript, JournalEntry, JournalOperation, JournalQuery, LineCallback, LockedTool,
+    MethodSelection, MissingDependency, OperationJournal, OperationPlan, OutputLine, OutputSink,
+    OutputStream, PlanStep, Platform, ProjectManifest, RegistryClient, SharedBackends, SyncAction,
+    SyncEntry, SyncReport, TimeoutSettings, ToolConfig, ToolError, ToolLocation, Version,
+    VersionCheckStrategy, VersionChecker, VersionHistory, VersionHistoryEntry, VersionInfo,
+    VersionRequirement, BINARY_METHOD, CUSTOM_METHOD, OPERATION_JOURNAL_FILE, REGISTRY_CACHE_FILE,
+    SCRIPT_METHOD, VERSION_HISTORY_FILE,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tracing::{debug, warn};
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
-
-#[cfg(windows)]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 // 类型别名以减少复杂度警告
 type StatusCache = Arc<Mutex<HashMap<String, ToolStatus>>>;
 type ToolList = Vec<ToolInfo>;
 type StringVec = Vec<String>;
+type JournalEntries = Vec<JournalEntry>;
+type MissingDependencies = Vec<MissingDependency>;
+type DependencyRequirements = Vec<(String, Option<String>)>;
+type OutputSinkSlot = Arc<Mutex<Option<OutputSink>>>;
+type StatusResults = Vec<(String, Result<ToolStatus, ToolError>)>;
+type UpdateCheckResults = Vec<(String, Result<bool, ToolError>)>;
+type OperationConfig = (ToolConfig, InstallMethod, bool);
+type PreparedCommand = (Command, Option<Elevation>);
+type PlanSteps = Vec<PlanStep>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ToolStatus {
@@ -28,6 +46,7 @@ pub enum ToolStatus {
     Error(String),
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for ToolStatus {
     fn default() -> Self {
         Self::Unknown
@@ -42,22 +61,171 @@ pub struct ToolInfo {
     pub user_config: HashMap<String, serde_json::Value>,
 }
 
+/// Progress of one tool during `ToolManager::refresh_statuses`
+#[derive(Debug, Clone)]
+pub struct RefreshEvent {
+    pub tool_id: String,
+    pub stage: RefreshStage,
+    /// Tools whose check has ended, out of `total`
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum RefreshStage {
+    /// The version check command is running
+    Checking,
+    /// The command is not on PATH, so the tool is not installed and nothing was started
+    NotOnPath,
+    Finished(ToolStatus),
+    Failed(String),
+}
+
+/// How an install, uninstall or update is carried out
+enum OperationCommands {
+    /// Commands run in order
+    Steps(Vec<StringVec>),
+    /// Download the install script at `url` and run it
+    Script,
+    /// Unpack a release archive into the binary store (or remove it)
+    Binary,
+}
+
+/// What an operation executed, collected for the journal
+struct OperationRecord {
+    tool_id: String,
+    operation: JournalOperation,
+    started: Instant,
+    command: StringVec,
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    version_before: Option<String>,
+}
+
+impl OperationRecord {
+    fn start(tool_id: &str, operation: JournalOperation) -> Self {
+        Self {
+            tool_id: tool_id.to_string(),
+            operation,
+            started: Instant::now(),
+            command: Vec::new(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            version_before: None,
+        }
+    }
+
+    fn capture(&mut self, output: &std::process::Output) {
+        self.exit_code = output.status.code();
+        self.stdout = truncate_output(&String::from_utf8_lossy(&output.stdout));
+        self.stderr = truncate_output(&String::from_utf8_lossy(&output.stderr));
+    }
+}
+
 #[derive(Clone)]
 pub struct ToolManager {
     config_manager: Arc<Mutex<ConfigManager>>,
     version_checker: VersionChecker,
     status_cache: StatusCache,
+    version_history: Arc<Mutex<VersionHistory>>,
+    version_history_path: PathBuf,
+    journal: OperationJournal,
+    backends: SharedBackends,
+    binaries: BinaryStore,
+    /// Where install scripts wait for review, readable only by the current user
+    scripts_dir: PathBuf,
+    output_sink: OutputSinkSlot,
+    elevation: Arc<Mutex<Elevation>>,
+    cancel: CancellationToken,
 }
 
 impl ToolManager {
     pub fn new(config_manager: Arc<Mutex<ConfigManager>>) -> Self {
+        let data_dir = config_manager.lock().unwrap().get_data_dir();
+        let version_history_path = data_dir.join(VERSION_HISTORY_FILE);
+        let version_history = VersionHistory::load(&version_history_path).unwrap_or_else(|e| {
+            warn!("Failed to load version history: {}", e);
+            VersionHistory::default()
+        });
+
+        let (version_timeout, registries) = {
+            let config_manager = config_manager.lock().unwrap();
+            let settings = config_manager.get_app_settings();
+            (
+                settings.timeouts.version_check_secs,
+                settings.registries.clone(),
+            )
+        };
+        let backends: SharedBackends = Arc::new(Mutex::new(BackendRegistry::with_defaults()));
+        let binaries = BinaryStore::new(&data_dir);
+        binaries.add_bin_dir_to_path();
+
         Self {
             config_manager,
-            version_checker: VersionChecker::new(),
+            version_checker: VersionChecker::with_backends(backends.clone())
+                .with_timeout(Duration::from_secs(version_timeout))
+                .with_registry(
+                    RegistryClient::new(registries)
+                        .with_cache_file(data_dir.join(REGISTRY_CACHE_FILE)),
+                ),
             status_cache: Arc::new(Mutex::new(HashMap::new())),
+            version_history: Arc::new(Mutex::new(version_history)),
+            version_history_path,
+            journal: OperationJournal::new(data_dir.join(OPERATION_JOURNAL_FILE)),
+            backends,
+            binaries,
+            scripts_dir: data_dir.join("scripts"),
+            output_sink: Arc::new(Mutex::new(None)),
+            elevation: Arc::new(Mutex::new(Elevation::default())),
+            cancel: CancellationToken::new(),
         }
     }
 
+    /// A handle on the same tools, caches and journal whose commands stop when `cancel` fires
+    pub fn with_cancellation(&self, cancel: CancellationToken) -> Self {
+        let mut manager = self.clone();
+        manager.version_checker = manager.version_checker.with_cancellation(cancel.clone());
+        manager.cancel = cancel;
+        manager
+    }
+
+    /// Receive install, uninstall and update output line by line while commands run
+    pub fn set_output_sink(&self, sink: impl Fn(OutputLine) + Send + Sync + 'static) {
+        *self.output_sink.lock().unwrap() = Some(Arc::new(sink));
+    }
+
+    /// Choose how `sudo` commands get a password; by default sudo prompts on the terminal
+    pub fn set_elevation(&self, elevation: Elevation) {
+        *self.elevation.lock().unwrap() = elevation;
+    }
+
+    /// Directory where `binary` installs link their executables; users add it to PATH
+    pub fn managed_bin_dir(&self) -> PathBuf {
+        self.binaries.bin_dir()
+    }
+
+    /// Register an install backend for a new method, or replace a built-in one
+    pub fn register_backend(&self, backend: BackendRef) {
+        self.backends.lock().unwrap().register(backend);
+    }
+
+    /// Install methods with a registered backend
+    pub fn backend_methods(&self) -> StringVec {
+        let backends = self.backends.lock().unwrap();
+        backends.methods().into_iter().map(str::to_string).collect()
+    }
+
+    /// Whether `method` can be used in a catalog entry (`script`, `custom` or a backend)
+    pub fn supports_method(&self, method: &str) -> bool {
+        self.backends.lock().unwrap().is_known_method(method)
+    }
+
+    fn backend(&self, method: &str) -> Result<BackendRef, ToolError> {
+        self.backends.lock().unwrap().get(method)
+    }
+
     /// Get all available tools with full status checking (blocking)
     pub async fn get_all_tools(&self) -> Result<Vec<ToolInfo>, ToolError> {
         let tools_config = {
@@ -137,17 +305,12 @@ impl ToolManager {
                 .clone()
         };
 
-        let status = if self.is_tool_installed(&tool_config).await {
-            match self.get_tool_version(&tool_config).await {
-                Ok(version) => ToolStatus::Installed { version },
-                Err(e) => {
-                    warn!("Failed to get version for {}: {}", tool_id, e);
-                    ToolStatus::Error("Version check failed".to_string())
-                }
-            }
-        } else {
-            ToolStatus::NotInstalled
-        };
+        let status = self.probe_status(&tool_config).await?;
+
+        // 被取消的检查结果不可信，不写入缓存
+        if self.cancel.is_cancelled() {
+            return Err(ToolError::Cancelled(format!("status check of {tool_id}")));
+        }
 
         // Cache the status
         if let Ok(mut cache) = self.status_cache.lock() {
@@ -159,28 +322,21 @@ impl ToolManager {
 
     /// Install a tool
     pub async fn install_tool(&self, tool_id: &str) -> Result<(), ToolError> {
-        debug!("Installing tool: {}", tool_id);
-
-        let (tool_config, install_config) = {
-            let config_manager = self.config_manager.lock().unwrap();
-            let tool_config = config_manager
-                .get_tool_config(tool_id)
-                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
-                .clone();
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Install);
+        let result = self.perform_install(tool_id, &mut record).await;
+        self.journal_operation(record, &result).await;
+        result
+    }
 
-            let platform = std::env::consts::OS;
-            let install_config = tool_config
-                .install
-                .get(platform)
-                .ok_or_else(|| {
-                    ToolError::NotSupported(format!(
-                        "Platform {platform} not supported for {tool_id}"
-                    ))
-                })?
-                .clone();
+    async fn perform_install(
+        &self,
+        tool_id: &str,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        debug!("Installing tool: {}", tool_id);
 
-            (tool_config, install_config)
-        };
+        let (tool_config, install_config, _) =
+            self.operation_config(tool_id, JournalOperation::Install)?;
 
         // Check if already installed
         if self.is_tool_installed(&tool_config).await {
@@ -188,14 +344,13 @@ impl ToolManager {
             return Ok(());
         }
 
+        self.ensure_dependencies(tool_id).await?;
+
         // Execute installation command
-        if let Some(command) = &install_config.command {
-            self.execute_install_command(command).await?;
-        } else {
-            // Fallback: construct command from method and package_name
-            let command = self.construct_install_command(&install_config)?;
-            self.execute_install_command(&command).await?;
-        }
+        let commands =
+            self.operation_commands(tool_id, JournalOperation::Install, &install_config, true)?;
+        self.run_operation_commands(tool_id, commands, &install_config, record)
+            .await?;
 
         // Clear status cache to force re-check
         if let Ok(mut cache) = self.status_cache.lock() {
@@ -206,262 +361,486 @@ impl ToolManager {
         Ok(())
     }
 
-    /// Construct install command from method and package name
-    fn construct_install_command(
+    /// Whether the tool installs on this platform by running a downloaded script
+    pub fn uses_install_script(&self, tool_id: &str) -> bool {
+        self.platform_install_config(tool_id).is_ok_and(|install| {
+            matches!(
+                self.operation_commands(tool_id, JournalOperation::Install, &install, true),
+                Ok(OperationCommands::Script)
+            )
+        })
+    }
+
+    /// Commands for an operation, from the most explicit configuration available:
+    /// `<operation>_command`, then `command` when `config` comes from the operation's own
+    /// catalog section (`dedicated`), then the method's backend
+    fn operation_commands(
         &self,
-        install_config: &crate::InstallMethod,
-    ) -> Result<StringVec, ToolError> {
-        match install_config.method.as_str() {
-            "npm" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "npm".to_string(),
-                        "install".to_string(),
-                        "-g".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "npm install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "brew" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "brew".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "brew install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "pip" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "pip".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "pip install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "apt" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "apt".to_string(),
-                        "install".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "apt install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "yum" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "yum".to_string(),
-                        "install".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "yum install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "dnf" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "dnf".to_string(),
-                        "install".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "dnf install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "pacman" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "pacman".to_string(),
-                        "-S".to_string(),
-                        "--noconfirm".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "pacman install requires package_name".to_string(),
-                    ))
-                }
-            }
-            "winget" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "winget".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                        "--accept-source-agreements".to_string(),
-                        "--accept-package-agreements".to_string(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "winget install requires package_name".to_string(),
-                    ))
-                }
+        tool_id: &str,
+        operation: JournalOperation,
+        config: &InstallMethod,
+        dedicated: bool,
+    ) -> Result<OperationCommands, ToolError> {
+        let explicit = match operation {
+            JournalOperation::Install => config.install_command.as_ref(),
+            JournalOperation::Uninstall => config.uninstall_command.as_ref(),
+            JournalOperation::Update | JournalOperation::Rollback => config.update_command.as_ref(),
+        };
+        if let Some(sequence) = explicit {
+            return Ok(OperationCommands::Steps(sequence.steps().clone()));
+        }
+        if let Some(command) = config.command.as_ref().filter(|_| dedicated) {
+            let steps = CommandSequence::from_argv(command).0;
+            return Ok(OperationCommands::Steps(steps));
+        }
+
+        match config.method.as_str() {
+            // 安装脚本重新运行即可更新到最新版本
+            SCRIPT_METHOD if operation != JournalOperation::Uninstall => {
+                Ok(OperationCommands::Script)
             }
-            "choco" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "choco".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                        "-y".to_string(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "choco install requires package_name".to_string(),
-                    ))
-                }
+            BINARY_METHOD => Ok(OperationCommands::Binary),
+            SCRIPT_METHOD | CUSTOM_METHOD => Err(ToolError::NotSupported(format!(
+                "{tool_id} defines no {} command for method '{}'",
+                operation.as_str(),
+                config.method
+            ))),
+            method => {
+                let backend = self.backend(method)?;
+                let command = match operation {
+                    JournalOperation::Install => backend.install_command(config)?,
+                    JournalOperation::Uninstall => backend.uninstall_command(config)?,
+                    JournalOperation::Update | JournalOperation::Rollback => {
+                        backend.update_command(config)?
+                    }
+                };
+                Ok(OperationCommands::Steps(vec![command]))
             }
-            "scoop" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "scoop".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "scoop install requires package_name".to_string(),
-                    ))
+        }
+    }
+
+    /// Run install or update commands step by step, stopping at the first failure
+    async fn run_operation_commands(
+        &self,
+        tool_id: &str,
+        commands: OperationCommands,
+        config: &InstallMethod,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        match commands {
+            OperationCommands::Steps(steps) => {
+                for command in &steps {
+                    self.execute_install_command(command, record).await?;
                 }
+                Ok(())
             }
-            "cargo" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "cargo".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "cargo install requires package_name".to_string(),
-                    ))
-                }
+            // 脚本必须先经用户审阅，只能通过 install_script 运行
+            OperationCommands::Script => Err(ToolError::ReviewRequired(format!(
+                "{tool_id} runs {}; fetch and confirm it before running it",
+                config.url.as_deref().unwrap_or("an install script")
+            ))),
+            OperationCommands::Binary => {
+                let version = config.version.as_deref().ok_or_else(|| {
+                    ToolError::ConfigError(format!("{tool_id}: binary install requires version"))
+                })?;
+                self.install_binary(tool_id, config, version, record).await
             }
-            "go" => {
-                if let Some(package_name) = &install_config.package_name {
-                    Ok(vec![
-                        "go".to_string(),
-                        "install".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "go install requires package_name".to_string(),
-                    ))
-                }
+        }
+    }
+
+    /// Download and link a release of a `binary` tool
+    async fn install_binary(
+        &self,
+        tool_id: &str,
+        config: &InstallMethod,
+        version: &str,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        let command = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_tool_config(tool_id)
+                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
+                .command
+                .clone()
+        };
+
+        check_release_version(version)?;
+        let on_line = self.output_callback(record);
+        if let Some(template) = &config.url {
+            let url = render_url(template, version);
+            on_line(OutputStream::Stdout, &format!("Downloading {url}"));
+            record.command = vec![url];
+        }
+        let install = self.binaries.install(tool_id, &command, config, version);
+        let executable = self.cancel.guard(tool_id, install).await?;
+        record.stdout = format!(
+            "{} -> {}",
+            self.binaries.link_path(&command).display(),
+            executable.display()
+        );
+        on_line(OutputStream::Stdout, &record.stdout);
+        Ok(())
+    }
+
+    /// Download a tool's install script so it can be reviewed before `install_script` runs it
+    pub async fn fetch_install_script(&self, tool_id: &str) -> Result<InstallScript, ToolError> {
+        let install_config = self.platform_install_config(tool_id)?;
+        if install_config.method != SCRIPT_METHOD {
+            return Err(ToolError::NotSupported(format!(
+                "{tool_id} is installed with {}, not a script",
+                install_config.method
+            )));
+        }
+        InstallScript::fetch(tool_id, &install_config, &self.scripts_dir).await
+    }
+
+    /// Whether `update_tool` stops with `ReviewRequired` because the tool updates by
+    /// re-running a downloaded script
+    pub fn uses_update_script(&self, tool_id: &str) -> bool {
+        self.operation_config(tool_id, JournalOperation::Update)
+            .is_ok_and(|(_, config, dedicated)| {
+                matches!(
+                    self.operation_commands(tool_id, JournalOperation::Update, &config, dedicated),
+                    Ok(OperationCommands::Script)
+                )
+            })
+    }
+
+    /// Download the script a tool updates with so it can be reviewed before
+    /// `update_script` runs it
+    pub async fn fetch_update_script(&self, tool_id: &str) -> Result<InstallScript, ToolError> {
+        let (_, update_config, dedicated) =
+            self.operation_config(tool_id, JournalOperation::Update)?;
+        match self.operation_commands(
+            tool_id,
+            JournalOperation::Update,
+            &update_config,
+            dedicated,
+        )? {
+            OperationCommands::Script => {
+                InstallScript::fetch(tool_id, &update_config, &self.scripts_dir).await
             }
-            method => Err(ToolError::NotSupported(format!(
-                "Install method '{method}' not supported"
+            _ => Err(ToolError::NotSupported(format!(
+                "{tool_id} is updated with {}, not a script",
+                update_config.method
             ))),
         }
     }
 
-    /// Execute install command with proper error handling
-    async fn execute_install_command(&self, command: &[String]) -> Result<(), ToolError> {
-        if command.is_empty() {
-            return Err(ToolError::ConfigError("Empty install command".to_string()));
+    /// Run a script returned by `fetch_install_script` after the user confirmed it
+    pub async fn install_script(&self, script: &InstallScript) -> Result<(), ToolError> {
+        let mut record = OperationRecord::start(&script.tool_id, JournalOperation::Install);
+        let result = self.perform_script_install(script, &mut record).await;
+        script.discard();
+        self.journal_operation(record, &result).await;
+        result
+    }
+
+    async fn perform_script_install(
+        &self,
+        script: &InstallScript,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        let tool_config = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_tool_config(&script.tool_id)
+                .ok_or_else(|| ToolError::NotFound(format!("Tool {} not found", script.tool_id)))?
+                .clone()
+        };
+
+        if self.is_tool_installed(&tool_config).await {
+            debug!("Tool {} is already installed", script.tool_id);
+            return Ok(());
         }
+        self.ensure_dependencies(&script.tool_id).await?;
 
-        debug!("Executing install command: {:?}", command);
+        self.run_install_script(script, record).await?;
 
-        let mut cmd = Command::new(&command[0]);
-        if command.len() > 1 {
-            cmd.args(&command[1..]);
+        if let Ok(mut cache) = self.status_cache.lock() {
+            cache.remove(&script.tool_id);
         }
+        Ok(())
+    }
 
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {e}")))?;
+    /// Run a script returned by `fetch_update_script` after the user confirmed it
+    pub async fn update_script(&self, script: &InstallScript) -> Result<(), ToolError> {
+        let mut record = OperationRecord::start(&script.tool_id, JournalOperation::Update);
+        let result = self.perform_script_update(script, &mut record).await;
+        script.discard();
+        self.journal_operation(record, &result).await;
+        result
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            return Err(ToolError::ExecutionFailed(format!(
-                "Install command failed with exit code {}: stdout: {}, stderr: {}",
-                output.status.code().unwrap_or(-1),
-                stdout,
-                stderr
+    async fn perform_script_update(
+        &self,
+        script: &InstallScript,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        let tool_id = &script.tool_id;
+        let (tool_config, _, _) = self.operation_config(tool_id, JournalOperation::Update)?;
+
+        if !self.is_tool_installed(&tool_config).await {
+            return Err(ToolError::NotFound(format!(
+                "Tool {tool_id} is not installed"
             )));
         }
 
+        record.version_before = self.get_tool_version(&tool_config).await.ok();
+        let previous_version = match self.platform_install_config(tool_id) {
+            Ok(install) => self.pinnable_version(tool_id, &install).await.ok(),
+            Err(_) => None,
+        };
+
+        self.run_install_script(script, record).await?;
+
+        if let Ok(mut cache) = self.status_cache.lock() {
+            cache.remove(tool_id);
+        }
+        self.record_replaced_version(tool_id, previous_version);
         Ok(())
     }
 
-    /// Uninstall a tool
-    pub async fn uninstall_tool(&self, tool_id: &str) -> Result<(), ToolError> {
-        debug!("Uninstalling tool: {}", tool_id);
+    /// Execute a downloaded script, refusing to if the file changed since it was fetched
+    async fn run_install_script(
+        &self,
+        script: &InstallScript,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        record.command = script.command();
+        script.check_unchanged()?;
+        self.execute_install_command(&script.command(), record)
+            .await
+    }
+
+    /// Resolve the lockfile entry for an installed tool
+    pub async fn lock_tool(&self, tool_id: &str) -> Result<LockedTool, ToolError> {
+        let install_config = self.platform_install_config(tool_id)?;
+
+        match self.check_tool_status(tool_id).await? {
+            ToolStatus::Installed { .. } => Ok(LockedTool {
+                version: self.pinnable_version(tool_id, &install_config).await?,
+                method: install_config.method,
+                package_name: install_config.package_name,
+            }),
+            ToolStatus::Error(_) => Err(ToolError::ParseError(format!(
+                "Could not determine installed version of {tool_id}"
+            ))),
+            _ => Err(ToolError::NotFound(format!(
+                "Tool {tool_id} is not installed"
+            ))),
+        }
+    }
+
+    /// Installed version as `install` pins it: the package manager's own version string
+    /// (`1:2.43.0-1ubuntu7` for apt), or the tool's `--version` when the backend cannot
+    /// report one
+    async fn pinnable_version(
+        &self,
+        tool_id: &str,
+        install: &InstallMethod,
+    ) -> Result<String, ToolError> {
+        let has_lookup = self
+            .backend(&install.method)
+            .is_ok_and(|backend| backend.installed_version_command(install).is_ok());
+        if has_lookup {
+            return self
+                .version_checker
+                .query_installed_version(install)
+                .await
+                .ok_or_else(|| {
+                    ToolError::ParseError(format!(
+                        "{} reports no installed version of {tool_id}",
+                        install.method
+                    ))
+                });
+        }
 
-        let (tool_config, uninstall_config) = {
+        let tool_config = {
             let config_manager = self.config_manager.lock().unwrap();
-            let tool_config = config_manager
+            config_manager
                 .get_tool_config(tool_id)
                 .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
-                .clone();
+                .clone()
+        };
+        match self.get_tool_version(&tool_config).await? {
+            version if version == "unknown" => Err(ToolError::ParseError(format!(
+                "Could not determine installed version of {tool_id}"
+            ))),
+            version => Ok(version),
+        }
+    }
 
-            let platform = std::env::consts::OS;
+    /// Install a specific version of a tool using its platform install method
+    pub async fn install_tool_version(
+        &self,
+        tool_id: &str,
+        version: &str,
+    ) -> Result<(), ToolError> {
+        debug!("Installing tool {} at version {}", tool_id, version);
+
+        let install_config = self.platform_install_config(tool_id)?;
+
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Install);
+        let result = self
+            .install_pinned(tool_id, &install_config, version, &mut record)
+            .await;
+        self.journal_operation(record, &result).await;
+        self.record_replaced_version(tool_id, result?);
+        Ok(())
+    }
 
-            // 首先尝试使用专门的卸载配置
-            let uninstall_config = if let Some(uninstall_configs) = &tool_config.uninstall {
-                uninstall_configs.get(platform).cloned()
-            } else {
-                None
+    /// Install a tool at the exact version recorded in a lockfile
+    ///
+    /// Only the version is taken from the lockfile; method and package come from the catalog
+    /// and must match what the lockfile recorded.
+    pub async fn install_locked_tool(
+        &self,
+        tool_id: &str,
+        locked: &LockedTool,
+    ) -> Result<(), ToolError> {
+        debug!("Installing {} {} from lockfile", tool_id, locked.version);
+
+        let install_config = self.platform_install_config(tool_id)?;
+        if locked.method != install_config.method
+            || locked.package_name != install_config.package_name
+        {
+            let describe = |method: &str, package: &Option<String>| match package {
+                Some(package) => format!("{method} package '{package}'"),
+                None => method.to_string(),
             };
+            return Err(ToolError::ConfigError(format!(
+                "{tool_id}: lockfile records {} but the catalog installs it with {}",
+                describe(&locked.method, &locked.package_name),
+                describe(&install_config.method, &install_config.package_name)
+            )));
+        }
 
-            // 如果没有专门的卸载配置，回退到安装配置
-            let uninstall_config = uninstall_config
-                .or_else(|| tool_config.install.get(platform).cloned())
-                .ok_or_else(|| {
-                    ToolError::NotSupported(format!(
-                        "Platform {platform} not supported for {tool_id}"
-                    ))
-                })?;
+        // brew 等无法安装指定版本的方式，锁文件中的版本只作参考
+        let pinnable = install_config.method == BINARY_METHOD
+            || self
+                .pinned_command(
+                    &install_config.method,
+                    install_config.package_name.as_deref(),
+                    &locked.version,
+                )
+                .is_ok();
+
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Install);
+        let result = self
+            .install_pinned(tool_id, &install_config, &locked.version, &mut record)
+            .await
+            .map_err(|e| match e {
+                ToolError::NotSupported(_) if !pinnable => ToolError::NotSupported(format!(
+                    "{tool_id}: {} cannot install an exact version, so the locked {} is \
+                     advisory; install the current release without --locked",
+                    install_config.method, locked.version
+                )),
+                e => e,
+            });
+        self.journal_operation(record, &result).await;
+        self.record_replaced_version(tool_id, result?);
+        Ok(())
+    }
 
-            (tool_config, uninstall_config)
+    /// Reinstall the version that was installed before the last update
+    ///
+    /// Returns the version that was restored.
+    pub async fn rollback_tool(&self, tool_id: &str) -> Result<String, ToolError> {
+        debug!("Rolling back tool: {}", tool_id);
+
+        let install_config = self.platform_install_config(tool_id)?;
+        // 历史记录保存包管理器的版本号，当前版本也按同样方式取得
+        let current = match self.check_tool_status(tool_id).await? {
+            ToolStatus::Installed { .. } => {
+                self.pinnable_version(tool_id, &install_config).await.ok()
+            }
+            _ => None,
         };
 
-        if !self.is_tool_installed(&tool_config).await {
-            debug!("Tool {} is not installed", tool_id);
-            return Ok(());
+        let target = {
+            let history = self.version_history.lock().unwrap();
+            history
+                .previous_version(tool_id, current.as_deref())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    ToolError::NotFound(format!("No previous version recorded for {tool_id}"))
+                })?
+        };
+
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Rollback);
+        let result = self
+            .install_pinned(tool_id, &install_config, &target, &mut record)
+            .await;
+        self.journal_operation(record, &result).await;
+        result?;
+
+        {
+            let mut history = self.version_history.lock().unwrap();
+            history.remove_rolled_back(tool_id, &target);
+            if let Err(e) = history.save(&self.version_history_path) {
+                warn!("Failed to save version history: {}", e);
+            }
         }
 
-        // Execute uninstall command
-        if let Some(command) = &uninstall_config.command {
-            self.execute_uninstall_command(command).await?;
+        debug!("Tool {} rolled back to {}", tool_id, target);
+        Ok(target)
+    }
+
+    /// Version a rollback would restore from the given installed version
+    pub fn previous_version(&self, tool_id: &str, current: Option<&str>) -> Option<String> {
+        let history = self.version_history.lock().ok()?;
+        history
+            .previous_version(tool_id, current)
+            .map(str::to_string)
+    }
+
+    /// Versions replaced by earlier updates, oldest first
+    pub fn version_history(&self, tool_id: &str) -> Vec<VersionHistoryEntry> {
+        self.version_history
+            .lock()
+            .map(|history| history.entries(tool_id).to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Install an exact version, returning the version it replaced
+    ///
+    /// The replaced version is the one `pinnable_version` reports, so it can be pinned again.
+    async fn install_pinned(
+        &self,
+        tool_id: &str,
+        install: &InstallMethod,
+        version: &str,
+        record: &mut OperationRecord,
+    ) -> Result<Option<String>, ToolError> {
+        let (displayed, current) = match self.check_tool_status(tool_id).await? {
+            ToolStatus::Installed { version: displayed } => {
+                let current = self.pinnable_version(tool_id, install).await.ok();
+                let same = |installed: &str| {
+                    installed == version
+                        || compare_versions(installed, version) == Some(std::cmp::Ordering::Equal)
+                };
+                if current.as_deref().is_some_and(same) || same(&displayed) {
+                    debug!("Tool {} is already at version {}", tool_id, version);
+                    return Ok(None);
+                }
+                (Some(displayed), current)
+            }
+            _ => (None, None),
+        };
+        record.version_before.clone_from(&displayed);
+        if displayed.is_none() {
+            self.ensure_dependencies(tool_id).await?;
+        }
+
+        if install.method == BINARY_METHOD {
+            self.install_binary(tool_id, install, version, record)
+                .await?;
         } else {
-            // Fallback: construct command from method and package_name
-            let command = self.construct_uninstall_command(&uninstall_config)?;
-            self.execute_uninstall_command(&command).await?;
+            let command =
+                self.pinned_command(&install.method, install.package_name.as_deref(), version)?;
+            self.execute_install_command(&command, record).await?;
         }
 
         // Clear status cache to force re-check
@@ -469,178 +848,514 @@ impl ToolManager {
             cache.remove(tool_id);
         }
 
-        debug!("Tool {} uninstalled successfully", tool_id);
-        Ok(())
+        debug!("Tool {} installed at {}", tool_id, version);
+        Ok(current)
     }
 
-    /// Construct uninstall command from method and package name
-    fn construct_uninstall_command(
+    /// Backend command that installs exactly `version`
+    fn pinned_command(
         &self,
-        uninstall_config: &crate::InstallMethod,
+        method: &str,
+        package_name: Option<&str>,
+        version: &str,
     ) -> Result<StringVec, ToolError> {
-        match uninstall_config.method.as_str() {
-            "npm" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "npm".to_string(),
-                        "uninstall".to_string(),
-                        "-g".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "npm uninstall requires package_name".to_string(),
-                    ))
-                }
-            }
-            "brew" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "brew".to_string(),
-                        "uninstall".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "brew uninstall requires package_name".to_string(),
-                    ))
-                }
-            }
-            "pip" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "pip".to_string(),
-                        "uninstall".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "pip uninstall requires package_name".to_string(),
-                    ))
-                }
+        let install = InstallMethod {
+            method: method.to_string(),
+            package_name: package_name.map(str::to_string),
+            ..Default::default()
+        };
+        self.backend(method)?
+            .pinned_install_command(&install, version)
+    }
+
+    /// Persist a replaced version so it can be rolled back to later
+    fn record_replaced_version(&self, tool_id: &str, replaced: Option<String>) {
+        let Some(version) = replaced.filter(|v| v != "unknown") else {
+            return;
+        };
+
+        if let Ok(mut history) = self.version_history.lock() {
+            history.record(tool_id, &version);
+            if let Err(e) = history.save(&self.version_history_path) {
+                warn!("Failed to save version history: {}", e);
             }
-            "apt" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "apt".to_string(),
-                        "remove".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "apt remove requires package_name".to_string(),
-                    ))
+        }
+    }
+
+    /// Prerequisites of a tool that are not installed or too old, in install order
+    pub async fn missing_dependencies(
+        &self,
+        tool_id: &str,
+    ) -> Result<MissingDependencies, ToolError> {
+        let requirements: DependencyRequirements = {
+            let config_manager = self.config_manager.lock().unwrap();
+            let order = install_order(&config_manager.get_tools_config().tools, tool_id)?;
+
+            // 同一前置工具可能被多个工具依赖，取最高的最低版本
+            order[..order.len() - 1]
+                .iter()
+                .map(|dependency_id| {
+                    let min_version = order
+                        .iter()
+                        .filter_map(|id| config_manager.get_tool_config(id))
+                        .flat_map(|tool| &tool.depends_on)
+                        .filter(|dependency| dependency.id == *dependency_id)
+                        .filter_map(|dependency| dependency.min_version.clone())
+                        .reduce(|a, b| if Self::version_at_least(&a, &b) { a } else { b });
+                    (dependency_id.clone(), min_version)
+                })
+                .collect()
+        };
+
+        let mut missing = Vec::new();
+        for (dependency_id, min_version) in requirements {
+            let installed_version = match self.check_tool_status(&dependency_id).await? {
+                ToolStatus::Installed { version } => Some(version),
+                // Installed, but the version could not be read
+                ToolStatus::Error(_) => Some("unknown".to_string()),
+                _ => None,
+            };
+
+            let satisfied = match (&installed_version, &min_version) {
+                (None, _) => false,
+                (Some(version), Some(min)) if version != "unknown" => {
+                    Self::version_at_least(version, min)
                 }
+                _ => true,
+            };
+
+            if !satisfied {
+                missing.push(MissingDependency {
+                    tool_id: dependency_id,
+                    min_version,
+                    installed_version,
+                });
             }
-            "yum" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "yum".to_string(),
-                        "remove".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "yum remove requires package_name".to_string(),
-                    ))
-                }
+        }
+
+        Ok(missing)
+    }
+
+    /// Install or update the missing prerequisites of a tool, returning what was changed
+    pub async fn install_dependencies(
+        &self,
+        tool_id: &str,
+    ) -> Result<MissingDependencies, ToolError> {
+        let missing = self.missing_dependencies(tool_id).await?;
+        for dependency in &missing {
+            if dependency.installed_version.is_some() {
+                self.update_tool(&dependency.tool_id).await?;
+            } else {
+                self.install_tool(&dependency.tool_id).await?;
             }
-            "dnf" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "dnf".to_string(),
-                        "remove".to_string(),
-                        "-y".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "dnf remove requires package_name".to_string(),
-                    ))
+        }
+        Ok(missing)
+    }
+
+    /// Install a tool after its missing prerequisites
+    pub async fn install_with_dependencies(
+        &self,
+        tool_id: &str,
+    ) -> Result<MissingDependencies, ToolError> {
+        let installed = self.install_dependencies(tool_id).await?;
+        self.install_tool(tool_id).await?;
+        Ok(installed)
+    }
+
+    /// Reorder tool ids so prerequisites come before the tools that need them
+    pub fn dependency_order(&self, tool_ids: &[String]) -> StringVec {
+        let config_manager = self.config_manager.lock().unwrap();
+        let tools = &config_manager.get_tools_config().tools;
+
+        let mut ordered: StringVec = Vec::new();
+        for tool_id in tool_ids {
+            let order = install_order(tools, tool_id).unwrap_or_else(|_| vec![tool_id.clone()]);
+            for id in order {
+                if tool_ids.contains(&id) && !ordered.contains(&id) {
+                    ordered.push(id);
                 }
             }
-            "pacman" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "sudo".to_string(),
-                        "pacman".to_string(),
-                        "-R".to_string(),
-                        "--noconfirm".to_string(),
-                        package_name.clone(),
-                    ])
+        }
+        ordered
+    }
+
+    async fn ensure_dependencies(&self, tool_id: &str) -> Result<(), ToolError> {
+        let missing = self.missing_dependencies(tool_id).await?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let missing: StringVec = missing.iter().map(ToString::to_string).collect();
+        Err(ToolError::MissingDependencies(format!(
+            "{tool_id} requires {}",
+            missing.join(", ")
+        )))
+    }
+
+    /// An unreadable minimum is satisfied by anything, an unreadable version by nothing
+    fn version_at_least(version: &str, min: &str) -> bool {
+        match (Version::parse(version), Version::parse(min)) {
+            (Some(version), Some(min)) => version >= min,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Query the operation journal, newest entries first
+    pub fn operation_history(&self, query: &JournalQuery) -> Result<JournalEntries, ToolError> {
+        self.journal.query(query)
+    }
+
+    /// Append an executed operation to the journal; operations that ran no command are skipped
+    async fn journal_operation<T>(&self, record: OperationRecord, result: &Result<T, ToolError>) {
+        if record.command.is_empty() {
+            return;
+        }
+
+        let duration = record.started.elapsed();
+        let version_after = match self.check_tool_status(&record.tool_id).await {
+            Ok(ToolStatus::Installed { version }) => Some(version),
+            _ => None,
+        };
+
+        let entry = JournalEntry {
+            timestamp: chrono::Utc::now(),
+            tool_id: record.tool_id,
+            operation: record.operation,
+            user: current_user(),
+            command: record.command,
+            exit_code: record.exit_code,
+            duration_ms: duration.as_millis() as u64,
+            version_before: record.version_before,
+            version_after,
+            success: result.is_ok(),
+            error: result
+                .as_ref()
+                .err()
+                .map(|e| truncate_output(&e.to_string())),
+            stdout: record.stdout,
+            stderr: record.stderr,
+        };
+
+        if let Err(e) = self.journal.append(&entry) {
+            warn!("Failed to write operation journal: {}", e);
+        }
+    }
+
+    /// Catalog entry and platform section for an operation: the operation's own section when
+    /// the catalog has one (`dedicated`), otherwise the install section
+    fn operation_config(
+        &self,
+        tool_id: &str,
+        operation: JournalOperation,
+    ) -> Result<OperationConfig, ToolError> {
+        let tool_config = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_tool_config(tool_id)
+                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
+                .clone()
+        };
+        let install = self.select_method(&tool_config)?.method().clone();
+
+        let platform = Platform::current();
+        let own = match operation {
+            // 安装配置本身就是安装操作的专门配置
+            JournalOperation::Install => None,
+            JournalOperation::Uninstall => tool_config.uninstall.as_ref(),
+            JournalOperation::Update | JournalOperation::Rollback => tool_config.update.as_ref(),
+        }
+        .and_then(|configs| platform.lookup(configs))
+        // 专门配置中优先使用与安装相同的方式
+        .and_then(|candidates| {
+            candidates.select(Some(&install.method), |candidate| {
+                self.method_available(tool_id, candidate)
+            })
+        })
+        .map(|selection| selection.method().clone());
+        let dedicated = own.is_some() || operation == JournalOperation::Install;
+
+        // 没有专门的配置时回退到安装配置
+        let config = own.unwrap_or(install);
+        Ok((tool_config, config, dedicated))
+    }
+
+    /// Install method used for a tool on this platform: the one the user preferred when the
+    /// catalog lists it, otherwise the first whose package manager is on PATH, otherwise the
+    /// first listed
+    pub fn method_selection(&self, tool_id: &str) -> Result<MethodSelection, ToolError> {
+        let tool_config = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_tool_config(tool_id)
+                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
+                .clone()
+        };
+        self.select_method(&tool_config)
+    }
+
+    /// Use `method` for a tool from now on instead of choosing automatically, or go back to
+    /// choosing automatically with `None`; the settings still have to be saved
+    pub fn set_preferred_method(&self, tool_id: &str, method: Option<&str>) {
+        let mut config_manager = self.config_manager.lock().unwrap();
+        let mut settings = config_manager.get_app_settings().clone();
+        match method {
+            Some(method) => settings
+                .preferred_methods
+                .insert(tool_id.to_string(), method.to_string()),
+            None => settings.preferred_methods.remove(tool_id),
+        };
+        config_manager.update_app_settings(settings);
+    }
+
+    /// Every copy of a tool's command on PATH, with the package manager each came from
+    ///
+    /// `ToolLocation::shadowed_by` tells whether another copy runs instead of the one installed
+    /// with the tool's install method.
+    pub fn locate_tool(&self, tool_id: &str) -> Result<ToolLocation, ToolError> {
+        let tool_config = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_tool_config(tool_id)
+                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
+                .clone()
+        };
+        let selection = self.select_method(&tool_config)?;
+        Ok(ToolLocation {
+            method: selection.method().method.clone(),
+            copies: locate_command(&tool_config.command, &self.binaries),
+        })
+    }
+
+    fn select_method(&self, tool_config: &ToolConfig) -> Result<MethodSelection, ToolError> {
+        let preferred = self
+            .config_manager
+            .lock()
+            .unwrap()
+            .get_app_settings()
+            .preferred_methods
+            .get(&tool_config.id)
+            .cloned();
+
+        let platform = Platform::current();
+        platform
+            .lookup(&tool_config.install)
+            .and_then(|candidates| {
+                candidates.select(preferred.as_deref(), |candidate| {
+                    self.method_available(&tool_config.id, candidate)
+                })
+            })
+            .ok_or_else(|| {
+                ToolError::NotSupported(format!(
+                    "Platform {platform} not supported for {}",
+                    tool_config.id
+                ))
+            })
+    }
+
+    /// Whether the program a method installs with is on PATH; scripts and release archives
+    /// only need a download
+    fn method_available(&self, tool_id: &str, config: &InstallMethod) -> bool {
+        match self.operation_commands(tool_id, JournalOperation::Install, config, true) {
+            Ok(OperationCommands::Steps(steps)) => steps.first().is_some_and(|step| {
+                let program = if is_privileged(step) {
+                    step.get(1)
                 } else {
-                    Err(ToolError::ConfigError(
-                        "pacman remove requires package_name".to_string(),
-                    ))
-                }
-            }
-            "winget" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "winget".to_string(),
-                        "uninstall".to_string(),
-                        package_name.clone(),
-                    ])
+                    step.first()
+                };
+                program.is_some_and(|program| find_on_path(program).is_some())
+            }),
+            Ok(OperationCommands::Script | OperationCommands::Binary) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Copy of a tool's configuration listing only the selected install method for this
+    /// platform, for the version checker, which reads the first one
+    fn with_selected_method(&self, tool_config: &ToolConfig) -> ToolConfig {
+        let mut tool_config = tool_config.clone();
+        if let Ok(selection) = self.select_method(&tool_config) {
+            let selected = selection.method().clone();
+            tool_config.install =
+                HashMap::from([(Platform::current().name().to_string(), selected.into())]);
+        }
+        tool_config
+    }
+
+    /// Self-update command derived from the tool's update check, if it has one here
+    ///
+    /// Only a check run with `--check-only` has an updating counterpart (`--update`); any
+    /// other check would merely report the version and pass for an update.
+    fn self_update_command(tool_config: &ToolConfig) -> Option<StringVec> {
+        let update_cmd = VersionChecker::update_check_command(tool_config)?;
+        if !update_cmd.iter().any(|arg| arg == "--check-only") {
+            return None;
+        }
+        let self_update_cmd = update_cmd
+            .into_iter()
+            .map(|arg| {
+                if arg == "--check-only" {
+                    "--update".to_string()
                 } else {
-                    Err(ToolError::ConfigError(
-                        "winget uninstall requires package_name".to_string(),
-                    ))
+                    arg
                 }
+            })
+            .collect();
+        Some(self_update_cmd)
+    }
+
+    fn platform_install_config(&self, tool_id: &str) -> Result<InstallMethod, ToolError> {
+        Ok(self.method_selection(tool_id)?.method().clone())
+    }
+
+    /// Execute install command with proper error handling
+    async fn execute_install_command(
+        &self,
+        command: &[String],
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        if command.is_empty() {
+            return Err(ToolError::ConfigError("Empty install command".to_string()));
+        }
+
+        debug!("Executing install command: {:?}", command);
+        let output = self.run_recorded_command(command, record).await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(ToolError::ExecutionFailed(format!(
+                "Install command failed with exit code {}: stdout: {}, stderr: {}",
+                output.status.code().unwrap_or(-1),
+                stdout,
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Run a command for an operation, streaming its output to the sink as it arrives
+    async fn run_recorded_command(
+        &self,
+        command: &[String],
+        record: &mut OperationRecord,
+    ) -> Result<std::process::Output, ToolError> {
+        // 记录实际执行的命令
+        let (mut cmd, elevation) = self.prepare_command(command);
+        record.command = argv(&cmd);
+
+        let timeout = Duration::from_secs(self.timeouts().install_secs);
+        let output = run_command(
+            &mut cmd,
+            Some(self.output_callback(record)),
+            &self.cancel,
+            timeout,
+        )
+        .await?;
+        record.capture(&output);
+
+        if elevation.is_some_and(|elevation| elevation.authentication_failed(&output)) {
+            return Err(ToolError::AuthenticationFailed(format!(
+                "{} was not given a valid password: {}",
+                command.join(" "),
+                record.stderr.trim()
+            )));
+        }
+        Ok(output)
+    }
+
+    /// Process for a configured command; `sudo` commands are rewritten for the current
+    /// elevation, which is returned for them
+    fn prepare_command(&self, command: &[String]) -> PreparedCommand {
+        let elevation = is_privileged(command).then(|| self.elevation.lock().unwrap().clone());
+        let cmd = match &elevation {
+            Some(elevation) => elevation.command(command),
+            None => {
+                let mut cmd = Command::new(&command[0]);
+                cmd.args(&command[1..]);
+                cmd
             }
-            "choco" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "choco".to_string(),
-                        "uninstall".to_string(),
-                        package_name.clone(),
-                        "-y".to_string(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "choco uninstall requires package_name".to_string(),
-                    ))
-                }
+        };
+        (cmd, elevation)
+    }
+
+    /// Forward lines of an operation to the output sink, if one is set
+    fn output_callback(&self, record: &OperationRecord) -> LineCallback {
+        let sink = self.output_sink.lock().unwrap().clone();
+        let (tool_id, operation) = (record.tool_id.clone(), record.operation);
+        Arc::new(move |stream, text| {
+            if let Some(sink) = &sink {
+                sink(OutputLine {
+                    tool_id: tool_id.clone(),
+                    operation,
+                    stream,
+                    text: text.to_string(),
+                });
             }
-            "scoop" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "scoop".to_string(),
-                        "uninstall".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "scoop uninstall requires package_name".to_string(),
-                    ))
+        })
+    }
+
+    /// Uninstall a tool
+    pub async fn uninstall_tool(&self, tool_id: &str) -> Result<(), ToolError> {
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Uninstall);
+        let result = self.perform_uninstall(tool_id, &mut record).await;
+        self.journal_operation(record, &result).await;
+        result
+    }
+
+    async fn perform_uninstall(
+        &self,
+        tool_id: &str,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        debug!("Uninstalling tool: {}", tool_id);
+
+        let (tool_config, uninstall_config, dedicated) =
+            self.operation_config(tool_id, JournalOperation::Uninstall)?;
+
+        if !self.is_tool_installed(&tool_config).await {
+            debug!("Tool {} is not installed", tool_id);
+            return Ok(());
+        }
+        record.version_before = self.get_tool_version(&tool_config).await.ok();
+
+        // Execute uninstall command
+        let commands = self.operation_commands(
+            tool_id,
+            JournalOperation::Uninstall,
+            &uninstall_config,
+            dedicated,
+        )?;
+        match commands {
+            OperationCommands::Steps(steps) => {
+                for command in &steps {
+                    self.execute_uninstall_command(command, record).await?;
                 }
             }
-            "cargo" => {
-                if let Some(package_name) = &uninstall_config.package_name {
-                    Ok(vec![
-                        "cargo".to_string(),
-                        "uninstall".to_string(),
-                        package_name.clone(),
-                    ])
-                } else {
-                    Err(ToolError::ConfigError(
-                        "cargo uninstall requires package_name".to_string(),
-                    ))
-                }
+            OperationCommands::Binary => {
+                record.command = vec![self.binaries.tool_dir(tool_id).display().to_string()];
+                self.binaries.uninstall(tool_id, &tool_config.command)?;
             }
-            method => Err(ToolError::NotSupported(format!(
-                "Uninstall method '{method}' not supported"
-            ))),
+            // 卸载不会运行安装脚本
+            OperationCommands::Script => {}
+        }
+
+        // Clear status cache to force re-check
+        if let Ok(mut cache) = self.status_cache.lock() {
+            cache.remove(tool_id);
         }
+
+        debug!("Tool {} uninstalled successfully", tool_id);
+        Ok(())
     }
 
     /// Execute uninstall command with proper error handling
-    async fn execute_uninstall_command(&self, command: &[String]) -> Result<(), ToolError> {
+    async fn execute_uninstall_command(
+        &self,
+        command: &[String],
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
         if command.is_empty() {
             return Err(ToolError::ConfigError(
                 "Empty uninstall command".to_string(),
@@ -648,16 +1363,7 @@ impl ToolManager {
         }
 
         debug!("Executing uninstall command: {:?}", command);
-
-        let mut cmd = Command::new(&command[0]);
-        if command.len() > 1 {
-            cmd.args(&command[1..]);
-        }
-
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {e}")))?;
+        let output = self.run_recorded_command(command, record).await?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -695,7 +1401,10 @@ impl ToolManager {
             )));
         }
 
-        let output = Self::execute_hidden_command(&tool_config.command, args).await?;
+        let timeout = Duration::from_secs(self.timeouts().install_secs);
+        let output = self
+            .execute_hidden_command(&tool_config.command, args, timeout)
+            .await?;
 
         Ok(output)
     }
@@ -715,7 +1424,7 @@ impl ToolManager {
         };
 
         self.version_checker
-            .check_version(&tool_config, strategy)
+            .check_version(&self.with_selected_method(&tool_config), strategy)
             .await
     }
 
@@ -727,7 +1436,7 @@ impl ToolManager {
 
         // Compare current version with latest version
         if let (Some(current), Some(latest)) = (&version_info.current, &version_info.latest) {
-            Ok(Self::compare_versions(current, latest) == std::cmp::Ordering::Less)
+            Ok(compare_versions(current, latest) == Some(std::cmp::Ordering::Less))
         } else {
             Ok(false)
         }
@@ -772,8 +1481,9 @@ impl ToolManager {
         )))
     }
 
-    /// Check all tools for available updates
-    pub async fn check_all_updates(&self) -> Result<Vec<(String, bool)>, ToolError> {
+    /// Check all tools for available updates, keeping each tool's own result so a failed
+    /// check is not mistaken for "up to date"
+    pub async fn check_all_updates(&self) -> UpdateCheckResults {
         let tools_config = {
             let config_manager = self.config_manager.lock().unwrap();
             config_manager.get_tools_config().clone()
@@ -782,44 +1492,33 @@ impl ToolManager {
         let mut update_results = Vec::new();
 
         for tool_config in &tools_config.tools {
-            match self.has_updates_available(&tool_config.id).await {
-                Ok(has_updates) => {
-                    update_results.push((tool_config.id.clone(), has_updates));
-                }
-                Err(e) => {
-                    warn!("Failed to check updates for {}: {}", tool_config.id, e);
-                    update_results.push((tool_config.id.clone(), false));
-                }
+            let result = self.has_updates_available(&tool_config.id).await;
+            if let Err(e) = &result {
+                warn!("Failed to check updates for {}: {}", tool_config.id, e);
             }
+            update_results.push((tool_config.id.clone(), result));
         }
 
-        Ok(update_results)
+        update_results
     }
 
     /// Update tool to latest version
     pub async fn update_tool(&self, tool_id: &str) -> Result<(), ToolError> {
-        debug!("Updating tool: {}", tool_id);
-
-        let (tool_config, install_config) = {
-            let config_manager = self.config_manager.lock().unwrap();
-            let tool_config = config_manager
-                .get_tool_config(tool_id)
-                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
-                .clone();
+        let mut record = OperationRecord::start(tool_id, JournalOperation::Update);
+        let result = self.perform_update(tool_id, &mut record).await;
+        self.journal_operation(record, &result).await;
+        result
+    }
 
-            let platform = std::env::consts::OS;
-            let install_config = tool_config
-                .install
-                .get(platform)
-                .ok_or_else(|| {
-                    ToolError::NotSupported(format!(
-                        "Platform {platform} not supported for {tool_id}"
-                    ))
-                })?
-                .clone();
+    async fn perform_update(
+        &self,
+        tool_id: &str,
+        record: &mut OperationRecord,
+    ) -> Result<(), ToolError> {
+        debug!("Updating tool: {}", tool_id);
 
-            (tool_config, install_config)
-        };
+        let (tool_config, update_config, dedicated) =
+            self.operation_config(tool_id, JournalOperation::Update)?;
 
         if !self.is_tool_installed(&tool_config).await {
             return Err(ToolError::NotFound(format!(
@@ -827,116 +1526,398 @@ impl ToolManager {
             )));
         }
 
-        // Try self-update first if available
-        if let Some(update_check_configs) = &tool_config.update_check {
-            let platform = std::env::consts::OS;
-            if let Some(update_cmd) = update_check_configs.get(platform) {
-                let self_update_cmd: Vec<String> = update_cmd
-                    .iter()
-                    .map(|s| {
-                        if s == "--check-only" {
-                            "--update".to_string()
-                        } else {
-                            s.clone()
-                        }
-                    })
-                    .collect();
+        // Remember the current version so the update can be rolled back, in the form the
+        // install method pins it
+        record.version_before = self.get_tool_version(&tool_config).await.ok();
+        let previous_version = match self.platform_install_config(tool_id) {
+            Ok(install) => self.pinnable_version(tool_id, &install).await.ok(),
+            Err(_) => None,
+        };
 
-                match self.execute_install_command(&self_update_cmd).await {
-                    Ok(_) => {
-                        debug!("Tool {} updated via self-update", tool_id);
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Self-update failed for {}: {}, trying package manager",
-                            tool_id, e
-                        );
-                    }
+        // Try self-update first if available
+        if let Some(self_update_cmd) = Self::self_update_command(&tool_config) {
+            match self.execute_install_command(&self_update_cmd, record).await {
+                Ok(_) => {
+                    debug!("Tool {} updated via self-update", tool_id);
+                    self.record_replaced_version(tool_id, previous_version);
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!(
+                        "Self-update failed for {}: {}, trying package manager",
+                        tool_id, e
+                    );
                 }
             }
         }
 
-        // Fallback to package manager update
-        let update_command = match install_config.method.as_str() {
-            "npm" => {
-                if let Some(package_name) = &install_config.package_name {
-                    vec![
-                        "npm".to_string(),
-                        "update".to_string(),
-                        "-g".to_string(),
-                        package_name.clone(),
-                    ]
-                } else {
-                    return Err(ToolError::ConfigError(
-                        "NPM package name not specified".to_string(),
-                    ));
+        // Fallback to the configured update commands or the package manager
+        let commands =
+            self.operation_commands(tool_id, JournalOperation::Update, &update_config, dedicated)?;
+        self.run_operation_commands(tool_id, commands, &update_config, record)
+            .await?;
+
+        // Clear status cache to force refresh
+        if let Ok(mut cache) = self.status_cache.lock() {
+            cache.remove(tool_id);
+        }
+        self.record_replaced_version(tool_id, previous_version);
+
+        debug!("Tool {} updated successfully", tool_id);
+        Ok(())
+    }
+
+    /// What an install, uninstall, update or rollback would run, without running anything
+    ///
+    /// Commands are resolved the way the operation resolves them, including the package
+    /// manager fallback after a failed self-update and the rewrite of `sudo` commands for the
+    /// current elevation. Whether the tool is installed is not checked, and a rollback uses
+    /// the cached status to pick its target.
+    pub fn plan_operation(
+        &self,
+        tool_id: &str,
+        operation: JournalOperation,
+    ) -> Result<OperationPlan, ToolError> {
+        let alternatives = if operation == JournalOperation::Rollback {
+            let current = match self.get_cached_status(tool_id) {
+                Some(ToolStatus::Installed { version }) => Some(version),
+                _ => None,
+            };
+            let target = self
+                .previous_version(tool_id, current.as_deref())
+                .ok_or_else(|| {
+                    ToolError::NotFound(format!("No previous version recorded for {tool_id}"))
+                })?;
+            vec![self.pinned_steps(tool_id, &target)?]
+        } else {
+            let (tool_config, config, dedicated) = self.operation_config(tool_id, operation)?;
+            let mut alternatives = Vec::new();
+            if operation == JournalOperation::Update {
+                if let Some(command) = Self::self_update_command(&tool_config) {
+                    alternatives.push(vec![self.run_step(&command)?]);
                 }
             }
-            "brew" => {
-                if let Some(package_name) = &install_config.package_name {
-                    vec![
-                        "brew".to_string(),
-                        "upgrade".to_string(),
-                        package_name.clone(),
-                    ]
-                } else {
-                    return Err(ToolError::ConfigError(
-                        "Brew formula name not specified".to_string(),
-                    ));
-                }
+            let commands = self.operation_commands(tool_id, operation, &config, dedicated)?;
+            alternatives.push(self.plan_steps(&tool_config, operation, commands, &config)?);
+            alternatives
+        };
+
+        Ok(OperationPlan {
+            tool_id: tool_id.to_string(),
+            operation,
+            alternatives,
+        })
+    }
+
+    /// What `install_tool_version` would run
+    pub fn plan_version_install(
+        &self,
+        tool_id: &str,
+        version: &str,
+    ) -> Result<OperationPlan, ToolError> {
+        Ok(OperationPlan {
+            tool_id: tool_id.to_string(),
+            operation: JournalOperation::Install,
+            alternatives: vec![self.pinned_steps(tool_id, version)?],
+        })
+    }
+
+    fn plan_steps(
+        &self,
+        tool_config: &ToolConfig,
+        operation: JournalOperation,
+        commands: OperationCommands,
+        config: &InstallMethod,
+    ) -> Result<PlanSteps, ToolError> {
+        let tool_id = &tool_config.id;
+        match commands {
+            OperationCommands::Steps(steps) => {
+                steps.iter().map(|command| self.run_step(command)).collect()
             }
-            "pip" => {
-                if let Some(package_name) = &install_config.package_name {
-                    vec![
-                        "pip".to_string(),
-                        "install".to_string(),
-                        "--upgrade".to_string(),
-                        package_name.clone(),
-                    ]
-                } else {
-                    return Err(ToolError::ConfigError(
-                        "Pip package name not specified".to_string(),
-                    ));
-                }
+            OperationCommands::Script if operation != JournalOperation::Install => {
+                Err(ToolError::ReviewRequired(format!(
+                    "{tool_id} can only run its install script through a reviewed install"
+                )))
+            }
+            OperationCommands::Script => {
+                let url = config.url.clone().ok_or_else(|| {
+                    ToolError::ConfigError(format!("{tool_id}: script install requires url"))
+                })?;
+                Ok(vec![PlanStep::Script {
+                    url,
+                    interpreter: InstallScript::interpreter(config),
+                }])
+            }
+            OperationCommands::Binary if operation == JournalOperation::Uninstall => {
+                Ok(vec![PlanStep::RemoveBinary {
+                    path: self.binaries.tool_dir(tool_id),
+                    link: self.binaries.link_path(&tool_config.command),
+                }])
             }
-            _ => {
-                return Err(ToolError::NotSupported(format!(
-                    "Update method {} not supported",
-                    install_config.method
-                )));
+            OperationCommands::Binary => {
+                let version = config.version.as_deref().ok_or_else(|| {
+                    ToolError::ConfigError(format!("{tool_id}: binary install requires version"))
+                })?;
+                Ok(vec![self.binary_step(tool_config, config, version)?])
             }
+        }
+    }
+
+    /// Steps of `install_pinned`
+    fn pinned_steps(&self, tool_id: &str, version: &str) -> Result<PlanSteps, ToolError> {
+        let (tool_config, install, _) =
+            self.operation_config(tool_id, JournalOperation::Install)?;
+        let step = if install.method == BINARY_METHOD {
+            self.binary_step(&tool_config, &install, version)?
+        } else {
+            let command =
+                self.pinned_command(&install.method, install.package_name.as_deref(), version)?;
+            self.run_step(&command)?
         };
+        Ok(vec![step])
+    }
 
-        self.execute_install_command(&update_command).await?;
+    fn binary_step(
+        &self,
+        tool_config: &ToolConfig,
+        config: &InstallMethod,
+        version: &str,
+    ) -> Result<PlanStep, ToolError> {
+        check_release_version(version)?;
+        let template = config.url.as_deref().ok_or_else(|| {
+            ToolError::ConfigError(format!("{}: binary install requires url", tool_config.id))
+        })?;
+        Ok(PlanStep::InstallBinary {
+            url: render_url(template, version),
+            link: self.binaries.link_path(&tool_config.command),
+        })
+    }
 
-        // Clear status cache to force refresh
-        if let Ok(mut cache) = self.status_cache.lock() {
-            cache.remove(tool_id);
+    fn run_step(&self, command: &[String]) -> Result<PlanStep, ToolError> {
+        if command.is_empty() {
+            return Err(ToolError::ConfigError("Empty command".to_string()));
         }
-
-        debug!("Tool {} updated successfully", tool_id);
-        Ok(())
+        let (cmd, elevation) = self.prepare_command(command);
+        Ok(PlanStep::Run {
+            command: argv(&cmd),
+            privileged: elevation.is_some(),
+        })
     }
 
     /// Refresh all tool statuses
     pub async fn refresh_all_status(&self) -> Result<(), ToolError> {
         debug!("Refreshing all tool statuses");
 
-        let tools_config = {
+        let tool_ids = {
             let config_manager = self.config_manager.lock().unwrap();
-            config_manager.get_tools_config().clone()
+            let tools = &config_manager.get_tools_config().tools;
+            tools
+                .iter()
+                .map(|tool| tool.id.clone())
+                .collect::<StringVec>()
         };
 
-        for tool_config in &tools_config.tools {
-            if let Err(e) = self.check_tool_status(&tool_config.id).await {
-                warn!("Failed to check status for {}: {}", tool_config.id, e);
+        for (tool_id, result) in self.refresh_statuses(&tool_ids, |_| {}).await {
+            if let Err(e) = result {
+                warn!("Failed to check status for {}: {}", tool_id, e);
             }
         }
 
         Ok(())
     }
 
+    /// Check the status of several tools, a few at a time
+    ///
+    /// At most `behavior.refresh_concurrency` checks run at once and each starts a single
+    /// process; tools whose command is not on PATH are marked not installed without starting
+    /// anything. `on_event` hears about every tool as its check starts and ends. Results come
+    /// back in the order of `tool_ids`.
+    pub async fn refresh_statuses(
+        &self,
+        tool_ids: &[String],
+        on_event: impl Fn(RefreshEvent) + Send + Sync + 'static,
+    ) -> StatusResults {
+        let concurrency = {
+            let config_manager = self.config_manager.lock().unwrap();
+            config_manager
+                .get_app_settings()
+                .behavior
+                .refresh_concurrency
+        };
+        let slots = Arc::new(Semaphore::new(concurrency.max(1)));
+        let on_event = Arc::new(on_event);
+        let completed = Arc::new(AtomicUsize::new(0));
+        let total = tool_ids.len();
+
+        let mut tasks = JoinSet::new();
+        for (index, tool_id) in tool_ids.iter().cloned().enumerate() {
+            let manager = self.clone();
+            let slots = Arc::clone(&slots);
+            let on_event = Arc::clone(&on_event);
+            let completed = Arc::clone(&completed);
+
+            tasks.spawn(async move {
+                let reachable = {
+                    let config_manager = manager.config_manager.lock().unwrap();
+                    config_manager.get_tool_config(&tool_id).map(Self::on_path)
+                };
+                // 不在 PATH 上的工具不会启动进程，无需占用并发名额
+                let _slot = match reachable {
+                    Some(true) => Some(slots.acquire_owned().await),
+                    _ => None,
+                };
+                if reachable == Some(true) {
+                    on_event(RefreshEvent {
+                        tool_id: tool_id.clone(),
+                        stage: RefreshStage::Checking,
+                        completed: completed.load(Ordering::SeqCst),
+                        total,
+                    });
+                }
+
+                let result = manager.check_tool_status(&tool_id).await;
+                let stage = match (&result, reachable) {
+                    (Ok(_), Some(false)) => RefreshStage::NotOnPath,
+                    (Ok(status), _) => RefreshStage::Finished(status.clone()),
+                    (Err(e), _) => RefreshStage::Failed(e.to_string()),
+                };
+                on_event(RefreshEvent {
+                    tool_id: tool_id.clone(),
+                    stage,
+                    completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                    total,
+                });
+                (index, tool_id, result)
+            });
+        }
+
+        let mut results: Vec<_> = tool_ids.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, tool_id, result)) => results[index] = Some((tool_id, result)),
+                Err(e) => warn!("Status check task failed: {}", e),
+            }
+        }
+        results
+            .into_iter()
+            .zip(tool_ids)
+            .map(|(result, tool_id)| {
+                result.unwrap_or_else(|| {
+                    let error = ToolError::ExecutionFailed("status check task failed".to_string());
+                    (tool_id.clone(), Err(error))
+                })
+            })
+            .collect()
+    }
+
+    /// Reconcile installed tools with a project manifest
+    ///
+    /// With `apply` set, missing tools are installed and outdated ones updated;
+    /// otherwise the report only describes what is out of sync.
+    pub async fn sync_manifest(&self, manifest: &ProjectManifest, apply: bool) -> SyncReport {
+        let mut report = SyncReport::default();
+
+        let tool_ids: StringVec = manifest.tools.keys().cloned().collect();
+        for tool_id in self.dependency_order(&tool_ids) {
+            let requirement = &manifest.tools[&tool_id];
+            debug!("Syncing tool {} ({})", tool_id, requirement);
+            report
+                .entries
+                .push(self.sync_tool(&tool_id, requirement, apply).await);
+        }
+
+        report
+    }
+
+    async fn sync_tool(
+        &self,
+        tool_id: &str,
+        requirement: &VersionRequirement,
+        apply: bool,
+    ) -> SyncEntry {
+        let mut entry = SyncEntry {
+            tool_id: tool_id.to_string(),
+            requirement: requirement.to_string(),
+            version_before: None,
+            version_after: None,
+            action: SyncAction::Satisfied,
+        };
+
+        let status = match self.check_tool_status(tool_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                entry.action = SyncAction::Failed(e.to_string());
+                return entry;
+            }
+        };
+
+        let operation = match status {
+            ToolStatus::Installed { version } => {
+                entry.version_before = Some(version.clone());
+                if requirement.matches(&version) {
+                    entry.version_after = Some(version);
+                    return entry;
+                }
+                if !requirement.is_outdated(&version) {
+                    entry.action = SyncAction::Unsatisfied(format!(
+                        "Installed version {version} does not satisfy {requirement}"
+                    ));
+                    return entry;
+                }
+                if !apply {
+                    entry.action = SyncAction::Outdated;
+                    return entry;
+                }
+                SyncAction::Updated
+            }
+            ToolStatus::NotInstalled => {
+                if !apply {
+                    entry.action = SyncAction::Missing;
+                    return entry;
+                }
+                SyncAction::Installed
+            }
+            ToolStatus::Error(msg) => {
+                entry.action = SyncAction::Unsatisfied(msg);
+                return entry;
+            }
+            ToolStatus::Unknown => {
+                entry.action =
+                    SyncAction::Unsatisfied("Could not determine tool status".to_string());
+                return entry;
+            }
+        };
+
+        let result = if operation == SyncAction::Updated {
+            self.update_tool(tool_id).await
+        } else {
+            self.install_tool(tool_id).await
+        };
+        if let Err(e) = result {
+            entry.action = SyncAction::Failed(e.to_string());
+            return entry;
+        }
+
+        entry.action = match self.check_tool_status(tool_id).await {
+            Ok(ToolStatus::Installed { version }) => {
+                let satisfied = requirement.matches(&version);
+                entry.version_after = Some(version.clone());
+                if satisfied {
+                    operation
+                } else {
+                    SyncAction::Unsatisfied(format!(
+                        "Version {version} after sync still does not satisfy {requirement}"
+                    ))
+                }
+            }
+            Ok(_) => {
+                SyncAction::Unsatisfied("Tool is still not detected after installation".to_string())
+            }
+            Err(e) => SyncAction::Failed(e.to_string()),
+        };
+        entry
+    }
+
     /// Update cached status for a specific tool
     pub fn update_cached_status(&self, tool_id: &str, status: ToolStatus) {
         if let Ok(mut cache) = self.status_cache.lock() {
@@ -955,63 +1936,97 @@ impl ToolManager {
 
     // Private helper methods
 
-    /// Execute command with hidden window on Windows
-    #[cfg(windows)]
+    /// Execute command with hidden window on Windows, killing it after `timeout`
     async fn execute_hidden_command(
+        &self,
         command: &str,
         args: &[String],
+        timeout: Duration,
     ) -> Result<std::process::Output, ToolError> {
-        // Use std::process::Command with CREATE_NO_WINDOW flag, then convert to async
-        let command_str = format!("{} {}", command, args.join(" "));
-
-        // Use tokio::task::spawn_blocking to run std::process::Command in a blocking context
-        let result = tokio::task::spawn_blocking(move || {
-            let mut cmd = std::process::Command::new("cmd");
-            cmd.args(["/C", &command_str]);
-            cmd.creation_flags(CREATE_NO_WINDOW);
-            cmd.output()
-        })
-        .await;
+        run_command(
+            &mut hidden_command(command, args),
+            None,
+            &self.cancel,
+            timeout,
+        )
+        .await
+    }
 
-        match result {
-            Ok(Ok(output)) => Ok(output),
-            Ok(Err(e)) => Err(ToolError::ExecutionFailed(format!(
-                "Failed to execute command: {e}"
-            ))),
-            Err(e) => Err(ToolError::ExecutionFailed(format!(
-                "Task execution failed: {e}"
-            ))),
-        }
+    fn timeouts(&self) -> TimeoutSettings {
+        let config_manager = self.config_manager.lock().unwrap();
+        config_manager.get_app_settings().timeouts.clone()
     }
 
-    /// Execute command normally on non-Windows platforms
-    #[cfg(not(windows))]
-    async fn execute_hidden_command(
-        command: &str,
-        args: &[String],
-    ) -> Result<std::process::Output, ToolError> {
-        let mut cmd = Command::new(command);
-        cmd.args(args);
+    async fn is_tool_installed(&self, tool_config: &ToolConfig) -> bool {
+        self.probe_installed(tool_config).await.unwrap_or(false)
+    }
 
-        cmd.output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {e}")))
+    /// Like `is_tool_installed`, but a cancelled or timed out check is an error rather than
+    /// "not installed"
+    async fn probe_installed(&self, tool_config: &ToolConfig) -> Result<bool, ToolError> {
+        Ok(self
+            .run_version_check(tool_config)
+            .await?
+            .is_some_and(|output| output.status.success()))
     }
 
-    async fn is_tool_installed(&self, tool_config: &ToolConfig) -> bool {
-        let platform = std::env::consts::OS;
-        if let Some(version_check_args) = tool_config.version_check.get(platform) {
-            match Self::execute_hidden_command(&tool_config.command, version_check_args).await {
-                Ok(output) => output.status.success(),
-                Err(_) => false,
+    /// Installed state and version from a single run of the version check command
+    async fn probe_status(&self, tool_config: &ToolConfig) -> Result<ToolStatus, ToolError> {
+        let output = match self.run_version_check(tool_config).await {
+            Ok(Some(output)) if output.status.success() => output,
+            Ok(_) => return Ok(ToolStatus::NotInstalled),
+            Err(e @ ToolError::TimedOut(_)) => return Ok(ToolStatus::Error(e.to_string())),
+            Err(e) => return Err(e),
+        };
+
+        match self
+            .version_checker
+            .version_from_output(&self.with_selected_method(tool_config), &output)
+            .await
+        {
+            Ok(version) => Ok(ToolStatus::Installed { version }),
+            Err(e) => {
+                warn!("Failed to get version for {}: {}", tool_config.id, e);
+                Ok(ToolStatus::Error("Version check failed".to_string()))
             }
-        } else {
-            false
         }
     }
 
+    /// Run the tool's version check command
+    ///
+    /// Returns `None` without starting anything when the platform has no version check or the
+    /// command is not on PATH. Failing to start the command also counts as not installed.
+    async fn run_version_check(
+        &self,
+        tool_config: &ToolConfig,
+    ) -> Result<Option<std::process::Output>, ToolError> {
+        let platform = Platform::current();
+        let Some(version_check_args) = platform.lookup(&tool_config.version_check) else {
+            return Ok(None);
+        };
+        if !Self::on_path(tool_config) {
+            return Ok(None);
+        }
+
+        let timeout = Duration::from_secs(self.timeouts().version_check_secs);
+        match self
+            .execute_hidden_command(&tool_config.command, version_check_args, timeout)
+            .await
+        {
+            Ok(output) => Ok(Some(output)),
+            Err(e @ (ToolError::Cancelled(_) | ToolError::TimedOut(_))) => Err(e),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn on_path(tool_config: &ToolConfig) -> bool {
+        find_on_path(&tool_config.command).is_some()
+    }
+
     async fn get_tool_version(&self, tool_config: &ToolConfig) -> Result<String, ToolError> {
-        self.version_checker.get_current_version(tool_config).await
+        self.version_checker
+            .get_current_version(&self.with_selected_method(tool_config))
+            .await
     }
 
     async fn get_tool_status(&self, tool_id: &str) -> Result<ToolStatus, ToolError> {
@@ -1022,33 +2037,7 @@ impl ToolManager {
             }
         }
 
-        // If not cached, perform status check without using the public method to avoid recursion
-        let tool_config = {
-            let config_manager = self.config_manager.lock().unwrap();
-            config_manager
-                .get_tool_config(tool_id)
-                .ok_or_else(|| ToolError::NotFound(format!("Tool {tool_id} not found")))?
-                .clone()
-        };
-
-        let status = if self.is_tool_installed(&tool_config).await {
-            match self.get_tool_version(&tool_config).await {
-                Ok(version) => ToolStatus::Installed { version },
-                Err(e) => {
-                    warn!("Failed to get version for {}: {}", tool_id, e);
-                    ToolStatus::Error("Version check failed".to_string())
-                }
-            }
-        } else {
-            ToolStatus::NotInstalled
-        };
-
-        // Cache the status
-        if let Ok(mut cache) = self.status_cache.lock() {
-            cache.insert(tool_id.to_string(), status.clone());
-        }
-
-        Ok(status)
+        self.check_tool_status(tool_id).await
     }
 
     async fn get_cached_version_info(&self, _tool_id: &str) -> Option<VersionInfo> {
@@ -1062,28 +2051,340 @@ impl ToolManager {
         command: &str,
         args: &[String],
     ) -> Result<std::process::Output, ToolError> {
-        Self::execute_hidden_command(command, args).await
+        let timeout = Duration::from_secs(self.timeouts().help_secs);
+        self.execute_hidden_command(command, args, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppSettings, ToolsConfig};
+
+    // 类型别名以减少复杂度警告
+    type CatalogTool<'a> = (&'a str, &'a str);
+    type FakeCommand<'a> = (&'a str, String);
+
+    // 修改 PATH 的测试需要串行执行
+    static PATH_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Write `(name, script)` executables into `dir` and put it first on PATH, returning the
+    /// previous PATH
+    #[cfg(unix)]
+    fn prepend_commands(dir: &std::path::Path, scripts: &[FakeCommand]) -> std::ffi::OsString {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::create_dir_all(dir).unwrap();
+        for (name, body) in scripts {
+            let path = dir.join(name);
+            std::fs::write(&path, body).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let original_path = std::env::var_os("PATH").unwrap_or_default();
+        let mut paths = vec![dir.to_path_buf()];
+        paths.extend(std::env::split_paths(&original_path));
+        std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+        original_path
+    }
+
+    /// Manager over catalog tools given as `(id, install method JSON)`, each run as a command
+    /// named like its id, with a data directory of its own under `name`
+    fn manager_for(name: &str, tools: &[CatalogTool]) -> ToolManager {
+        let tools: Vec<String> = tools
+            .iter()
+            .map(|(id, install)| {
+                format!(
+                    r#"{{"id": "{id}", "name": "{id}", "description": "{id}",
+                        "website": "https://example.com", "command": "{id}",
+                        "version_check": ["--version"],
+                        "install": {{"windows": {install}, "macos": {install}, "linux": {install}}}}}"#
+                )
+            })
+            .collect();
+        let tools_config: ToolsConfig = serde_json::from_str(&format!(
+            r#"{{"version": "1.0", "tools": [{}]}}"#,
+            tools.join(",")
+        ))
+        .unwrap();
+
+        let mut settings = AppSettings::default();
+        settings.paths.data_directory = std::env::temp_dir()
+            .join(format!("cliverge-tool-{name}-{}", std::process::id()))
+            .display()
+            .to_string();
+        let mut config_manager = ConfigManager::new_with_settings(settings);
+        config_manager.set_tools_config(tools_config);
+        ToolManager::new(Arc::new(Mutex::new(config_manager)))
+    }
+
+    fn manager_with(method: &str) -> ToolManager {
+        let install = format!(r#"{{"method": "{method}", "package_name": "demo"}}"#);
+        manager_for("plan", &[("demo", &install)])
     }
 
-    /// Compare two semantic version strings
-    fn compare_versions(current: &str, latest: &str) -> std::cmp::Ordering {
-        // Simple version comparison - in production you'd want a proper semver library
-        let current_parts: Vec<u32> = current.split('.').filter_map(|s| s.parse().ok()).collect();
-        let latest_parts: Vec<u32> = latest.split('.').filter_map(|s| s.parse().ok()).collect();
+    #[test]
+    fn test_plan_version_install() {
+        let plan = manager_with("npm")
+            .plan_version_install("demo", "1.2.3")
+            .unwrap();
+        assert_eq!(
+            plan.alternatives,
+            vec![vec![PlanStep::Run {
+                command: ["npm", "install", "-g", "demo@1.2.3"]
+                    .map(String::from)
+                    .to_vec(),
+                privileged: false,
+            }]]
+        );
+
+        // 无法固定版本的安装方式直接报错，不会退回安装最新版本
+        assert!(matches!(
+            manager_with("go").plan_version_install("demo", "1.2.3"),
+            Err(ToolError::NotSupported(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refresh_statuses_runs_one_check_per_tool_within_limit() {
+        let _path_guard = PATH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // 每个版本检查记录开始和结束，用来确认同时只运行一个且每个工具只启动一次
+        let dir =
+            std::env::temp_dir().join(format!("cliverge-fake-refresh-{}", std::process::id()));
+        let log = dir.join("checks.log");
+        let log_path = log.display();
+        let check = |name: &str| {
+            format!(
+                "#!/bin/sh\necho \"start {name}\" >> '{log_path}'\nsleep 0.2\n\
+                 echo \"end {name}\" >> '{log_path}'\necho \"{name} 1.0.0\"\n"
+            )
+        };
+        let original_path = prepend_commands(
+            &dir,
+            &[
+                ("demo-first", check("demo-first")),
+                ("demo-second", check("demo-second")),
+            ],
+        );
+
+        let install = r#"{"method": "custom"}"#;
+        let manager = manager_for(
+            "refresh",
+            &[
+                ("demo-first", install),
+                ("demo-missing", install),
+                ("demo-second", install),
+            ],
+        );
+        {
+            let mut config_manager = manager.config_manager.lock().unwrap();
+            let mut settings = config_manager.get_app_settings().clone();
+            settings.behavior.refresh_concurrency = 1;
+            config_manager.update_app_settings(settings);
+        }
 
-        let max_len = std::cmp::max(current_parts.len(), latest_parts.len());
+        let ids: StringVec = ["demo-first", "demo-missing", "demo-second"]
+            .map(String::from)
+            .to_vec();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let results = runtime.block_on(manager.refresh_statuses(&ids, move |event| {
+            sink.lock().unwrap().push(event);
+        }));
+
+        // 结果按传入顺序返回
+        let statuses: Vec<_> = results
+            .into_iter()
+            .map(|(id, result)| (id, result.unwrap()))
+            .collect();
+        assert_eq!(statuses[0].0, "demo-first");
+        assert_eq!(statuses[1].0, "demo-missing");
+        assert_eq!(statuses[2].0, "demo-second");
+        assert!(matches!(&statuses[0].1, ToolStatus::Installed { version } if version == "1.0.0"));
+        assert!(matches!(statuses[1].1, ToolStatus::NotInstalled));
+        assert!(matches!(&statuses[2].1, ToolStatus::Installed { version } if version == "1.0.0"));
+
+        let events = events.lock().unwrap();
+        assert!(events.iter().all(|event| event.total == 3));
+        let ended: Vec<usize> = events
+            .iter()
+            .filter(|event| !matches!(event.stage, RefreshStage::Checking))
+            .map(|event| event.completed)
+            .collect();
+        assert_eq!(ended, [1, 2, 3]);
+
+        // 不在 PATH 上的工具只有一个 NotOnPath 事件
+        let missing: Vec<_> = events
+            .iter()
+            .filter(|event| event.tool_id == "demo-missing")
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert!(matches!(missing[0].stage, RefreshStage::NotOnPath));
+
+        // 并发数为 1 时，每个工具的 Checking 紧跟着它自己的 Finished
+        let reachable: Vec<_> = events
+            .iter()
+            .filter(|event| event.tool_id != "demo-missing")
+            .collect();
+        assert_eq!(reachable.len(), 4);
+        for pair in reachable.chunks(2) {
+            assert!(matches!(pair[0].stage, RefreshStage::Checking));
+            assert!(matches!(pair[1].stage, RefreshStage::Finished(_)));
+            assert_eq!(pair[0].tool_id, pair[1].tool_id);
+            // 期间可能有不在 PATH 上的工具完成
+            assert!(pair[1].completed > pair[0].completed);
+        }
+        assert_ne!(reachable[0].tool_id, reachable[2].tool_id);
+
+        let log = std::fs::read_to_string(&log).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 4, "{log}");
+        for pair in lines.chunks(2) {
+            let name = pair[0].strip_prefix("start ").unwrap();
+            assert_eq!(pair[1], format!("end {name}"), "{log}");
+        }
+        assert_ne!(lines[0], lines[2], "{log}");
 
-        for i in 0..max_len {
-            let current_part = current_parts.get(i).unwrap_or(&0);
-            let latest_part = latest_parts.get(i).unwrap_or(&0);
+        std::env::set_var("PATH", original_path);
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(manager.config_manager.lock().unwrap().get_data_dir());
+    }
 
-            match current_part.cmp(latest_part) {
-                std::cmp::Ordering::Less => return std::cmp::Ordering::Less,
-                std::cmp::Ordering::Greater => return std::cmp::Ordering::Greater,
-                std::cmp::Ordering::Equal => continue,
+    #[test]
+    fn test_install_locked_brew_is_advisory() {
+        let locked = LockedTool {
+            version: "2.45.0".to_string(),
+            method: "brew".to_string(),
+            package_name: Some("demo".to_string()),
+        };
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(manager_with("brew").install_locked_tool("demo", &locked));
+        match result {
+            Err(ToolError::NotSupported(message)) => {
+                assert!(message.contains("locked 2.45.0 is advisory"), "{message}")
             }
+            other => panic!("expected NotSupported, got {other:?}"),
         }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rollback_pins_apt_package_version() {
+        let _path_guard = PATH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // 用脚本模拟 sudo、apt 和 dpkg-query，状态文件保存包管理器的版本号
+        let dir = std::env::temp_dir().join(format!("cliverge-fake-apt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let state = dir.join("installed");
+        let log = dir.join("apt.log");
+        std::fs::write(&state, "1:2.43.0-1ubuntu7\n").unwrap();
+        let (state_path, log_path) = (state.display(), log.display());
+        let scripts = [
+            ("sudo", "#!/bin/sh\nexec \"$@\"\n".to_string()),
+            ("dpkg-query", format!("#!/bin/sh\ncat '{state_path}'\n")),
+            (
+                "apt",
+                format!(
+                    "#!/bin/sh\necho \"$*\" >> '{log_path}'\n\
+                     case \"$*\" in *--only-upgrade*) echo 1:2.45.0-1ubuntu1 > '{state_path}' ;; esac\n\
+                     for arg; do case $arg in demo-apt=*) echo \"${{arg#demo-apt=}}\" > '{state_path}' ;; esac; done\n"
+                ),
+            ),
+            (
+                "demo-apt",
+                format!(
+                    "#!/bin/sh\nv=$(cat '{state_path}'); v=${{v#*:}}; echo \"demo-apt version ${{v%-*}}\"\n"
+                ),
+            ),
+        ];
+        let original_path = prepend_commands(&dir, &scripts);
+
+        let manager = manager_for(
+            "rollback",
+            &[(
+                "demo-apt",
+                r#"{"method": "apt", "package_name": "demo-apt"}"#,
+            )],
+        );
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(manager.update_tool("demo-apt")).unwrap();
+        let history = manager.version_history("demo-apt");
+        assert_eq!(history.last().unwrap().version, "1:2.43.0-1ubuntu7");
+
+        let restored = runtime.block_on(manager.rollback_tool("demo-apt")).unwrap();
+        assert_eq!(restored, "1:2.43.0-1ubuntu7");
+        let log = std::fs::read_to_string(&log).unwrap();
+        assert!(
+            log.contains("install -y demo-apt=1:2.43.0-1ubuntu7"),
+            "{log}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&state).unwrap().trim(),
+            "1:2.43.0-1ubuntu7"
+        );
+
+        std::env::set_var("PATH", original_path);
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(manager.config_manager.lock().unwrap().get_data_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_script_runs_reviewed_script() {
+        let _path_guard = PATH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let dir = std::env::temp_dir().join(format!("cliverge-fake-script-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let state = dir.join("version");
+        std::fs::write(&state, "1.0.0\n").unwrap();
+        let state_path = state.display();
+        let original_path = prepend_commands(
+            &dir,
+            &[(
+                "demo-script",
+                format!("#!/bin/sh\necho \"demo-script $(cat '{state_path}')\"\n"),
+            )],
+        );
+
+        let manager = manager_for(
+            "update-script",
+            &[(
+                "demo-script",
+                r#"{"method": "script", "url": "https://example.com/install.sh"}"#,
+            )],
+        );
+        assert!(manager.uses_update_script("demo-script"));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(matches!(
+            runtime.block_on(manager.update_tool("demo-script")),
+            Err(ToolError::ReviewRequired(_))
+        ));
+
+        // 模拟 fetch_update_script 下载并经用户确认的脚本
+        let contents = format!("echo 2.0.0 > '{state_path}'\n");
+        let path = dir.join("update.sh");
+        std::fs::write(&path, &contents).unwrap();
+        let script = InstallScript {
+            tool_id: "demo-script".to_string(),
+            url: "https://example.com/install.sh".to_string(),
+            path: path.clone(),
+            interpreter: vec!["sh".to_string()],
+            sha256: crate::sha256_hex(contents.as_bytes()),
+            contents,
+            verified: false,
+        };
+
+        runtime.block_on(manager.update_script(&script)).unwrap();
+        assert_eq!(std::fs::read_to_string(&state).unwrap().trim(), "2.0.0");
+        assert!(!path.exists());
+        let history = manager.version_history("demo-script");
+        assert_eq!(history.last().unwrap().version, "1.0.0");
 
-        std::cmp::Ordering::Equal
+        std::env::set_var("PATH", original_path);
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(manager.config_manager.lock().unwrap().get_data_dir());
     }
 }
@@ -1,18 +1,18 @@
 //! Version checking functionality for CLI tools
 
-use crate::{ToolConfig, ToolError};
+use crate::{
+    compare_versions, find_version, find_versions, hidden_command, package_name, run_command,
+    BackendRegistry, CancellationToken, InstallMethod, MethodCandidates, OutputStream, Platform,
+    Registry, RegistryClient, RegistrySettings, SharedBackends, TimeoutSettings, ToolConfig,
+    ToolError, Version, VersionParse, BINARY_METHOD, CUSTOM_METHOD, SCRIPT_METHOD,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use tokio::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, error, warn};
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
-
-#[cfg(windows)]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionInfo {
     pub current: Option<String>,
@@ -20,6 +20,9 @@ pub struct VersionInfo {
     pub update_available: bool,
     pub check_method: String,
     pub last_checked: chrono::DateTime<chrono::Utc>,
+    /// Release notes of the latest version, when the source links them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changelog_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,9 +30,11 @@ pub enum VersionCheckStrategy {
     Auto,           // Automatically select best method
     SelfCheck,      // Use tool's own update check
     PackageManager, // Use package manager
+    GitHubRelease,  // Use the latest release of the tool's `github_repo`
     LocalDatabase,  // Use local version database
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for VersionCheckStrategy {
     fn default() -> Self {
         Self::Auto
@@ -39,6 +44,10 @@ impl Default for VersionCheckStrategy {
 #[derive(Clone)]
 pub struct VersionChecker {
     local_db: Option<VersionDatabase>,
+    backends: SharedBackends,
+    registry: RegistryClient,
+    cancel: CancellationToken,
+    timeout: Duration,
 }
 
 impl Default for VersionChecker {
@@ -49,51 +58,52 @@ impl Default for VersionChecker {
 
 impl VersionChecker {
     pub fn new() -> Self {
+        Self::with_backends(Arc::new(Mutex::new(BackendRegistry::with_defaults())))
+    }
+
+    /// Use a registry shared with the `ToolManager`, so registered backends apply to version checks
+    pub fn with_backends(backends: SharedBackends) -> Self {
         let local_db = VersionDatabase::load().ok();
-        Self { local_db }
+        Self {
+            local_db,
+            backends,
+            registry: RegistryClient::new(RegistrySettings::default()),
+            cancel: CancellationToken::new(),
+            timeout: Duration::from_secs(TimeoutSettings::default().version_check_secs),
+        }
     }
 
-    /// Execute command with hidden window on Windows
-    #[cfg(windows)]
-    async fn execute_hidden_command(
-        command: &str,
-        args: &[String],
-    ) -> Result<std::process::Output, ToolError> {
-        // Use std::process::Command with CREATE_NO_WINDOW flag, then convert to async
-        let command_str = format!("{} {}", command, args.join(" "));
-
-        // Use tokio::task::spawn_blocking to run std::process::Command in a blocking context
-        let result = tokio::task::spawn_blocking(move || {
-            let mut cmd = std::process::Command::new("cmd");
-            cmd.args(["/C", &command_str]);
-            cmd.creation_flags(CREATE_NO_WINDOW);
-            cmd.output()
-        })
-        .await;
+    /// Stop this checker's commands when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
 
-        match result {
-            Ok(Ok(output)) => Ok(output),
-            Ok(Err(e)) => Err(ToolError::ExecutionFailed(format!(
-                "Failed to execute command: {e}"
-            ))),
-            Err(e) => Err(ToolError::ExecutionFailed(format!(
-                "Task execution failed: {e}"
-            ))),
-        }
+    /// Look up latest versions through `registry`, e.g. one pointing at mirrors
+    pub fn with_registry(mut self, registry: RegistryClient) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Limit how long a single version command may run
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    /// Execute command normally on non-Windows platforms
-    #[cfg(not(windows))]
+    /// Execute command with hidden window on Windows
     async fn execute_hidden_command(
+        &self,
         command: &str,
         args: &[String],
     ) -> Result<std::process::Output, ToolError> {
-        let mut cmd = Command::new(command);
-        cmd.args(args);
-
-        cmd.output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to execute command: {}", e)))
+        run_command(
+            &mut hidden_command(command, args),
+            None,
+            &self.cancel,
+            self.timeout,
+        )
+        .await
     }
 
     /// Main entry point for version checking
@@ -112,6 +122,7 @@ impl VersionChecker {
             VersionCheckStrategy::PackageManager => {
                 self.check_via_package_manager(tool_config).await
             }
+            VersionCheckStrategy::GitHubRelease => self.check_via_github_release(tool_config).await,
             VersionCheckStrategy::LocalDatabase => self.check_via_local_database(tool_config).await,
             VersionCheckStrategy::Auto => self.auto_check(tool_config).await,
         };
@@ -132,18 +143,43 @@ impl VersionChecker {
     pub async fn get_current_version(&self, tool_config: &ToolConfig) -> Result<String, ToolError> {
         debug!("Getting current version for: {}", tool_config.id);
 
-        let platform = std::env::consts::OS;
-        let version_check_args = tool_config.version_check.get(platform).ok_or_else(|| {
+        let platform = Platform::current();
+        let version_check_args = platform.lookup(&tool_config.version_check).ok_or_else(|| {
             ToolError::NotSupported(format!(
                 "Platform {platform} not supported for version check"
             ))
         })?;
 
-        let output = Self::execute_hidden_command(&tool_config.command, version_check_args).await?;
+        let output = self
+            .execute_hidden_command(&tool_config.command, version_check_args)
+            .await?;
+        self.version_from_output(tool_config, &output).await
+    }
 
+    /// Current version from the output of the tool's version check command
+    pub async fn version_from_output(
+        &self,
+        tool_config: &ToolConfig,
+        output: &std::process::Output,
+    ) -> Result<String, ToolError> {
+        let platform = Platform::current();
         if output.status.success() {
-            let version_str = String::from_utf8_lossy(&output.stdout);
-            let version = Self::parse_version_string(&version_str);
+            let mut version = Self::extract_version(
+                tool_config.version_parse.as_ref(),
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            );
+            if version == "unknown" {
+                // The tool's output had no version; the package manager may still know it
+                if let Some(install_config) = platform
+                    .lookup(&tool_config.install)
+                    .and_then(MethodCandidates::primary)
+                {
+                    if let Some(installed) = self.query_installed_version(install_config).await {
+                        version = installed;
+                    }
+                }
+            }
             debug!("Current version for {}: {}", tool_config.id, version);
             Ok(version)
         } else {
@@ -161,10 +197,26 @@ impl VersionChecker {
             tool_config.id
         );
 
-        // Priority 1: Tool's own update check
+        // GitHub releases come first only when no package manager owns the install;
+        // otherwise a release the package manager cannot install yet would show as an update
+        let platform = Platform::current();
+        let releases_first = platform
+            .lookup(&tool_config.install)
+            .and_then(MethodCandidates::primary)
+            .is_some_and(|install| {
+                [BINARY_METHOD, SCRIPT_METHOD, CUSTOM_METHOD].contains(&install.method.as_str())
+            });
+
+        // Priority 1: GitHub releases, for tools installed outside a package manager
+        if releases_first {
+            if let Some(result) = self.try_github_release(tool_config).await {
+                return Ok(result);
+            }
+        }
+
+        // Priority 2: Tool's own update check
         if let Some(update_check_configs) = &tool_config.update_check {
-            let platform = std::env::consts::OS;
-            if let Some(update_check) = update_check_configs.get(platform) {
+            if let Some(update_check) = platform.lookup(update_check_configs) {
                 if !update_check.is_empty() {
                     if let Ok(result) = self.check_via_self_update(tool_config).await {
                         debug!("Auto-check succeeded with self-update method");
@@ -178,9 +230,22 @@ impl VersionChecker {
             }
         }
 
-        // Priority 2: Package manager
-        if let Ok(result) = self.check_via_package_manager(tool_config).await {
-            debug!("Auto-check succeeded with package manager method");
+        // Priority 3: Package manager
+        let package_manager = match self.check_via_package_manager(tool_config).await {
+            Ok(result) if result.latest.is_some() => {
+                debug!("Auto-check succeeded with package manager method");
+                return Ok(result);
+            }
+            other => other,
+        };
+
+        // Priority 4: GitHub releases, when the package manager knows no latest version
+        if !releases_first {
+            if let Some(result) = self.try_github_release(tool_config).await {
+                return Ok(result);
+            }
+        }
+        if let Ok(result) = package_manager {
             return Ok(result);
         }
         warn!(
@@ -188,11 +253,51 @@ impl VersionChecker {
             tool_config.id
         );
 
-        // Priority 3: Local database
+        // Priority 5: Local database
         self.check_via_local_database(tool_config).await
     }
 
+    /// GitHub release check for tools with a `github_repo`, when it finds a release
+    async fn try_github_release(&self, tool_config: &ToolConfig) -> Option<VersionInfo> {
+        tool_config.github_repo.as_ref()?;
+        match self.check_via_github_release(tool_config).await {
+            Ok(result) if result.latest.is_some() => {
+                debug!("Auto-check succeeded with GitHub releases");
+                Some(result)
+            }
+            Ok(_) => {
+                warn!(
+                    "No usable GitHub release for {}, trying other sources",
+                    tool_config.id
+                );
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "GitHub release check failed for {}, trying other sources: {}",
+                    tool_config.id, e
+                );
+                None
+            }
+        }
+    }
+
     /// Use tool's own update checking mechanism
+    /// Full update check command for this platform: `update_check` holds arguments to the
+    /// tool's `command`, which older catalogs also spelled out as the first element
+    pub fn update_check_command(tool_config: &ToolConfig) -> Option<Vec<String>> {
+        let args = Platform::current().lookup(tool_config.update_check.as_ref()?)?;
+        if args.is_empty() {
+            return None;
+        }
+        if args[0] == tool_config.command {
+            return Some(args.clone());
+        }
+        let mut command = vec![tool_config.command.clone()];
+        command.extend(args.iter().cloned());
+        Some(command)
+    }
+
     async fn check_via_self_update(
         &self,
         tool_config: &ToolConfig,
@@ -201,28 +306,20 @@ impl VersionChecker {
 
         let current = self.get_current_version(tool_config).await.ok();
 
-        let latest = if let Some(update_check_configs) = &tool_config.update_check {
-            let platform = std::env::consts::OS;
-            if let Some(update_cmd) = update_check_configs.get(platform) {
-                if !update_cmd.is_empty() {
-                    let output =
-                        Self::execute_hidden_command(&update_cmd[0], &update_cmd[1..]).await;
-
-                    match output {
-                        Ok(output) if output.status.success() => {
-                            let output_str = String::from_utf8_lossy(&output.stdout);
-                            Some(Self::parse_latest_version_from_output(&output_str))
-                        }
-                        _ => {
-                            warn!("Update check command failed for {}", tool_config.id);
-                            None
-                        }
-                    }
-                } else {
+        let latest = if let Some(update_cmd) = Self::update_check_command(tool_config) {
+            let output = self
+                .execute_hidden_command(&update_cmd[0], &update_cmd[1..])
+                .await;
+
+            match output {
+                Ok(output) if output.status.success() => {
+                    let output_str = String::from_utf8_lossy(&output.stdout);
+                    Some(Self::parse_latest_version_from_output(&output_str))
+                }
+                _ => {
+                    warn!("Update check command failed for {}", tool_config.id);
                     None
                 }
-            } else {
-                None
             }
         } else {
             None
@@ -236,6 +333,37 @@ impl VersionChecker {
             update_available,
             check_method: "self-check".to_string(),
             last_checked: chrono::Utc::now(),
+            changelog_url: None,
+        })
+    }
+
+    /// Check version against the latest release of the tool's GitHub repository
+    async fn check_via_github_release(
+        &self,
+        tool_config: &ToolConfig,
+    ) -> Result<VersionInfo, ToolError> {
+        let repo = tool_config.github_repo.as_deref().ok_or_else(|| {
+            ToolError::NotSupported(format!("{} has no github_repo", tool_config.id))
+        })?;
+        debug!("Checking version via GitHub releases of {}", repo);
+
+        let current = self.get_current_version(tool_config).await.ok();
+        let release = self
+            .http_lookup(
+                format!("GitHub release lookup for {repo}"),
+                self.registry.latest_github_release(repo),
+            )
+            .await?;
+        let latest = release.as_ref().map(|r| r.latest_version.clone());
+        let update_available = Self::compare_versions(&current, &latest);
+
+        Ok(VersionInfo {
+            current,
+            latest,
+            update_available,
+            check_method: "github-release".to_string(),
+            last_checked: chrono::Utc::now(),
+            changelog_url: release.and_then(|r| r.changelog_url),
         })
     }
 
@@ -250,19 +378,20 @@ impl VersionChecker {
         );
 
         let current = self.get_current_version(tool_config).await.ok();
-        let platform = std::env::consts::OS;
+        let platform = Platform::current();
 
-        let install_config = tool_config
-            .install
-            .get(platform)
+        let install_config = platform
+            .lookup(&tool_config.install)
+            .and_then(MethodCandidates::primary)
             .ok_or_else(|| ToolError::NotSupported(format!("Platform {platform} not supported")))?;
 
-        let latest = match install_config.method.as_str() {
-            "npm" => self.check_npm_version(tool_config).await.ok(),
-            "brew" => self.check_brew_version(tool_config).await.ok(),
-            "pip" => self.check_pip_version(tool_config).await.ok(),
-            _ => {
-                warn!("Unsupported package manager: {}", install_config.method);
+        let latest = match self.query_latest_version(install_config).await {
+            Ok(latest) => latest,
+            Err(e) => {
+                warn!(
+                    "Latest version lookup via {} failed: {}",
+                    install_config.method, e
+                );
                 None
             }
         };
@@ -275,9 +404,93 @@ impl VersionChecker {
             update_available,
             check_method: format!("package-manager-{}", install_config.method),
             last_checked: chrono::Utc::now(),
+            changelog_url: None,
         })
     }
 
+    /// Ask the install method's backend for the newest available version
+    async fn query_latest_version(
+        &self,
+        install_config: &InstallMethod,
+    ) -> Result<Option<String>, ToolError> {
+        if let Some(registry) = Registry::for_method(&install_config.method) {
+            match self.query_registry(registry, install_config).await {
+                Ok(latest) => return Ok(latest),
+                Err(e @ ToolError::Cancelled(_)) => return Err(e),
+                Err(e) => warn!(
+                    "{} lookup failed, asking the package manager: {}",
+                    registry, e
+                ),
+            }
+        }
+
+        let backend = self.backends.lock().unwrap().get(&install_config.method)?;
+        let command = backend.latest_version_command(install_config)?;
+        let output = self
+            .execute_hidden_command(&command[0], &command[1..])
+            .await?;
+        if !output.status.success() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "{} failed: {}",
+                command.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(backend.parse_latest_version(install_config, &String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Read the latest version from the package's registry metadata
+    async fn query_registry(
+        &self,
+        registry: Registry,
+        install_config: &InstallMethod,
+    ) -> Result<Option<String>, ToolError> {
+        let package = package_name(install_config)?;
+        self.http_lookup(
+            format!("{registry} lookup for {package}"),
+            self.registry.latest_version(registry, package),
+        )
+        .await
+    }
+
+    /// Run an HTTP lookup under this checker's cancellation token and time limit
+    async fn http_lookup<T>(
+        &self,
+        what: String,
+        lookup: impl std::future::Future<Output = Result<T, ToolError>>,
+    ) -> Result<T, ToolError> {
+        let lookup = self.cancel.guard(&what, lookup);
+        tokio::time::timeout(self.timeout, lookup)
+            .await
+            .map_err(|_| {
+                ToolError::TimedOut(format!(
+                    "{what} did not finish within {}s",
+                    self.timeout.as_secs()
+                ))
+            })?
+    }
+
+    /// Ask the package manager which version it installed
+    pub async fn query_installed_version(&self, install_config: &InstallMethod) -> Option<String> {
+        let backend = self
+            .backends
+            .lock()
+            .ok()?
+            .get(&install_config.method)
+            .ok()?;
+        let command = backend.installed_version_command(install_config).ok()?;
+        let output = self
+            .execute_hidden_command(&command[0], &command[1..])
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        backend.parse_installed_version(install_config, &String::from_utf8_lossy(&output.stdout))
+    }
+
     /// Check version via local database
     async fn check_via_local_database(
         &self,
@@ -304,123 +517,59 @@ impl VersionChecker {
             update_available,
             check_method: "local-database".to_string(),
             last_checked: chrono::Utc::now(),
+            changelog_url: None,
         })
     }
 
-    /// Check NPM package version
-    async fn check_npm_version(&self, tool_config: &ToolConfig) -> Result<String, ToolError> {
-        let package_name = Self::extract_package_name_from_config(tool_config, "npm")?;
-
-        let output = Command::new("npm")
-            .args(["view", &package_name, "version"])
-            .output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("NPM command failed: {e}")))?;
-
-        if output.status.success() {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            Ok(version)
-        } else {
-            Err(ToolError::NotFound(format!(
-                "NPM package {package_name} not found"
-            )))
-        }
-    }
-
-    /// Check Homebrew formula version
-    async fn check_brew_version(&self, tool_config: &ToolConfig) -> Result<String, ToolError> {
-        let formula_name = Self::extract_package_name_from_config(tool_config, "brew")?;
-
-        let output = Command::new("brew")
-            .args(["info", &formula_name, "--json=v1"])
-            .output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Brew command failed: {e}")))?;
-
-        if output.status.success() {
-            let json_str = String::from_utf8_lossy(&output.stdout);
-            let json: serde_json::Value = serde_json::from_str(&json_str)
-                .map_err(|e| ToolError::ParseError(format!("Failed to parse brew JSON: {e}")))?;
-
-            if let Some(versions) = json.as_array() {
-                if let Some(first_formula) = versions.first() {
-                    if let Some(versions_obj) = first_formula.get("versions") {
-                        if let Some(stable) = versions_obj.get("stable") {
-                            if let Some(version) = stable.as_str() {
-                                return Ok(version.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Err(ToolError::NotFound(format!(
-            "Brew formula {formula_name} not found"
-        )))
-    }
-
-    /// Check pip package version
-    async fn check_pip_version(&self, tool_config: &ToolConfig) -> Result<String, ToolError> {
-        let package_name = Self::extract_package_name_from_config(tool_config, "pip")?;
-
-        let output = Command::new("pip")
-            .args(["index", "versions", &package_name])
-            .output()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Pip command failed: {e}")))?;
-
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            // Parse pip output to extract latest version
-            // Format: "package (x.y.z)"
-            if let Some(version) = Self::parse_pip_version_output(&output_str) {
-                Ok(version)
-            } else {
-                Err(ToolError::ParseError(
-                    "Failed to parse pip version output".to_string(),
-                ))
-            }
-        } else {
-            Err(ToolError::NotFound(format!(
-                "Pip package {package_name} not found"
-            )))
+    /// The tool's version in its version check output, read with its `version_parse` rules
+    /// when it has them and otherwise guessed from stdout; "unknown" when none is found
+    pub fn extract_version(rules: Option<&VersionParse>, stdout: &str, stderr: &str) -> String {
+        match rules {
+            Some(rules) => Self::apply_version_parse(rules, stdout, stderr)
+                .unwrap_or_else(|| "unknown".to_string()),
+            None => Self::parse_version_string(stdout),
         }
     }
 
-    /// Extract package name from tool config
-    fn extract_package_name_from_config(
-        tool_config: &ToolConfig,
-        method: &str,
-    ) -> Result<String, ToolError> {
-        let platform = std::env::consts::OS;
-        let install_config = tool_config
-            .install
-            .get(platform)
-            .ok_or_else(|| ToolError::NotSupported(format!("Platform {platform} not supported")))?;
-
-        if install_config.method != method {
-            return Err(ToolError::NotSupported(format!(
-                "Method {method} not supported"
-            )));
-        }
-
-        // Try to get package name from config first
-        if let Some(package_name) = &install_config.package_name {
-            return Ok(package_name.clone());
-        }
-
-        // Fallback: extract from install command
-        if let Some(command) = &install_config.command {
-            if let Some(package_name) = command.last() {
-                return Ok(package_name.clone());
+    fn apply_version_parse(rules: &VersionParse, stdout: &str, stderr: &str) -> Option<String> {
+        let output = match rules.stream.unwrap_or_default() {
+            OutputStream::Stdout => stdout,
+            OutputStream::Stderr => stderr,
+        };
+        let nth = rules.group.unwrap_or(1).checked_sub(1)?;
+
+        if let Some(pointer) = &rules.json_pointer {
+            let document: serde_json::Value = serde_json::from_str(output.trim()).ok()?;
+            let value = match document.pointer(pointer)? {
+                serde_json::Value::String(value) => value.trim().to_string(),
+                serde_json::Value::Number(value) => value.to_string(),
+                _ => return None,
+            };
+            // 单独的 "20" 这类值本身就是版本号
+            if nth == 0 && Version::parse(&value).is_some() {
+                return Some(value.trim_start_matches(['v', 'V']).to_string());
             }
+            return find_versions(&value).nth(nth).map(str::to_string);
         }
 
-        Err(ToolError::ConfigError(format!(
-            "No package name found for {}",
-            tool_config.id
-        )))
+        output
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_start();
+                let line = match &rules.line_prefix {
+                    Some(prefix) => line.strip_prefix(prefix.as_str())?,
+                    None => line,
+                };
+                match &rules.anchor {
+                    Some(anchor) => line
+                        .find(anchor.as_str())
+                        .map(|pos| &line[pos + anchor.len()..]),
+                    None => Some(line),
+                }
+            })
+            .flat_map(find_versions)
+            .nth(nth)
+            .map(str::to_string)
     }
 
     /// Parse version string from command output using simple string matching
@@ -458,46 +607,9 @@ impl VersionChecker {
         Self::find_version_number(&line)
     }
 
-    /// Find version number pattern (x.y.z or x.y.z.w) in text
+    /// Find a version number such as `2.43`, `1.2.3` or `1.0.0-beta.1` in text
     fn find_version_number(text: &str) -> Option<String> {
-        let chars: Vec<char> = text.chars().collect();
-        let mut i = 0;
-
-        while i < chars.len() {
-            if chars[i].is_ascii_digit() {
-                // Found start of potential version number
-                let _start = i;
-                let mut version_chars = Vec::new();
-                let mut dot_count = 0;
-
-                while i < chars.len() {
-                    let ch = chars[i];
-                    if ch.is_ascii_digit() {
-                        version_chars.push(ch);
-                    } else if ch == '.' && dot_count < 3 {
-                        version_chars.push(ch);
-                        dot_count += 1;
-                    } else {
-                        break;
-                    }
-                    i += 1;
-                }
-
-                // Valid version must have at least 2 dots (x.y.z)
-                if dot_count >= 2 {
-                    let version: String = version_chars.into_iter().collect();
-                    // Remove trailing dot if any
-                    if version.ends_with('.') {
-                        return Some(version[..version.len() - 1].to_string());
-                    }
-                    return Some(version);
-                }
-            } else {
-                i += 1;
-            }
-        }
-
-        None
+        find_version(text).map(str::to_string)
     }
 
     /// Parse latest version from update check output
@@ -528,23 +640,6 @@ impl VersionChecker {
         Self::parse_version_string(output)
     }
 
-    /// Parse pip version output
-    fn parse_pip_version_output(output: &str) -> Option<String> {
-        // pip index versions output format varies, try to parse common formats
-        let lines: Vec<&str> = output.lines().collect();
-        for line in &lines {
-            if line.contains("Available versions:") {
-                if let Some(version_line) = lines
-                    .iter()
-                    .find(|l| l.trim().starts_with(char::is_numeric))
-                {
-                    return Some(version_line.split_whitespace().next()?.to_string());
-                }
-            }
-        }
-        None
-    }
-
     /// Compare versions and determine if update is available
     fn compare_versions(current: &Option<String>, latest: &Option<String>) -> bool {
         match (current, latest) {
@@ -559,27 +654,10 @@ impl VersionChecker {
         }
     }
 
-    /// Simple semantic version comparison
+    /// Whether `new_version` comes after `current_version` in SemVer order; versions that
+    /// cannot be parsed are never newer
     fn is_version_newer(new_version: &str, current_version: &str) -> bool {
-        let parse_version = |v: &str| -> Vec<u32> {
-            v.trim_start_matches('v')
-                .split('.')
-                .filter_map(|part| part.parse::<u32>().ok())
-                .collect()
-        };
-
-        let new_parts = parse_version(new_version);
-        let current_parts = parse_version(current_version);
-
-        for (new_part, current_part) in new_parts.iter().zip(current_parts.iter()) {
-            match new_part.cmp(current_part) {
-                std::cmp::Ordering::Greater => return true,
-                std::cmp::Ordering::Less => return false,
-                std::cmp::Ordering::Equal => continue,
-            }
-        }
-
-        new_parts.len() > current_parts.len()
+        compare_versions(new_version, current_version) == Some(std::cmp::Ordering::Greater)
     }
 }
 
@@ -676,14 +754,81 @@ mod tests {
         );
         assert_eq!(
             VersionChecker::parse_version_string("foo 1.0.0-beta"),
-            "1.0.0"
+            "1.0.0-beta"
         );
+        assert_eq!(
+            VersionChecker::parse_version_string("git version 2.43.0.windows.1"),
+            "2.43.0"
+        );
+        assert_eq!(VersionChecker::parse_version_string("Python 3.12"), "3.12");
         assert_eq!(
             VersionChecker::parse_version_string("no version here"),
             "unknown"
         );
     }
 
+    #[test]
+    fn test_version_parse_rules() {
+        let rules = |json: &str| -> VersionParse { serde_json::from_str(json).unwrap() };
+        let output = "Node.js v20.11.1\nmytool/1.4.2 linux-x64 api 2023.10\n";
+
+        assert_eq!(VersionChecker::extract_version(None, output, ""), "20.11.1");
+        assert_eq!(
+            VersionChecker::extract_version(
+                Some(&rules(r#"{"line_prefix": "mytool"}"#)),
+                output,
+                ""
+            ),
+            "1.4.2"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(Some(&rules(r#"{"anchor": "api"}"#)), output, ""),
+            "2023.10"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(Some(&rules(r#"{"group": 2}"#)), output, ""),
+            "1.4.2"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(
+                Some(&rules(r#"{"stream": "stderr", "anchor": "version "}"#)),
+                "",
+                "warning: old config\nmytool version 0.9.0-rc.1\n"
+            ),
+            "0.9.0-rc.1"
+        );
+
+        let json = r#"{"client": {"version": "v1.29.0"}, "node": "20"}"#;
+        assert_eq!(
+            VersionChecker::extract_version(
+                Some(&rules(r#"{"json_pointer": "/client/version"}"#)),
+                json,
+                ""
+            ),
+            "1.29.0"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(Some(&rules(r#"{"json_pointer": "/node"}"#)), json, ""),
+            "20"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(
+                Some(&rules(r#"{"json_pointer": "/missing"}"#)),
+                json,
+                ""
+            ),
+            "unknown"
+        );
+        assert_eq!(
+            VersionChecker::extract_version(
+                Some(&rules(r#"{"line_prefix": "other"}"#)),
+                output,
+                ""
+            ),
+            "unknown"
+        );
+    }
+
     #[test]
     fn test_version_comparison() {
         assert!(VersionChecker::is_version_newer("1.2.3", "1.2.2"));
@@ -691,5 +836,9 @@ mod tests {
         assert!(VersionChecker::is_version_newer("2.0.0", "1.9.9"));
         assert!(!VersionChecker::is_version_newer("1.2.2", "1.2.3"));
         assert!(!VersionChecker::is_version_newer("1.2.3", "1.2.3"));
+        assert!(VersionChecker::is_version_newer("1.0.0", "1.0.0-beta"));
+        assert!(VersionChecker::is_version_newer("1.10", "1.9.5"));
+        assert!(!VersionChecker::is_version_newer("2.43", "2.43.0"));
+        assert!(!VersionChecker::is_version_newer("current", "1.0.0"));
     }
 }
@@ -2,8 +2,11 @@
 #![allow(clippy::await_holding_lock, clippy::empty_line_after_outer_attr)]
 
 use cliverge_core::{
-    AppSettings, AppearanceSettings, BehaviorSettings, CacheManager, ConfigManager, ToolInfo,
-    ToolManager, ToolStatus,
+    AppSettings, AppearanceSettings, BehaviorSettings, CacheManager, CancellationToken,
+    CommandLocation, CommandSequence, ConfigManager, InstallScript, JournalEntry, JournalOperation,
+    JournalQuery, MethodCandidates, MissingDependency, OperationPlan, OutputLine, OutputStream,
+    PlanStep, RefreshEvent, RefreshStage, SelectionReason, ToolDependency, ToolInfo, ToolManager,
+    ToolStatus, VersionChecker, VersionParse,
 };
 use eframe::egui;
 use std::collections::HashMap;
@@ -20,8 +23,25 @@ type ProgressReceiver =
     Arc<Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<StatusCheckProgress>>>>;
 type InstallSender = Arc<Mutex<Option<tokio::sync::mpsc::UnboundedSender<InstallProgress>>>>;
 type InstallReceiver = Arc<Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<InstallProgress>>>>;
-type LogEntry = (Instant, String);
+type LogEntry = (Instant, String, Option<OperationKey>, Option<CancelKey>);
+type OperationKey = (String, InstallOperation);
+type OperationOutput = HashMap<OperationKey, Vec<String>>;
+type CancelTokens = HashMap<CancelKey, CancellationToken>;
+type PrerequisitePromptSlot = Arc<Mutex<Option<PrerequisitePrompt>>>;
+type ScriptPromptSlot = Arc<Mutex<Option<InstallScript>>>;
+type CommandTestSlot = Arc<Mutex<Option<CommandTestOutput>>>;
 type UpdateConfigMethods = std::collections::HashMap<String, Vec<String>>;
+type PlatformCandidates = HashMap<String, MethodCandidates>;
+
+/// 工具编辑表单中可编辑的平台
+const FORM_PLATFORMS: [&str; 3] = ["windows", "macos", "linux"];
+
+/// 历史视图最多加载的条目数
+const HISTORY_VIEW_LIMIT: usize = 500;
+/// 每个操作保留的输出行数
+const OUTPUT_LINE_LIMIT: usize = 5000;
+/// 操作进行中时日志里显示的最新输出行数
+const OUTPUT_TAIL_LINES: usize = 6;
 type LoadResult = Result<cliverge_core::ToolsConfig, Box<dyn std::error::Error>>;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -30,9 +50,33 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub enum AppView {
     Main,
     Settings,
+    History,
     About,
 }
 
+/// 安装前缺少前置工具时等待用户确认
+#[derive(Debug, Clone)]
+pub struct PrerequisitePrompt {
+    pub tool_id: String,
+    pub missing: Vec<MissingDependency>,
+}
+
+/// 日志中可取消的任务：状态刷新或安装类操作
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CancelKey {
+    /// 整轮状态刷新
+    Refresh,
+    Operation(OperationKey),
+}
+
+/// 安装任务的执行方式
+enum InstallMode {
+    Plain,
+    WithDependencies,
+    /// 用户已确认脚本内容
+    ReviewedScript(InstallScript),
+}
+
 #[derive(Debug, Clone)]
 pub struct Notification {
     pub message: String,
@@ -76,12 +120,13 @@ pub struct InstallProgress {
     pub timestamp: Instant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InstallOperation {
     Install,
     Uninstall,
     #[allow(dead_code)] // 为未来的更新功能预留
     Update,
+    Rollback,
 }
 
 pub struct AppState {
@@ -94,8 +139,13 @@ pub struct AppState {
     pub current_view: AppView,
     pub status_progress: Vec<StatusCheckProgress>,
     pub install_progress: Vec<InstallProgress>, // 新增此行
+    pub operation_output: OperationOutput,      // 每个操作的命令输出
     pub is_refreshing: bool,
 
+    // Operation history view state
+    pub history_entries: Vec<JournalEntry>,
+    pub history_tool_filter: String,
+
     // Tool configuration editor state
     pub show_tool_editor: bool,
     pub editing_tool_id: Option<String>, // None for new tool, Some(id) for editing
@@ -105,6 +155,9 @@ pub struct AppState {
     pub show_delete_confirmation: bool,
     pub tool_to_delete: Option<String>,
 
+    // 操作执行前的命令确认对话框
+    pub pending_plan: Option<OperationPlan>,
+
     // Settings state (live editing)
     pub settings_theme: String,
     pub settings_font_size: f32,
@@ -112,6 +165,7 @@ pub struct AppState {
     pub settings_check_interval: u32,
     pub settings_show_notifications: bool,
     pub settings_auto_refresh_on_startup: bool,
+    pub settings_refresh_concurrency: usize,
     pub settings_debug_mode: bool,
     pub settings_experimental_features: bool,
 
@@ -127,7 +181,9 @@ pub struct ToolFormState {
     pub name: String,
     pub description: String,
     pub website: String,
+    pub github_repo: String, // 例如 "cli/cli"
     pub command: String,
+    pub depends_on: String, // 例如 "node>=20, git"
 
     // Version check methods (per platform)
     pub version_check_methods: std::collections::HashMap<String, String>, // 平台 -> 版本检查参数
@@ -141,17 +197,73 @@ pub struct ToolFormState {
     // Update methods (per platform)
     pub update_methods: std::collections::HashMap<String, InstallMethodForm>,
 
+    // Version extraction rules
+    pub version_parse: VersionParseForm,
+
     // Validation errors
     pub errors: Vec<String>,
     pub is_valid: bool,
 }
 
+/// 版本解析规则的表单，附带用于实时预览的示例输出
+#[derive(Debug, Clone, Default)]
+pub struct VersionParseForm {
+    pub stream: OutputStream,
+    pub line_prefix: String,
+    pub anchor: String,
+    pub json_pointer: String,
+    pub group: String,         // 第几个版本号，从 1 开始，留空为 1
+    pub sample_stdout: String, // 🧪 Test 的输出，也可以手动粘贴
+    pub sample_stderr: String,
+}
+
+impl VersionParseForm {
+    fn from_config(rules: &VersionParse) -> Self {
+        Self {
+            stream: rules.stream.unwrap_or_default(),
+            line_prefix: rules.line_prefix.clone().unwrap_or_default(),
+            anchor: rules.anchor.clone().unwrap_or_default(),
+            json_pointer: rules.json_pointer.clone().unwrap_or_default(),
+            group: rules.group.map(|g| g.to_string()).unwrap_or_default(),
+            ..Self::default()
+        }
+    }
+
+    /// 所有规则都留空时为 None，即按默认方式识别版本
+    fn to_config(&self) -> Option<VersionParse> {
+        let text = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+        let rules = VersionParse {
+            stream: Some(self.stream).filter(|s| *s != OutputStream::Stdout),
+            line_prefix: text(&self.line_prefix),
+            anchor: text(&self.anchor),
+            json_pointer: text(self.json_pointer.trim()),
+            group: self.group.trim().parse().ok(),
+        };
+        (rules != VersionParse::default()).then_some(rules)
+    }
+}
+
+/// Output of a command run with the editor's 🧪 Test button
+#[derive(Debug, Clone)]
+pub struct CommandTestOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct InstallMethodForm {
-    pub method: String,       // npm, brew, pip, script, etc.
-    pub command_args: String, // Space-separated command args
-    pub url: String,          // For script installs
-    pub package_name: String, // Package name for package managers
+    pub method: String,             // npm, brew, pip, script, etc.
+    pub command_args: String,       // Space-separated command args
+    pub url: String,                // For script installs
+    pub package_name: String,       // Package name for package managers
+    pub sha256: String,             // Expected SHA-256 of the file at url
+    pub interpreter: String,        // Program that runs a downloaded script
+    pub classic: bool,              // snap --classic confinement
+    pub uninstall_commands: String, // Steps separated by &&
+    pub version: String,            // Release installed by the binary method
+    pub bin: String,                // Executable path inside a binary archive
+    pub update_commands: String,    // Steps separated by &&
 }
 
 impl Default for ToolFormState {
@@ -163,7 +275,7 @@ impl Default for ToolFormState {
         let mut update_methods = std::collections::HashMap::new();
 
         // Initialize with empty forms for all platforms
-        for platform in ["windows", "macos", "linux"] {
+        for platform in FORM_PLATFORMS {
             version_check_methods.insert(platform.to_string(), "--version".to_string());
             update_check_methods.insert(platform.to_string(), String::new());
             install_methods.insert(platform.to_string(), InstallMethodForm::default());
@@ -176,12 +288,15 @@ impl Default for ToolFormState {
             name: String::new(),
             description: String::new(),
             website: String::new(),
+            github_repo: String::new(),
             command: String::new(),
+            depends_on: String::new(),
             version_check_methods,
             update_check_methods,
             install_methods,
             uninstall_methods,
             update_methods,
+            version_parse: VersionParseForm::default(),
             errors: Vec::new(),
             is_valid: false,
         }
@@ -195,6 +310,13 @@ impl Default for InstallMethodForm {
             command_args: String::new(),
             url: String::new(),
             package_name: String::new(),
+            sha256: String::new(),
+            interpreter: String::new(),
+            classic: false,
+            uninstall_commands: String::new(),
+            update_commands: String::new(),
+            version: String::new(),
+            bin: String::new(),
         }
     }
 }
@@ -210,8 +332,12 @@ impl Default for AppState {
             current_view: AppView::Main,
             status_progress: Vec::new(),
             install_progress: Vec::new(), // 新增此行
+            operation_output: HashMap::new(),
             is_refreshing: false,
 
+            history_entries: Vec::new(),
+            history_tool_filter: String::new(),
+
             // Tool editor state
             show_tool_editor: false,
             editing_tool_id: None,
@@ -220,6 +346,7 @@ impl Default for AppState {
             // Delete confirmation state
             show_delete_confirmation: false,
             tool_to_delete: None,
+            pending_plan: None,
 
             settings_theme: "dark".to_string(),
             settings_font_size: 14.0,
@@ -227,6 +354,7 @@ impl Default for AppState {
             settings_check_interval: 30,
             settings_show_notifications: true,
             settings_auto_refresh_on_startup: true,
+            settings_refresh_concurrency: 8,
             settings_debug_mode: false,
             settings_experimental_features: false,
             last_auto_check: None,
@@ -247,6 +375,12 @@ pub struct CLIvergeApp {
     progress_receiver: ProgressReceiver,
     install_sender: InstallSender,
     install_receiver: InstallReceiver,
+    output_sender: Option<tokio::sync::mpsc::UnboundedSender<OutputLine>>,
+    output_receiver: tokio::sync::mpsc::UnboundedReceiver<OutputLine>,
+    prerequisite_prompt: PrerequisitePromptSlot,
+    script_prompt: ScriptPromptSlot,
+    command_test: CommandTestSlot,
+    cancel_tokens: CancelTokens,
     ctx: Option<egui::Context>,
 }
 
@@ -276,10 +410,12 @@ impl CLIvergeApp {
         let config_manager = Arc::new(Mutex::new(config_manager));
         let cache_manager = Arc::new(Mutex::new(cache_manager));
         let tool_manager = ToolManager::new(Arc::clone(&config_manager));
+        Self::setup_elevation(&tool_manager, &config_dir);
 
         // Create progress channel
         let (progress_sender, progress_receiver) = tokio::sync::mpsc::unbounded_channel();
         let (install_sender, install_receiver) = tokio::sync::mpsc::unbounded_channel(); // 新增此行
+        let (output_sender, output_receiver) = tokio::sync::mpsc::unbounded_channel();
 
         let mut app = Self {
             config_manager: Arc::clone(&config_manager),
@@ -294,6 +430,12 @@ impl CLIvergeApp {
             progress_receiver: Arc::new(Mutex::new(Some(progress_receiver))),
             install_sender: Arc::new(Mutex::new(Some(install_sender))), // 新增此行
             install_receiver: Arc::new(Mutex::new(Some(install_receiver))), // 新增此行
+            output_sender: Some(output_sender),
+            output_receiver,
+            prerequisite_prompt: Arc::new(Mutex::new(None)),
+            script_prompt: Arc::new(Mutex::new(None)),
+            command_test: Arc::new(Mutex::new(None)),
+            cancel_tokens: HashMap::new(),
             ctx: None,
         };
 
@@ -325,11 +467,15 @@ impl CLIvergeApp {
                 check_interval_minutes: 30,
                 show_notifications: true,
                 auto_refresh_on_startup: true,
+                refresh_concurrency: 8,
             },
             paths: PathSettings {
                 tools_config_path: "tools.json".to_string(),
                 data_directory: "~/.cliverge".to_string(),
             },
+            timeouts: Default::default(),
+            registries: Default::default(),
+            preferred_methods: HashMap::new(),
         };
 
         let mut config_manager = ConfigManager::new_with_settings(app_settings);
@@ -342,6 +488,20 @@ impl CLIvergeApp {
         config_manager
     }
 
+    /// GUI 没有终端，sudo 通过本程序的密码对话框获取密码，无法写入辅助脚本时改用 pkexec
+    fn setup_elevation(tool_manager: &ToolManager, config_dir: &std::path::Path) {
+        #[cfg(unix)]
+        {
+            let helper = std::env::current_exe()
+                .and_then(|exe| cliverge_core::write_askpass_helper(config_dir, &exe))
+                .map_err(|e| tracing::warn!("Failed to set up askpass helper: {}", e))
+                .ok();
+            tool_manager.set_elevation(cliverge_core::Elevation::without_terminal(helper));
+        }
+        #[cfg(not(unix))]
+        let _ = (tool_manager, config_dir);
+    }
+
     // Helper method to get config directory - same as ConfigManager
     fn get_config_dir() -> std::path::PathBuf {
         dirs::home_dir()
@@ -381,6 +541,7 @@ impl CLIvergeApp {
             self.app_state.settings_show_notifications = settings.behavior.show_notifications;
             self.app_state.settings_auto_refresh_on_startup =
                 settings.behavior.auto_refresh_on_startup;
+            self.app_state.settings_refresh_concurrency = settings.behavior.refresh_concurrency;
         }
     }
 
@@ -395,6 +556,7 @@ impl CLIvergeApp {
             settings.behavior.show_notifications = self.app_state.settings_show_notifications;
             settings.behavior.auto_refresh_on_startup =
                 self.app_state.settings_auto_refresh_on_startup;
+            settings.behavior.refresh_concurrency = self.app_state.settings_refresh_concurrency;
 
             config.update_app_settings(settings);
 
@@ -439,6 +601,13 @@ impl CLIvergeApp {
         }
     }
 
+    /// 为新任务登记取消令牌，返回的 ToolManager 在令牌取消时结束正在运行的命令
+    fn cancellable_tool_manager(&mut self, key: CancelKey) -> ToolManager {
+        let token = CancellationToken::new();
+        self.cancel_tokens.insert(key, token.clone());
+        self.tool_manager.with_cancellation(token)
+    }
+
     pub fn start_background_status_checking(&mut self) {
         if self.ctx.is_none() {
             return; // No context yet, will be called later
@@ -450,7 +619,7 @@ impl CLIvergeApp {
             Vec::new()
         };
 
-        let tool_manager = self.tool_manager.clone();
+        let tool_manager = self.cancellable_tool_manager(CancelKey::Refresh);
         let tools_cache = Arc::clone(&self.tools_cache);
         let cache_manager = Arc::clone(&self.cache_manager);
         let runtime = Arc::clone(&self.runtime);
@@ -461,93 +630,81 @@ impl CLIvergeApp {
             None
         };
 
-        for tool_id in tools_to_check {
-            let tool_manager = tool_manager.clone();
-            let tools_cache = Arc::clone(&tools_cache);
-            let cache_manager = Arc::clone(&cache_manager);
-            let tool_id = tool_id.clone();
+        // 核心调度器限制并发，每完成一个工具就更新列表
+        let on_event = {
             let ctx = ctx.clone();
-            let sender = sender.clone();
+            move |event: RefreshEvent| {
+                let (status, message) = match event.stage {
+                    RefreshStage::Checking => {
+                        (ProgressStatus::InProgress, "Checking status...".to_string())
+                    }
+                    RefreshStage::NotOnPath => {
+                        (ProgressStatus::Completed, "Not found on PATH".to_string())
+                    }
+                    RefreshStage::Finished(status) => {
+                        if let Ok(mut tools) = tools_cache.lock() {
+                            if let Some(tool) = tools
+                                .iter_mut()
+                                .find(|tool| tool.config.id == event.tool_id)
+                            {
+                                tool.status = status;
+                            }
+                        }
+                        (
+                            ProgressStatus::Completed,
+                            "Status check completed".to_string(),
+                        )
+                    }
+                    RefreshStage::Failed(e) => {
+                        tracing::error!("Status check failed for {}: {}", event.tool_id, e);
+                        (ProgressStatus::Failed, format!("Failed: {e}"))
+                    }
+                };
 
-            let handle = runtime.spawn(async move {
-                // Send progress update - starting
                 if let Some(sender) = &sender {
                     let _ = sender.send(StatusCheckProgress {
-                        tool_id: tool_id.clone(),
-                        tool_name: tool_id.clone(), // We'll get the real name from cache
-                        status: ProgressStatus::InProgress,
-                        message: "Checking status...".to_string(),
+                        tool_id: event.tool_id.clone(),
+                        tool_name: event.tool_id,
+                        status,
+                        message: format!("{message} ({}/{})", event.completed, event.total),
                         timestamp: Instant::now(),
                     });
                 }
+                if let Some(context) = &ctx {
+                    context.request_repaint();
+                }
+            }
+        };
 
-                match tool_manager.check_tool_status(&tool_id).await {
-                    Ok(status) => {
-                        // Update cache and save it
-                        let cache_manager_clone = Arc::clone(&cache_manager);
-                        let status_clone = status.clone();
-                        let tool_id_for_cache = tool_id.clone();
-
-                        // Update cache in a separate thread to avoid Send issues
-                        std::thread::spawn(move || {
-                            // Create a new runtime for this thread
-                            let rt = tokio::runtime::Runtime::new().unwrap();
-                            rt.block_on(async {
-                                // This is safe because we're in a blocking context, not async
-                                if let Ok(mut cache) = cache_manager_clone.lock() {
-                                    cache.set_tool_status(&tool_id_for_cache, status_clone);
-                                    if let Err(e) = cache.save().await {
-                                        tracing::warn!("Failed to save cache: {}", e);
-                                    }
-                                }
-                            });
-                        });
+        let handle = runtime.spawn(async move {
+            let results = tool_manager
+                .refresh_statuses(&tools_to_check, on_event)
+                .await;
 
-                        // Update tools cache
-                        if let Ok(mut tools) = tools_cache.lock() {
-                            for tool in tools.iter_mut() {
-                                if tool.config.id == tool_id {
-                                    tool.status = status;
-                                    break;
-                                }
+            // Update cache in a separate thread to avoid Send issues
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                rt.block_on(async {
+                    if let Ok(mut cache) = cache_manager.lock() {
+                        for (tool_id, result) in results {
+                            if let Ok(status) = result {
+                                cache.set_tool_status(&tool_id, status);
                             }
                         }
-
-                        // Send completion progress
-                        if let Some(sender) = &sender {
-                            let _ = sender.send(StatusCheckProgress {
-                                tool_id: tool_id.clone(),
-                                tool_name: tool_id.clone(),
-                                status: ProgressStatus::Completed,
-                                message: "Status check completed".to_string(),
-                                timestamp: Instant::now(),
-                            });
-                        }
-
-                        if let Some(context) = &ctx {
-                            context.request_repaint();
-                        }
-                    }
-                    Err(e) => {
-                        // Send failure progress
-                        if let Some(sender) = &sender {
-                            let _ = sender.send(StatusCheckProgress {
-                                tool_id: tool_id.clone(),
-                                tool_name: tool_id.clone(),
-                                status: ProgressStatus::Failed,
-                                message: format!("Failed: {e}"),
-                                timestamp: Instant::now(),
-                            });
+                        if let Err(e) = cache.save().await {
+                            tracing::warn!("Failed to save cache: {}", e);
                         }
-
-                        tracing::error!("Status check failed for {}: {}", tool_id, e);
                     }
-                }
+                });
             });
 
-            if let Ok(mut tasks) = self.background_tasks.lock() {
-                tasks.push(handle);
+            if let Some(context) = &ctx {
+                context.request_repaint();
             }
+        });
+
+        if let Ok(mut tasks) = self.background_tasks.lock() {
+            tasks.push(handle);
         }
     }
 
@@ -586,9 +743,15 @@ impl CLIvergeApp {
         }
 
         // 新增：更新安装进度
+        let mut operation_finished = false;
         if let Ok(mut receiver_guard) = self.install_receiver.lock() {
             if let Some(receiver) = receiver_guard.as_mut() {
                 while let Ok(progress) = receiver.try_recv() {
+                    operation_finished |= matches!(
+                        progress.status,
+                        ProgressStatus::Completed | ProgressStatus::Failed
+                    );
+
                     // 更新工具名称从缓存
                     let tool_name = if let Ok(tools) = self.tools_cache.lock() {
                         tools
@@ -603,6 +766,24 @@ impl CLIvergeApp {
                     let mut updated_progress = progress;
                     updated_progress.tool_name = tool_name;
 
+                    // 新一轮操作开始时清空上次的输出
+                    let key = (updated_progress.tool_id.clone(), updated_progress.operation);
+                    let restarted = matches!(updated_progress.status, ProgressStatus::InProgress)
+                        && self
+                            .app_state
+                            .install_progress
+                            .iter()
+                            .find(|p| p.tool_id == key.0 && p.operation == key.1)
+                            .is_none_or(|p| {
+                                matches!(
+                                    p.status,
+                                    ProgressStatus::Completed | ProgressStatus::Failed
+                                )
+                            });
+                    if restarted {
+                        self.app_state.operation_output.remove(&key);
+                    }
+
                     // 更新或添加安装进度条目
                     if let Some(existing) = self.app_state.install_progress.iter_mut().find(|p| {
                         p.tool_id == updated_progress.tool_id
@@ -616,6 +797,29 @@ impl CLIvergeApp {
                 }
             }
         }
+
+        while let Ok(line) = self.output_receiver.try_recv() {
+            let operation = match line.operation {
+                JournalOperation::Install => InstallOperation::Install,
+                JournalOperation::Uninstall => InstallOperation::Uninstall,
+                JournalOperation::Update => InstallOperation::Update,
+                JournalOperation::Rollback => InstallOperation::Rollback,
+            };
+            let output = self
+                .app_state
+                .operation_output
+                .entry((line.tool_id, operation))
+                .or_default();
+            output.push(line.text);
+            if output.len() > OUTPUT_LINE_LIMIT {
+                output.drain(..output.len() - OUTPUT_LINE_LIMIT);
+            }
+        }
+
+        // 操作结束后刷新历史视图
+        if operation_finished && matches!(self.app_state.current_view, AppView::History) {
+            self.load_operation_history();
+        }
     }
 
     pub fn get_tool_help(&mut self, tool_id: String) {
@@ -858,6 +1062,8 @@ impl CLIvergeApp {
                     ui.colored_label(status_color, status_icon);
                     ui.label(format!("Status: {}", Self::get_status_text(&tool.status)));
                 });
+                self.render_install_method(ui, &tool.config.id);
+                self.render_binary_location(ui, &tool.config.id);
 
                 ui.separator();
 
@@ -886,6 +1092,100 @@ impl CLIvergeApp {
         });
     }
 
+    /// 显示当前平台使用的安装方式，有多个候选时允许手动指定
+    fn render_install_method(&mut self, ui: &mut egui::Ui, tool_id: &str) {
+        let Ok(selection) = self.tool_manager.method_selection(tool_id) else {
+            return;
+        };
+        let chosen = selection.method().method.clone();
+        let preferred = selection.reason == SelectionReason::Preferred;
+        let note = match selection.reason {
+            SelectionReason::Preferred => "chosen by you",
+            SelectionReason::Available => "first one installed",
+            SelectionReason::NoneAvailable => "none of the package managers found",
+        };
+
+        let mut picked: Option<Option<String>> = None;
+        ui.horizontal(|ui| {
+            ui.label("📦 Install method:");
+            if selection.candidates.len() > 1 {
+                egui::ComboBox::from_id_source(format!("{tool_id}_install_method"))
+                    .selected_text(&chosen)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(!preferred, "Automatic").clicked() {
+                            picked = Some(None);
+                        }
+                        for candidate in &selection.candidates {
+                            let selected = preferred && candidate.method == chosen;
+                            if ui.selectable_label(selected, &candidate.method).clicked() {
+                                picked = Some(Some(candidate.method.clone()));
+                            }
+                        }
+                    });
+            } else {
+                ui.monospace(&chosen);
+            }
+            ui.weak(format!("({note})"));
+        });
+
+        if let Some(method) = picked {
+            self.tool_manager
+                .set_preferred_method(tool_id, method.as_deref());
+            self.save_settings_from_state();
+        }
+    }
+
+    /// 显示 PATH 上实际运行的可执行文件，托管的安装被其他副本遮蔽时给出警告
+    fn render_binary_location(&self, ui: &mut egui::Ui, tool_id: &str) {
+        let Ok(location) = self.tool_manager.locate_tool(tool_id) else {
+            return;
+        };
+        let Some(active) = location.active() else {
+            return;
+        };
+
+        let describe = |copy: &CommandLocation| {
+            let mut text = copy.path.display().to_string();
+            if copy.real_path != copy.path {
+                text.push_str(&format!(" → {}", copy.real_path.display()));
+            }
+            match &copy.manager {
+                Some(manager) => format!("{text} ({manager})"),
+                None => format!("{text} (unknown origin)"),
+            }
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("📍 Runs from:");
+            ui.monospace(describe(active));
+        });
+
+        if let (Some(shadow), Some(managed)) = (location.shadowed_by(), location.managed()) {
+            ui.colored_label(
+                egui::Color32::from_rgb(200, 120, 0),
+                format!(
+                    "⚠ The {} install at {} is shadowed by {}, which comes earlier on PATH",
+                    location.method,
+                    managed.path.display(),
+                    shadow.path.display()
+                ),
+            );
+        }
+
+        if location.copies.len() > 1 {
+            egui::CollapsingHeader::new(format!(
+                "Other copies on PATH ({})",
+                location.copies.len() - 1
+            ))
+            .id_source(format!("{tool_id}_path_copies"))
+            .show(ui, |ui| {
+                for copy in &location.copies[1..] {
+                    ui.monospace(describe(copy));
+                }
+            });
+        }
+    }
+
     fn render_tool_actions(&mut self, ui: &mut egui::Ui, tool: &ToolInfo) {
         ui.horizontal(|ui| {
             match &tool.status {
@@ -895,22 +1195,24 @@ impl CLIvergeApp {
                 }
                 ToolStatus::NotInstalled => {
                     if ui.button("📥 Install").clicked() {
-                        self.install_tool(tool.config.id.clone());
-                        self.add_notification(
-                            "Installation started".to_string(),
-                            NotificationLevel::Info,
-                        );
+                        self.confirm_operation(&tool.config.id, JournalOperation::Install);
                     }
                 }
                 ToolStatus::Installed { version } => {
                     ui.label(format!("Version: {version}"));
 
                     if ui.button("🗑 Uninstall").clicked() {
-                        self.uninstall_tool(tool.config.id.clone());
-                        self.add_notification(
-                            "Uninstallation started".to_string(),
-                            NotificationLevel::Info,
-                        );
+                        self.confirm_operation(&tool.config.id, JournalOperation::Uninstall);
+                    }
+
+                    // 存在历史版本时提供回滚
+                    if let Some(previous) = self
+                        .tool_manager
+                        .previous_version(&tool.config.id, Some(version))
+                    {
+                        if ui.button(format!("⏪ Roll back to {previous}")).clicked() {
+                            self.confirm_operation(&tool.config.id, JournalOperation::Rollback);
+                        }
                     }
 
                     if ui.button("🔄 Check Updates").clicked() {
@@ -1100,6 +1402,18 @@ impl CLIvergeApp {
                 {
                     settings_changed = true;
                 }
+                ui.horizontal(|ui| {
+                    ui.label("Parallel status checks:");
+                    if ui
+                        .add(egui::Slider::new(
+                            &mut self.app_state.settings_refresh_concurrency,
+                            1..=32,
+                        ))
+                        .changed()
+                    {
+                        settings_changed = true;
+                    }
+                });
 
                 // Auto-save when settings change
                 if settings_changed {
@@ -1237,6 +1551,7 @@ impl CLIvergeApp {
                 self.app_state.settings_check_interval = 30;
                 self.app_state.settings_show_notifications = true;
                 self.app_state.settings_auto_refresh_on_startup = true;
+                self.app_state.settings_refresh_concurrency = 8;
                 self.app_state.settings_debug_mode = false;
                 self.app_state.settings_experimental_features = false;
                 self.add_notification(
@@ -1259,6 +1574,7 @@ impl CLIvergeApp {
                 if ui.button("🧹 Clear All").clicked() {
                     self.app_state.status_progress.clear();
                     self.app_state.install_progress.clear();
+                    self.app_state.operation_output.clear();
                 }
 
                 if ui.button("📋 Copy All").clicked() {
@@ -1303,7 +1619,9 @@ impl CLIvergeApp {
                 "[STATUS] {} {} - {}",
                 icon, progress.tool_name, progress.message
             );
-            combined_entries.push((progress.timestamp, entry));
+            let cancel =
+                matches!(progress.status, ProgressStatus::InProgress).then_some(CancelKey::Refresh);
+            combined_entries.push((progress.timestamp, entry, None, cancel));
         }
 
         // 添加安装进度条目
@@ -1319,6 +1637,7 @@ impl CLIvergeApp {
                 InstallOperation::Install => "INSTALL",
                 InstallOperation::Uninstall => "UNINSTALL",
                 InstallOperation::Update => "UPDATE",
+                InstallOperation::Rollback => "ROLLBACK",
             };
 
             let mut entry = format!(
@@ -1331,11 +1650,26 @@ impl CLIvergeApp {
                 entry.push_str(&format!("\n   Command: {command}"));
             }
 
-            combined_entries.push((progress.timestamp, entry));
+            // 进行中显示最新的几行输出，结束后可展开查看完整输出
+            let key = (progress.tool_id.clone(), progress.operation);
+            let in_progress = matches!(progress.status, ProgressStatus::InProgress);
+            let output_key = match self.app_state.operation_output.get(&key) {
+                Some(output) if in_progress => {
+                    for line in &output[output.len().saturating_sub(OUTPUT_TAIL_LINES)..] {
+                        entry.push_str(&format!("\n   │ {line}"));
+                    }
+                    None
+                }
+                Some(_) => Some(key.clone()),
+                None => None,
+            };
+            let cancel = in_progress.then_some(CancelKey::Operation(key));
+
+            combined_entries.push((progress.timestamp, entry, output_key, cancel));
         }
 
         // 按时间戳排序（最新的在最后）
-        combined_entries.sort_by_key(|&(timestamp, _)| timestamp);
+        combined_entries.sort_by_key(|(timestamp, _, _, _)| *timestamp);
 
         if combined_entries.is_empty() {
             ui.centered_and_justified(|ui| {
@@ -1348,7 +1682,7 @@ impl CLIvergeApp {
             .auto_shrink([false, false])
             .stick_to_bottom(true)
             .show(ui, |ui| {
-                for (timestamp, entry) in combined_entries {
+                for (timestamp, entry, output_key, cancel_key) in combined_entries {
                     // 格式化时间戳为事件发生的实际时间 (HH:MM:SS)
                     let time_str = {
                         use chrono::{DateTime, Local};
@@ -1402,12 +1736,116 @@ impl CLIvergeApp {
                                 .frame(false),
                         );
                     }
+
+                    // 进行中的操作可以取消，取消后等待任务自行报告失败
+                    let token = cancel_key.and_then(|key| self.cancel_tokens.get(&key));
+                    if let Some(token) = token {
+                        ui.horizontal(|ui| {
+                            ui.add_space(70.0);
+                            if token.is_cancelled() {
+                                ui.add_enabled(false, egui::Button::new("Cancelling..."));
+                            } else if ui
+                                .small_button("✖ Cancel")
+                                .on_hover_text("Stop this operation and the commands it started")
+                                .clicked()
+                            {
+                                token.cancel();
+                            }
+                        });
+                    }
+
+                    // 已结束操作的完整输出
+                    let output = output_key
+                        .as_ref()
+                        .and_then(|key| self.app_state.operation_output.get(key));
+                    if let (Some((tool_id, operation)), Some(output)) = (&output_key, output) {
+                        egui::CollapsingHeader::new(format!(
+                            "           Output ({} lines)",
+                            output.len()
+                        ))
+                        .id_source(format!("operation_output_{tool_id}_{operation:?}"))
+                        .show(ui, |ui| {
+                            let mut text = output.join("\n");
+                            ui.add(
+                                egui::TextEdit::multiline(&mut text)
+                                    .code_editor()
+                                    .desired_width(f32::INFINITY)
+                                    .interactive(true),
+                            );
+                        });
+                    }
                 }
             });
     }
 
+    /// 计算操作将要执行的命令，确认后再执行
+    fn confirm_operation(&mut self, tool_id: &str, operation: JournalOperation) {
+        match self.tool_manager.plan_operation(tool_id, operation) {
+            Ok(plan) => self.app_state.pending_plan = Some(plan),
+            Err(e) => self.add_notification(
+                format!("Cannot {} {tool_id}: {e}", operation.as_str()),
+                NotificationLevel::Error,
+            ),
+        }
+    }
+
+    /// 执行用户已确认的操作
+    fn run_confirmed_plan(&mut self, plan: &OperationPlan) {
+        let tool_id = plan.tool_id.clone();
+        match plan.operation {
+            JournalOperation::Install => {
+                self.install_tool(tool_id);
+                self.add_notification("Installation started".to_string(), NotificationLevel::Info);
+            }
+            JournalOperation::Uninstall => {
+                self.uninstall_tool(tool_id);
+                self.add_notification(
+                    "Uninstallation started".to_string(),
+                    NotificationLevel::Info,
+                );
+            }
+            JournalOperation::Rollback => {
+                self.rollback_tool(tool_id);
+                self.add_notification("Rollback started".to_string(), NotificationLevel::Info);
+            }
+            // 界面中还没有更新按钮
+            JournalOperation::Update => {}
+        }
+    }
+
+    /// 用于日志显示的命令：操作计划中首先尝试的那组命令
+    fn display_command(&self, tool_id: &str, operation: JournalOperation) -> Option<String> {
+        let plan = self.tool_manager.plan_operation(tool_id, operation).ok()?;
+        let steps: Vec<String> = plan
+            .alternatives
+            .first()?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        (!steps.is_empty()).then(|| steps.join(" && "))
+    }
+
     pub fn install_tool(&mut self, tool_id: String) {
-        let tool_manager = self.tool_manager.clone();
+        self.spawn_install(tool_id, InstallMode::Plain);
+    }
+
+    /// 先安装缺失的前置工具，再安装目标工具
+    pub fn install_tool_with_dependencies(&mut self, tool_id: String) {
+        self.spawn_install(tool_id, InstallMode::WithDependencies);
+    }
+
+    /// 运行用户已审阅的安装脚本
+    pub fn run_install_script(&mut self, script: InstallScript) {
+        self.spawn_install(script.tool_id.clone(), InstallMode::ReviewedScript(script));
+    }
+
+    fn spawn_install(&mut self, tool_id: String, mode: InstallMode) {
+        let tool_manager = self.cancellable_tool_manager(CancelKey::Operation((
+            tool_id.clone(),
+            InstallOperation::Install,
+        )));
+        let prerequisite_prompt = Arc::clone(&self.prerequisite_prompt);
+        let script_prompt = Arc::clone(&self.script_prompt);
         let tools_cache = Arc::clone(&self.tools_cache);
         let cache_manager = Arc::clone(&self.cache_manager);
         let runtime = Arc::clone(&self.runtime);
@@ -1419,35 +1857,7 @@ impl CLIvergeApp {
         };
 
         // 获取安装命令用于显示目的
-        let install_command = if let Ok(tools) = self.tools_cache.lock() {
-            if let Some(tool_info) = tools.iter().find(|t| t.config.id == tool_id) {
-                let platform = std::env::consts::OS;
-                tool_info
-                    .config
-                    .install
-                    .get(platform)
-                    .and_then(|install_method| install_method.command.as_ref())
-                    .map(|cmd| cmd.join(" "))
-                    .or_else(|| {
-                        // 回退：从方法和包名构造命令
-                        tool_info
-                            .config
-                            .install
-                            .get(platform)
-                            .map(|install_method| {
-                                format!(
-                                    "{} install -g {}",
-                                    install_method.method,
-                                    install_method.package_name.as_ref().unwrap_or(&tool_id)
-                                )
-                            })
-                    })
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let install_command = self.display_command(&tool_id, JournalOperation::Install);
 
         let handle = runtime.spawn(async move {
             // 发送进度更新 - 开始
@@ -1463,38 +1873,119 @@ impl CLIvergeApp {
                 });
             }
 
-            match tool_manager.install_tool(&tool_id).await {
-                Ok(_) => {
-                    // 安装后重新检查状态
-                    let new_status = tool_manager.check_tool_status(&tool_id).await;
+            let mut prerequisites = Vec::new();
+            let result = match mode {
+                InstallMode::ReviewedScript(script) => tool_manager.install_script(&script).await,
+                mode => {
+                    let ready = if matches!(mode, InstallMode::WithDependencies) {
+                        tool_manager
+                            .install_dependencies(&tool_id)
+                            .await
+                            .map(|installed| {
+                                prerequisites = installed.into_iter().map(|d| d.tool_id).collect();
+                            })
+                    } else {
+                        match tool_manager.missing_dependencies(&tool_id).await {
+                            Ok(missing) if !missing.is_empty() => {
+                                // 缺少前置工具，交给界面询问用户是否先安装
+                                if let Some(sender) = &sender {
+                                    let _ = sender.send(InstallProgress {
+                                        tool_id: tool_id.clone(),
+                                        tool_name: tool_id.clone(),
+                                        operation: InstallOperation::Install,
+                                        status: ProgressStatus::Pending,
+                                        message: "Waiting for prerequisites confirmation"
+                                            .to_string(),
+                                        command: install_command.clone(),
+                                        timestamp: Instant::now(),
+                                    });
+                                }
+                                if let Ok(mut prompt) = prerequisite_prompt.lock() {
+                                    *prompt = Some(PrerequisitePrompt {
+                                        tool_id: tool_id.clone(),
+                                        missing,
+                                    });
+                                }
+                                if let Some(context) = &ctx {
+                                    context.request_repaint();
+                                }
+                                return;
+                            }
+                            _ => Ok(()),
+                        }
+                    };
 
-                    // 更新工具缓存状态
-                    if let Ok(status) = &new_status {
-                        if let Ok(mut tools) = tools_cache.lock() {
-                            for tool in tools.iter_mut() {
-                                if tool.config.id == tool_id {
-                                    tool.status = status.clone();
-                                    break;
+                    match ready {
+                        Ok(()) if tool_manager.uses_install_script(&tool_id) => {
+                            // 先下载脚本，交给界面展示内容并等待用户确认
+                            match tool_manager.fetch_install_script(&tool_id).await {
+                                Ok(script) => {
+                                    if let Some(sender) = &sender {
+                                        let _ = sender.send(InstallProgress {
+                                            tool_id: tool_id.clone(),
+                                            tool_name: tool_id.clone(),
+                                            operation: InstallOperation::Install,
+                                            status: ProgressStatus::Pending,
+                                            message: format!(
+                                                "Waiting for review of {}",
+                                                script.url
+                                            ),
+                                            command: install_command.clone(),
+                                            timestamp: Instant::now(),
+                                        });
+                                    }
+                                    if let Ok(mut prompt) = script_prompt.lock() {
+                                        *prompt = Some(script);
+                                    }
+                                    if let Some(context) = &ctx {
+                                        context.request_repaint();
+                                    }
+                                    return;
                                 }
+                                Err(e) => Err(e),
                             }
                         }
+                        Ok(()) => tool_manager.install_tool(&tool_id).await,
+                        Err(e) => Err(e),
                     }
-
-                    // 使用我们已经检查的状态更新缓存
-                    if let Ok(status) = &new_status {
-                        let cache_manager_clone = Arc::clone(&cache_manager);
-                        let status_clone = status.clone();
-                        let tool_id_for_cache = tool_id.clone();
-
-                        std::thread::spawn(move || {
-                            let rt = tokio::runtime::Runtime::new().unwrap();
-                            rt.block_on(async {
-                                if let Ok(mut cache) = cache_manager_clone.lock() {
-                                    cache.set_tool_status(&tool_id_for_cache, status_clone);
-                                    let _ = cache.save().await;
+                }
+            }
+            .map(|()| prerequisites);
+
+            match result {
+                Ok(prerequisites) => {
+                    // 安装后重新检查状态（包括刚安装的前置工具）
+                    for id in prerequisites.iter().chain(std::iter::once(&tool_id)) {
+                        let new_status = tool_manager.check_tool_status(id).await;
+
+                        // 更新工具缓存状态
+                        if let Ok(status) = &new_status {
+                            if let Ok(mut tools) = tools_cache.lock() {
+                                for tool in tools.iter_mut() {
+                                    if &tool.config.id == id {
+                                        tool.status = status.clone();
+                                        break;
+                                    }
                                 }
+                            }
+                        }
+
+                        // 使用我们已经检查的状态更新缓存
+                        if let Ok(status) = &new_status {
+                            let cache_manager_clone = Arc::clone(&cache_manager);
+                            let status_clone = status.clone();
+                            let tool_id_for_cache = id.clone();
+
+                            std::thread::spawn(move || {
+                                let rt = tokio::runtime::Runtime::new().unwrap();
+                                rt.block_on(async {
+                                    if let Ok(mut cache) = cache_manager_clone.lock() {
+                                        cache.set_tool_status(&tool_id_for_cache, status_clone);
+                                        let _ = cache.save().await;
+                                    }
+                                });
                             });
-                        });
+                        }
                     }
 
                     // 发送完成进度
@@ -1539,7 +2030,10 @@ impl CLIvergeApp {
     }
 
     pub fn uninstall_tool(&mut self, tool_id: String) {
-        let tool_manager = self.tool_manager.clone();
+        let tool_manager = self.cancellable_tool_manager(CancelKey::Operation((
+            tool_id.clone(),
+            InstallOperation::Uninstall,
+        )));
         let tools_cache = Arc::clone(&self.tools_cache);
         let cache_manager = Arc::clone(&self.cache_manager);
         let runtime = Arc::clone(&self.runtime);
@@ -1551,35 +2045,7 @@ impl CLIvergeApp {
         };
 
         // 获取卸载命令用于显示目的
-        let uninstall_command = if let Ok(tools) = self.tools_cache.lock() {
-            if let Some(tool_info) = tools.iter().find(|t| t.config.id == tool_id) {
-                let platform = std::env::consts::OS;
-                if let Some(install_method) = tool_info.config.install.get(platform) {
-                    // 根据安装方法构造卸载命令
-                    match install_method.method.as_str() {
-                        "npm" => install_method
-                            .package_name
-                            .as_ref()
-                            .map(|pkg| format!("npm uninstall -g {pkg}")),
-                        "brew" => install_method
-                            .package_name
-                            .as_ref()
-                            .map(|pkg| format!("brew uninstall {pkg}")),
-                        "pip" => install_method
-                            .package_name
-                            .as_ref()
-                            .map(|pkg| format!("pip uninstall -y {pkg}")),
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let uninstall_command = self.display_command(&tool_id, JournalOperation::Uninstall);
 
         let handle = runtime.spawn(async move {
             // 发送进度更新 - 开始
@@ -1670,8 +2136,106 @@ impl CLIvergeApp {
         }
     }
 
+    pub fn rollback_tool(&mut self, tool_id: String) {
+        let tool_manager = self.cancellable_tool_manager(CancelKey::Operation((
+            tool_id.clone(),
+            InstallOperation::Rollback,
+        )));
+        let tools_cache = Arc::clone(&self.tools_cache);
+        let cache_manager = Arc::clone(&self.cache_manager);
+        let runtime = Arc::clone(&self.runtime);
+        let ctx = self.ctx.clone();
+        let sender = if let Ok(sender_guard) = self.install_sender.lock() {
+            sender_guard.clone()
+        } else {
+            None
+        };
+
+        let handle = runtime.spawn(async move {
+            // 发送进度更新 - 开始
+            if let Some(sender) = &sender {
+                let _ = sender.send(InstallProgress {
+                    tool_id: tool_id.clone(),
+                    tool_name: tool_id.clone(),
+                    operation: InstallOperation::Rollback,
+                    status: ProgressStatus::InProgress,
+                    message: "Starting rollback...".to_string(),
+                    command: None,
+                    timestamp: Instant::now(),
+                });
+            }
+
+            match tool_manager.rollback_tool(&tool_id).await {
+                Ok(version) => {
+                    // 回滚后重新检查状态
+                    let new_status = tool_manager.check_tool_status(&tool_id).await;
+
+                    if let Ok(status) = &new_status {
+                        if let Ok(mut tools) = tools_cache.lock() {
+                            for tool in tools.iter_mut() {
+                                if tool.config.id == tool_id {
+                                    tool.status = status.clone();
+                                    break;
+                                }
+                            }
+                        }
+
+                        let cache_manager_clone = Arc::clone(&cache_manager);
+                        let status_clone = status.clone();
+                        let tool_id_for_cache = tool_id.clone();
+
+                        std::thread::spawn(move || {
+                            let rt = tokio::runtime::Runtime::new().unwrap();
+                            rt.block_on(async {
+                                if let Ok(mut cache) = cache_manager_clone.lock() {
+                                    cache.set_tool_status(&tool_id_for_cache, status_clone);
+                                    let _ = cache.save().await;
+                                }
+                            });
+                        });
+                    }
+
+                    if let Some(sender) = &sender {
+                        let _ = sender.send(InstallProgress {
+                            tool_id: tool_id.clone(),
+                            tool_name: tool_id.clone(),
+                            operation: InstallOperation::Rollback,
+                            status: ProgressStatus::Completed,
+                            message: format!("Rolled back to {version}"),
+                            command: None,
+                            timestamp: Instant::now(),
+                        });
+                    }
+
+                    if let Some(context) = &ctx {
+                        context.request_repaint();
+                    }
+                }
+                Err(e) => {
+                    if let Some(sender) = &sender {
+                        let _ = sender.send(InstallProgress {
+                            tool_id: tool_id.clone(),
+                            tool_name: tool_id.clone(),
+                            operation: InstallOperation::Rollback,
+                            status: ProgressStatus::Failed,
+                            message: format!("Rollback failed: {e}"),
+                            command: None,
+                            timestamp: Instant::now(),
+                        });
+                    }
+
+                    tracing::error!("Rollback failed for {}: {}", tool_id, e);
+                }
+            }
+        });
+
+        if let Ok(mut tasks) = self.background_tasks.lock() {
+            tasks.push(handle);
+        }
+    }
+
     fn get_all_logs_as_text(&self) -> String {
-        let mut combined_entries = Vec::new();
+        let mut combined_entries: Vec<LogEntry> = Vec::new();
 
         // 添加状态检查进度条目
         for progress in &self.app_state.status_progress {
@@ -1686,7 +2250,7 @@ impl CLIvergeApp {
                 "[STATUS] {} {} - {}",
                 icon, progress.tool_name, progress.message
             );
-            combined_entries.push((progress.timestamp, entry));
+            combined_entries.push((progress.timestamp, entry, None, None));
         }
 
         // 添加安装进度条目
@@ -1702,6 +2266,7 @@ impl CLIvergeApp {
                 InstallOperation::Install => "INSTALL",
                 InstallOperation::Uninstall => "UNINSTALL",
                 InstallOperation::Update => "UPDATE",
+                InstallOperation::Rollback => "ROLLBACK",
             };
 
             let mut entry = format!(
@@ -1714,14 +2279,22 @@ impl CLIvergeApp {
                 entry.push_str(&format!("\n   Command: {command}"));
             }
 
-            combined_entries.push((progress.timestamp, entry));
+            // 附上完整的命令输出
+            let key = (progress.tool_id.clone(), progress.operation);
+            if let Some(output) = self.app_state.operation_output.get(&key) {
+                for line in output {
+                    entry.push_str(&format!("\n   │ {line}"));
+                }
+            }
+
+            combined_entries.push((progress.timestamp, entry, None, None));
         }
 
         // 按时间戳排序（最新的在最后）
-        combined_entries.sort_by_key(|&(timestamp, _)| timestamp);
+        combined_entries.sort_by_key(|(timestamp, _, _, _)| *timestamp);
 
         let mut result = String::new();
-        for (timestamp, entry) in combined_entries {
+        for (timestamp, entry, _, _) in combined_entries {
             // 格式化时间戳为事件发生的实际时间 (HH:MM:SS)
             let time_str = {
                 use chrono::{DateTime, Local};
@@ -1763,7 +2336,6 @@ impl CLIvergeApp {
                             "cargo (Rust)",
                         );
                         ui.selectable_value(&mut method.method, "go".to_string(), "go (Go)");
-                        ui.selectable_value(&mut method.method, "gem".to_string(), "gem (Ruby)");
 
                         ui.separator();
 
@@ -1783,11 +2355,6 @@ impl CLIvergeApp {
                             "scoop".to_string(),
                             "scoop (Scoop)",
                         );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "powershell".to_string(),
-                            "powershell (PowerShell)",
-                        );
 
                         ui.separator();
 
@@ -1797,11 +2364,6 @@ impl CLIvergeApp {
                             "brew".to_string(),
                             "brew (Homebrew)",
                         );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "port".to_string(),
-                            "port (MacPorts)",
-                        );
 
                         ui.separator();
 
@@ -1822,60 +2384,29 @@ impl CLIvergeApp {
                             "pacman".to_string(),
                             "pacman (Arch)",
                         );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "zypper".to_string(),
-                            "zypper (openSUSE)",
-                        );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "emerge".to_string(),
-                            "emerge (Gentoo)",
-                        );
-                        ui.selectable_value(&mut method.method, "apk".to_string(), "apk (Alpine)");
                         ui.selectable_value(
                             &mut method.method,
                             "snap".to_string(),
                             "snap (Snapcraft)",
                         );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "flatpak".to_string(),
-                            "flatpak (Flatpak)",
-                        );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "appimage".to_string(),
-                            "appimage (AppImage)",
-                        );
 
                         ui.separator();
 
                         // 通用方法
-                        ui.selectable_value(
-                            &mut method.method,
-                            "curl".to_string(),
-                            "curl (Download)",
-                        );
-                        ui.selectable_value(
-                            &mut method.method,
-                            "wget".to_string(),
-                            "wget (Download)",
-                        );
                         ui.selectable_value(
                             &mut method.method,
                             "script".to_string(),
-                            "script (Custom Script)",
+                            "script (Install Script)",
                         );
                         ui.selectable_value(
                             &mut method.method,
                             "binary".to_string(),
-                            "binary (Direct Binary)",
+                            "binary (Release Archive)",
                         );
                         ui.selectable_value(
                             &mut method.method,
-                            "manual".to_string(),
-                            "manual (Manual Install)",
+                            "custom".to_string(),
+                            "custom (Custom Commands)",
                         );
                     });
                 ui.end_row();
@@ -1885,13 +2416,176 @@ impl CLIvergeApp {
                 ui.end_row();
 
                 ui.label("Command Args:");
-                ui.text_edit_singleline(&mut method.command_args);
+                ui.add(
+                    egui::TextEdit::singleline(&mut method.command_args)
+                        .hint_text("steps separated by &&"),
+                );
+                ui.end_row();
+
+                ui.label("Uninstall Commands:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut method.uninstall_commands)
+                        .hint_text("optional, steps separated by &&"),
+                );
+                ui.end_row();
+
+                ui.label("Update Commands:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut method.update_commands)
+                        .hint_text("optional, steps separated by &&"),
+                );
                 ui.end_row();
 
-                if method.method == "script" || method.method == "curl" || method.method == "wget" {
+                if method.method == "script" || method.method == "binary" {
                     ui.label("URL:");
-                    ui.text_edit_singleline(&mut method.url);
+                    let hint = if method.method == "binary" {
+                        "{version}, {os} and {arch} are filled in"
+                    } else {
+                        ""
+                    };
+                    ui.add(egui::TextEdit::singleline(&mut method.url).hint_text(hint));
+                    ui.end_row();
+
+                    ui.label("SHA-256:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut method.sha256)
+                            .hint_text("optional, verified before running"),
+                    );
+                    ui.end_row();
+                }
+
+                if method.method == "script" {
+                    ui.label("Interpreter:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut method.interpreter)
+                            .hint_text("optional, defaults to sh"),
+                    );
+                    ui.end_row();
+                }
+
+                if method.method == "binary" {
+                    ui.label("Version:");
+                    ui.text_edit_singleline(&mut method.version);
                     ui.end_row();
+
+                    ui.label("Executable:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut method.bin)
+                            .hint_text("optional, path inside the archive"),
+                    );
+                    ui.end_row();
+                }
+
+                if method.method == "snap" {
+                    ui.label("Confinement:");
+                    ui.checkbox(&mut method.classic, "Classic (--classic)");
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn load_operation_history(&mut self) {
+        let query = JournalQuery {
+            limit: Some(HISTORY_VIEW_LIMIT),
+            ..Default::default()
+        };
+        match self.tool_manager.operation_history(&query) {
+            Ok(entries) => self.app_state.history_entries = entries,
+            Err(e) => {
+                self.add_notification(
+                    format!("Failed to load operation history: {e}"),
+                    NotificationLevel::Error,
+                );
+            }
+        }
+    }
+
+    fn render_history(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("📜 Operation History");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("⬅ Back").clicked() {
+                    self.app_state.current_view = AppView::Main;
+                }
+                if ui.button("🔄 Reload").clicked() {
+                    self.load_operation_history();
+                }
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Tool:");
+            ui.text_edit_singleline(&mut self.app_state.history_tool_filter);
+        });
+        ui.separator();
+
+        let filter = self.app_state.history_tool_filter.trim().to_lowercase();
+        let entries: Vec<&JournalEntry> = self
+            .app_state
+            .history_entries
+            .iter()
+            .filter(|entry| filter.is_empty() || entry.tool_id.to_lowercase().contains(&filter))
+            .collect();
+
+        if entries.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("No recorded operations. Install, update, uninstall or roll back a tool to see it here.");
+            });
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (index, entry) in entries.into_iter().enumerate() {
+                    let time_str = entry
+                        .timestamp
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string();
+                    let icon = if entry.success { "✅" } else { "❌" };
+                    let versions = format!(
+                        "{} → {}",
+                        entry.version_before.as_deref().unwrap_or("-"),
+                        entry.version_after.as_deref().unwrap_or("-")
+                    );
+                    let user = entry.user.as_deref().unwrap_or("unknown");
+
+                    let header = format!(
+                        "[{time_str}] {icon} {} {}  {versions}  by {user}",
+                        entry.operation.as_str().to_uppercase(),
+                        entry.tool_id
+                    );
+
+                    egui::CollapsingHeader::new(header)
+                        .id_source(("history_entry", index))
+                        .show(ui, |ui| {
+                            ui.label(format!("Command: {}", entry.command_line()));
+                            ui.label(format!(
+                                "Exit code: {}",
+                                entry
+                                    .exit_code
+                                    .map(|code| code.to_string())
+                                    .unwrap_or_else(|| "-".to_string())
+                            ));
+                            ui.label(format!(
+                                "Duration: {:.1}s",
+                                entry.duration_ms as f64 / 1000.0
+                            ));
+
+                            if let Some(error) = &entry.error {
+                                ui.colored_label(egui::Color32::RED, format!("Error: {error}"));
+                            }
+                            if !entry.stdout.is_empty() {
+                                ui.label("stdout:");
+                                ui.code(&entry.stdout);
+                            }
+                            if !entry.stderr.is_empty() {
+                                ui.label("stderr:");
+                                ui.code(&entry.stderr);
+                            }
+                        });
                 }
             });
     }
@@ -2222,7 +2916,15 @@ impl CLIvergeApp {
         self.app_state.tool_form_state.name = tool_config.name.clone();
         self.app_state.tool_form_state.description = tool_config.description.clone();
         self.app_state.tool_form_state.website = tool_config.website.clone();
+        self.app_state.tool_form_state.github_repo =
+            tool_config.github_repo.clone().unwrap_or_default();
         self.app_state.tool_form_state.command = tool_config.command.clone();
+        self.app_state.tool_form_state.depends_on = tool_config
+            .depends_on
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
 
         // Convert version check args from HashMap<String, Vec<String>> to per-platform strings
         for (platform, args) in &tool_config.version_check {
@@ -2251,7 +2953,10 @@ impl CLIvergeApp {
         }
 
         // Populate install methods
-        for (platform, install_method) in &tool_config.install {
+        for (platform, candidates) in &tool_config.install {
+            let Some(install_method) = candidates.primary() else {
+                continue;
+            };
             if let Some(form_method) = self
                 .app_state
                 .tool_form_state
@@ -2259,16 +2964,40 @@ impl CLIvergeApp {
                 .get_mut(platform)
             {
                 form_method.method = install_method.method.clone();
-                form_method.command_args = install_method
-                    .command
+                form_method.command_args = match &install_method.install_command {
+                    Some(steps) => steps.to_string(),
+                    None => install_method
+                        .command
+                        .as_ref()
+                        .map(|args| args.join(" "))
+                        .unwrap_or_default(),
+                };
+                form_method.uninstall_commands = install_method
+                    .uninstall_command
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                form_method.update_commands = install_method
+                    .update_command
                     .as_ref()
-                    .map(|args| args.join(" "))
+                    .map(ToString::to_string)
                     .unwrap_or_default();
                 form_method.url = install_method.url.clone().unwrap_or_default();
                 form_method.package_name = install_method.package_name.clone().unwrap_or_default();
+                form_method.sha256 = install_method.sha256.clone().unwrap_or_default();
+                form_method.interpreter = install_method.interpreter.clone().unwrap_or_default();
+                form_method.classic = install_method.classic;
+                form_method.version = install_method.version.clone().unwrap_or_default();
+                form_method.bin = install_method.bin.clone().unwrap_or_default();
             }
         }
 
+        self.app_state.tool_form_state.version_parse = tool_config
+            .version_parse
+            .as_ref()
+            .map(VersionParseForm::from_config)
+            .unwrap_or_default();
+
         // Clear errors and validate
         self.app_state.tool_form_state.errors.clear();
         self.validate_tool_form();
@@ -2299,6 +3028,46 @@ impl CLIvergeApp {
                 .push("Website must be a valid HTTP/HTTPS URL".to_string());
         }
 
+        let github_repo = form.github_repo.trim();
+        if !github_repo.is_empty()
+            && !matches!(github_repo.split_once('/'), Some((owner, name))
+                if !owner.is_empty() && !name.is_empty() && !name.contains('/'))
+        {
+            form.errors
+                .push("GitHub repository must look like owner/name".to_string());
+        }
+
+        match ToolDependency::parse_list(&form.depends_on) {
+            Ok(dependencies) => {
+                // 检查依赖是否存在以及是否形成循环
+                if let Ok(config_manager) = self.config_manager.lock() {
+                    let mut tools = config_manager.get_tools_config().tools.clone();
+                    let tool_id = self
+                        .app_state
+                        .editing_tool_id
+                        .clone()
+                        .unwrap_or_else(|| form.id.clone());
+                    match tools.iter_mut().find(|t| t.id == tool_id) {
+                        Some(tool) => {
+                            tool.depends_on = dependencies;
+                            if let Err(e) = cliverge_core::validate_dependencies(&tools) {
+                                form.errors.push(e.to_string());
+                            }
+                        }
+                        None => {
+                            for dependency in &dependencies {
+                                if !tools.iter().any(|t| t.id == dependency.id) {
+                                    form.errors
+                                        .push(format!("Unknown dependency '{}'", dependency.id));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => form.errors.push(e.to_string()),
+        }
+
         // Validate version check methods for all platforms
         let mut has_version_check = false;
         for args in form.version_check_methods.values() {
@@ -2312,6 +3081,14 @@ impl CLIvergeApp {
                 .push("At least one platform must have version check arguments".to_string());
         }
 
+        let group = form.version_parse.group.trim();
+        if !group.is_empty() && group.parse::<usize>().is_err() {
+            form.errors
+                .push("Version number to take must be a whole number".to_string());
+        } else if let Some(Err(e)) = form.version_parse.to_config().map(|rules| rules.validate()) {
+            form.errors.push(format!("Version extraction: {e}"));
+        }
+
         // Check for duplicate names (only for new tools)
         if self.app_state.editing_tool_id.is_none() {
             if let Ok(tools) = self.tools_cache.lock() {
@@ -2338,9 +3115,115 @@ impl CLIvergeApp {
                 .push("At least one platform install method must be configured".to_string());
         }
 
+        for (platform, method) in &form.install_methods {
+            if !self.tool_manager.supports_method(&method.method) {
+                form.errors.push(format!(
+                    "Install method '{}' for {platform} is not supported",
+                    method.method
+                ));
+            }
+            if method.method == "binary"
+                && (method.url.trim().is_empty() || method.version.trim().is_empty())
+            {
+                form.errors.push(format!(
+                    "Binary method for {platform} requires a URL and a version"
+                ));
+            }
+            if method.method == "custom" && method.command_args.trim().is_empty() {
+                form.errors.push(format!(
+                    "Custom method for {platform} requires command args"
+                ));
+            }
+            let sha256 = method.sha256.trim();
+            if !sha256.is_empty()
+                && (sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()))
+            {
+                form.errors.push(format!(
+                    "SHA-256 for {platform} must be 64 hexadecimal characters"
+                ));
+            }
+        }
+
         form.is_valid = form.errors.is_empty();
     }
 
+    /// 版本解析规则，以及按当前规则从示例输出中解析出的版本
+    fn render_version_parse_form(ui: &mut egui::Ui, form: &mut VersionParseForm) {
+        ui.group(|ui| {
+            ui.label("🔎 Version extraction (optional)");
+            ui.small(
+                "For tools that print other versions too. Leave everything empty to pick the \
+                 first version number on stdout.",
+            );
+
+            egui::Grid::new("version_parse_grid")
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Read from:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut form.stream, OutputStream::Stdout, "stdout");
+                        ui.radio_value(&mut form.stream, OutputStream::Stderr, "stderr");
+                    });
+                    ui.end_row();
+
+                    ui.label("Line prefix:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.line_prefix)
+                            .hint_text("only lines starting with this"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Anchor:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.anchor)
+                            .hint_text("only text after this token"),
+                    );
+                    ui.end_row();
+
+                    ui.label("JSON pointer:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.json_pointer)
+                            .hint_text("/version for --version --json output"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Version number:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.group)
+                            .hint_text("1 = first one found"),
+                    );
+                    ui.end_row();
+                });
+
+            ui.label("Sample output (filled by 🧪 Test, or paste it here):");
+            let sample = match form.stream {
+                OutputStream::Stdout => &mut form.sample_stdout,
+                OutputStream::Stderr => &mut form.sample_stderr,
+            };
+            ui.add(
+                egui::TextEdit::multiline(sample)
+                    .code_editor()
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+
+            let version = VersionChecker::extract_version(
+                form.to_config().as_ref(),
+                &form.sample_stdout,
+                &form.sample_stderr,
+            );
+            ui.horizontal(|ui| {
+                ui.label("Parsed version:");
+                if version == "unknown" {
+                    ui.colored_label(egui::Color32::from_rgb(200, 120, 0), "no version found");
+                } else {
+                    ui.monospace(version);
+                }
+            });
+        });
+    }
+
     /// Test a command to see if it's available
     fn test_tool_command(&mut self, command: &str, args: &str) {
         let command = command.trim();
@@ -2357,28 +3240,33 @@ impl CLIvergeApp {
         let command = command.to_string();
         let args: Vec<String> = args.into_iter().map(|s| s.to_string()).collect();
         let runtime = Arc::clone(&self.runtime);
+        let command_test = Arc::clone(&self.command_test);
+        let ctx = self.ctx.clone();
 
-        // Test the command in the background
+        // Test the command in the background; the editor shows the output as the version
+        // extraction sample
         runtime.spawn(async move {
-            match tokio::process::Command::new(&command)
+            let result = match tokio::process::Command::new(&command)
                 .args(&args)
                 .output()
                 .await
             {
-                Ok(output) => {
-                    let success = output.status.success();
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-
-                    // Note: We can't easily update the UI from here due to borrowing rules
-                    // In a real implementation, we'd use channels or other async communication
-                    println!(
-                        "Command test result: success={success}, stdout={stdout}, stderr={stderr}"
-                    );
-                }
-                Err(e) => {
-                    println!("Command test failed: {e}");
-                }
+                Ok(output) => CommandTestOutput {
+                    success: output.status.success(),
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                },
+                Err(e) => CommandTestOutput {
+                    success: false,
+                    stdout: String::new(),
+                    stderr: format!("Failed to run {command}: {e}"),
+                },
+            };
+            if let Ok(mut slot) = command_test.lock() {
+                *slot = Some(result);
+            }
+            if let Some(context) = &ctx {
+                context.request_repaint();
             }
         });
 
@@ -2479,12 +3367,23 @@ impl CLIvergeApp {
 
         let form = &self.app_state.tool_form_state;
 
+        // 表单只编辑每个平台的首选安装方式，保留 tools.json 中其余的候选方式
+        let existing_tool = self.app_state.editing_tool_id.as_ref().and_then(|id| {
+            let config = self.config_manager.lock().ok()?;
+            config.get_tool_config(id).cloned()
+        });
+        let existing_install: PlatformCandidates = existing_tool
+            .as_ref()
+            .map(|tool| tool.install.clone())
+            .unwrap_or_default();
+
         // Create tool config from form data
-        let tool_config = cliverge_core::ToolConfig {
+        let mut tool_config = cliverge_core::ToolConfig {
             id: form.id.clone(),
             name: form.name.clone(),
             description: form.description.clone(),
             website: form.website.clone(),
+            github_repo: Some(form.github_repo.trim().to_string()).filter(|repo| !repo.is_empty()),
             command: form.command.clone(),
             version_check: form
                 .version_check_methods
@@ -2534,9 +3433,11 @@ impl CLIvergeApp {
                         return None;
                     }
 
+                    // 含 && 的命令参数按多步命令保存
+                    let multi_step = method.command_args.contains("&&");
                     let install_method = cliverge_core::InstallMethod {
                         method: method.method.clone(),
-                        command: if method.command_args.trim().is_empty() {
+                        command: if method.command_args.trim().is_empty() || multi_step {
                             None
                         } else {
                             Some(
@@ -2557,16 +3458,61 @@ impl CLIvergeApp {
                         } else {
                             Some(method.package_name.clone())
                         },
+                        sha256: if method.sha256.trim().is_empty() {
+                            None
+                        } else {
+                            Some(method.sha256.trim().to_string())
+                        },
+                        interpreter: Some(method.interpreter.trim().to_string())
+                            .filter(|i| !i.is_empty() && method.method == "script"),
+                        classic: method.method == "snap" && method.classic,
+                        version: Some(method.version.trim().to_string())
+                            .filter(|v| !v.is_empty() && method.method == "binary"),
+                        bin: Some(method.bin.trim().to_string())
+                            .filter(|b| !b.is_empty() && method.method == "binary"),
+                        install_command: CommandSequence::parse(&method.command_args)
+                            .filter(|_| multi_step),
+                        uninstall_command: CommandSequence::parse(&method.uninstall_commands),
+                        update_command: CommandSequence::parse(&method.update_commands),
                     };
 
-                    Some((platform.clone(), install_method))
+                    let mut methods = vec![install_method];
+                    if let Some(existing) = existing_install.get(platform) {
+                        methods.extend(existing.methods().iter().skip(1).cloned());
+                    }
+                    Some((platform.clone(), MethodCandidates(methods)))
                 })
                 .collect(),
             uninstall: None,     // TODO: Add uninstall configuration in form
             update: None,        // TODO: Add update configuration in form
             config_schema: None, // Not editable in form for now
+            depends_on: ToolDependency::parse_list(&form.depends_on).unwrap_or_default(),
+            version_parse: form.version_parse.to_config(),
         };
 
+        // 表单只有 windows、macos 和 linux，保留按发行版或架构区分的配置
+        if let Some(existing) = existing_tool {
+            let extra = |key: &String| !FORM_PLATFORMS.contains(&key.as_str());
+            for (key, args) in existing.version_check.into_iter().filter(|(k, _)| extra(k)) {
+                tool_config.version_check.entry(key).or_insert(args);
+            }
+            for (key, args) in existing
+                .update_check
+                .into_iter()
+                .flatten()
+                .filter(|(k, _)| extra(k))
+            {
+                tool_config
+                    .update_check
+                    .get_or_insert_with(HashMap::new)
+                    .entry(key)
+                    .or_insert(args);
+            }
+            for (key, methods) in existing.install.into_iter().filter(|(k, _)| extra(k)) {
+                tool_config.install.entry(key).or_insert(methods);
+            }
+        }
+
         // Save configuration
         match self.app_state.editing_tool_id {
             Some(ref existing_id) => {
@@ -2666,6 +3612,26 @@ impl CLIvergeApp {
 
     /// Render the tool editor form UI
     fn render_tool_editor(&mut self, ui: &mut egui::Ui) {
+        let test_output = self
+            .command_test
+            .lock()
+            .ok()
+            .and_then(|mut slot| slot.take());
+        if let Some(output) = test_output {
+            let (message, level) = if output.success {
+                ("Command ran successfully", NotificationLevel::Success)
+            } else {
+                (
+                    "Command failed, see the sample output",
+                    NotificationLevel::Warning,
+                )
+            };
+            self.add_notification(message.to_string(), level);
+            let form = &mut self.app_state.tool_form_state.version_parse;
+            form.sample_stdout = output.stdout;
+            form.sample_stderr = output.stderr;
+        }
+
         // Validate form on each render to ensure errors are up-to-date
         self.validate_tool_form();
 
@@ -2739,6 +3705,24 @@ impl CLIvergeApp {
                         ui.label("Website *:");
                         ui.text_edit_singleline(&mut self.app_state.tool_form_state.website);
                         ui.end_row();
+
+                        ui.label("GitHub repo:");
+                        ui.add(
+                            egui::TextEdit::singleline(
+                                &mut self.app_state.tool_form_state.github_repo,
+                            )
+                            .hint_text("cli/cli"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Depends on:");
+                        ui.add(
+                            egui::TextEdit::singleline(
+                                &mut self.app_state.tool_form_state.depends_on,
+                            )
+                            .hint_text("node>=20, git"),
+                        );
+                        ui.end_row();
                     });
             });
 
@@ -2749,7 +3733,7 @@ impl CLIvergeApp {
                 ui.label("Configure version and update check commands for different platforms:");
                 ui.add_space(5.0);
 
-                for platform in ["windows", "macos", "linux"] {
+                for platform in FORM_PLATFORMS {
                     ui.group(|ui| {
                         ui.horizontal(|ui| {
                             let platform_icon = match platform {
@@ -2807,6 +3791,12 @@ impl CLIvergeApp {
                     ui.add_space(8.0);
                 }
                 ui.small("Tip: Use space-separated arguments like '--version' or 'update --check'");
+
+                ui.add_space(8.0);
+                Self::render_version_parse_form(
+                    ui,
+                    &mut self.app_state.tool_form_state.version_parse,
+                );
             });
 
             ui.add_space(10.0);
@@ -2814,9 +3804,13 @@ impl CLIvergeApp {
             // Installation Methods Section
             ui.collapsing("⚙ Installation Methods", |ui| {
                 ui.label("Configure how this tool can be installed on different platforms:");
+                ui.small(
+                    "Only the preferred method is edited here; further methods listed in \
+                     tools.json are kept.",
+                );
                 ui.add_space(5.0);
 
-                for platform in ["windows", "macos", "linux"] {
+                for platform in FORM_PLATFORMS {
                     ui.group(|ui| {
                         ui.horizontal(|ui| {
                             let platform_icon = match platform {
@@ -2852,7 +3846,7 @@ impl CLIvergeApp {
                 ui.label("Configure how this tool can be uninstalled on different platforms:");
                 ui.add_space(5.0);
 
-                for platform in ["windows", "macos", "linux"] {
+                for platform in FORM_PLATFORMS {
                     ui.group(|ui| {
                         ui.horizontal(|ui| {
                             let platform_icon = match platform {
@@ -2888,7 +3882,7 @@ impl CLIvergeApp {
                 ui.label("Configure how this tool can be updated on different platforms:");
                 ui.add_space(5.0);
 
-                for platform in ["windows", "macos", "linux"] {
+                for platform in FORM_PLATFORMS {
                     ui.group(|ui| {
                         ui.horizontal(|ui| {
                             let platform_icon = match platform {
@@ -2991,6 +3985,16 @@ impl eframe::App for CLIvergeApp {
         // Store context for background tasks
         if self.ctx.is_none() {
             self.ctx = Some(ctx.clone());
+
+            // 命令输出逐行转发到操作日志
+            if let Some(sender) = self.output_sender.take() {
+                let context = ctx.clone();
+                self.tool_manager.set_output_sink(move |line| {
+                    if sender.send(line).is_ok() {
+                        context.request_repaint();
+                    }
+                });
+            }
             if self.app_state.settings_auto_refresh_on_startup {
                 self.start_background_status_checking();
             }
@@ -3044,6 +4048,11 @@ impl eframe::App for CLIvergeApp {
                     self.app_state.current_view = AppView::Settings;
                 }
 
+                if ui.button("📜 History").clicked() {
+                    self.load_operation_history();
+                    self.app_state.current_view = AppView::History;
+                }
+
                 if ui.button("ℹ About").clicked() {
                     self.app_state.current_view = AppView::About;
                 }
@@ -3147,6 +4156,211 @@ impl eframe::App for CLIvergeApp {
             }
         }
 
+        // 前置工具确认对话框
+        let pending_prompt = self
+            .prerequisite_prompt
+            .lock()
+            .ok()
+            .and_then(|prompt| prompt.clone());
+        if let Some(prompt) = pending_prompt {
+            let mut window_open = true;
+            let mut confirmed = false;
+            let mut cancelled = false;
+            egui::Window::new("📦 Prerequisites Required")
+                .open(&mut window_open)
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} needs the following tools installed first:",
+                        prompt.tool_id
+                    ));
+                    ui.add_space(5.0);
+                    for dependency in &prompt.missing {
+                        ui.label(format!("• {dependency}"));
+                    }
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("📥 Install All").clicked() {
+                            confirmed = true;
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+
+            if confirmed {
+                self.install_tool_with_dependencies(prompt.tool_id.clone());
+            } else if cancelled || !window_open {
+                // 将等待中的日志条目标记为已取消
+                if let Some(progress) = self.app_state.install_progress.iter_mut().find(|p| {
+                    p.tool_id == prompt.tool_id && matches!(p.operation, InstallOperation::Install)
+                }) {
+                    progress.status = ProgressStatus::Failed;
+                    progress.message = "Installation cancelled: prerequisites missing".to_string();
+                    progress.timestamp = Instant::now();
+                }
+            }
+
+            if confirmed || cancelled || !window_open {
+                if let Ok(mut slot) = self.prerequisite_prompt.lock() {
+                    *slot = None;
+                }
+            }
+        }
+
+        // 操作命令确认对话框
+        if let Some(plan) = self.app_state.pending_plan.clone() {
+            let mut window_open = true;
+            let mut confirmed = false;
+            let mut cancelled = false;
+            egui::Window::new(format!("📋 Confirm {}", plan.operation.as_str()))
+                .open(&mut window_open)
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "The following will run to {} {}:",
+                        plan.operation.as_str(),
+                        plan.tool_id
+                    ));
+                    ui.add_space(5.0);
+                    for (index, steps) in plan.alternatives.iter().enumerate() {
+                        if index > 0 {
+                            ui.weak("if that fails:");
+                        }
+                        if steps.is_empty() {
+                            ui.weak("(nothing to run)");
+                        }
+                        for step in steps {
+                            ui.horizontal(|ui| {
+                                if matches!(
+                                    step,
+                                    PlanStep::Run {
+                                        privileged: true,
+                                        ..
+                                    }
+                                ) {
+                                    ui.label("🔒")
+                                        .on_hover_text("Asks for an administrator password");
+                                } else {
+                                    ui.label("•");
+                                }
+                                ui.monospace(step.to_string());
+                            });
+                        }
+                    }
+                    if plan.is_privileged() {
+                        ui.add_space(5.0);
+                        ui.colored_label(
+                            egui::Color32::from_rgb(200, 120, 0),
+                            "🔒 Commands marked with a lock ask for an administrator password",
+                        );
+                    }
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("▶ Run").clicked() {
+                            confirmed = true;
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+
+            if confirmed || cancelled || !window_open {
+                self.app_state.pending_plan = None;
+            }
+            if confirmed {
+                self.run_confirmed_plan(&plan);
+            }
+        }
+
+        // 安装脚本审阅对话框
+        let pending_script = self
+            .script_prompt
+            .lock()
+            .ok()
+            .and_then(|prompt| prompt.clone());
+        if let Some(script) = pending_script {
+            let mut window_open = true;
+            let mut confirmed = false;
+            let mut cancelled = false;
+            egui::Window::new("📜 Review Install Script")
+                .open(&mut window_open)
+                .resizable(true)
+                .collapsible(false)
+                .default_size([640.0, 480.0])
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} is installed by running this script:",
+                        script.tool_id
+                    ));
+                    ui.monospace(&script.url);
+                    ui.horizontal(|ui| {
+                        ui.label("SHA-256:");
+                        ui.monospace(&script.sha256);
+                    });
+                    if script.verified {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(0, 150, 0),
+                            "✅ Matches the checksum declared in the catalog",
+                        );
+                    } else {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(200, 120, 0),
+                            "⚠ No checksum declared in the catalog; review the script carefully",
+                        );
+                    }
+                    ui.add_space(5.0);
+
+                    egui::ScrollArea::vertical()
+                        .max_height(320.0)
+                        .show(ui, |ui| {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut script.contents.as_str())
+                                    .code_editor()
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("▶ Run Script").clicked() {
+                            confirmed = true;
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+
+            if confirmed || cancelled || !window_open {
+                if let Ok(mut slot) = self.script_prompt.lock() {
+                    *slot = None;
+                }
+            }
+
+            if confirmed {
+                self.run_install_script(script);
+            } else if cancelled || !window_open {
+                script.discard();
+                if let Some(progress) = self.app_state.install_progress.iter_mut().find(|p| {
+                    p.tool_id == script.tool_id && matches!(p.operation, InstallOperation::Install)
+                }) {
+                    progress.status = ProgressStatus::Failed;
+                    progress.message = "Installation cancelled: script not confirmed".to_string();
+                    progress.timestamp = Instant::now();
+                }
+            }
+        }
+
         // Tool editor window
         let mut show_tool_editor = self.app_state.show_tool_editor;
         if show_tool_editor {
@@ -3207,6 +4421,11 @@ impl eframe::App for CLIvergeApp {
                     self.render_settings(ui);
                 });
             }
+            AppView::History => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    self.render_history(ui);
+                });
+            }
             AppView::About => {
                 egui::CentralPanel::default().show(ctx, |ui| {
                     self.render_about(ui);
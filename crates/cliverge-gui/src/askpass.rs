@@ -0,0 +1,79 @@
+//! Password dialog shown when sudo runs CLIverge as its askpass program
+//!
+//! sudo starts `cliverge --askpass <prompt>` and reads the password from stdout;
+//! a non-zero exit tells it the user cancelled.
+
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+
+// 类型别名以减少复杂度警告
+type PasswordSlot = Arc<Mutex<Option<String>>>;
+
+struct AskpassApp {
+    prompt: String,
+    password: String,
+    result: PasswordSlot,
+}
+
+impl eframe::App for AskpassApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("🔒 Administrator password required");
+            ui.label("CLIverge is running a command with sudo.");
+            ui.add_space(6.0);
+            ui.label(&self.prompt);
+
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.password)
+                    .password(true)
+                    .desired_width(f32::INFINITY),
+            );
+            field.request_focus();
+            let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button("OK").clicked() || submitted {
+                    *self.result.lock().unwrap() = Some(std::mem::take(&mut self.password));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+        });
+    }
+}
+
+/// Ask for the password and print it for sudo; exits with status 1 when cancelled
+pub fn run(prompt: Option<String>) -> Result<(), eframe::Error> {
+    let result: PasswordSlot = Arc::new(Mutex::new(None));
+    let app = AskpassApp {
+        prompt: prompt.unwrap_or_else(|| "Password:".to_string()),
+        password: String::new(),
+        result: Arc::clone(&result),
+    };
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([380.0, 150.0])
+            .with_resizable(false)
+            .with_always_on_top()
+            .with_title("CLIverge - Authentication"),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "CLIverge - Authentication",
+        options,
+        Box::new(|_cc| Box::new(app)),
+    )?;
+
+    let password = result.lock().unwrap().take();
+    match password {
+        Some(password) => {
+            println!("{password}");
+            Ok(())
+        }
+        None => std::process::exit(1),
+    }
+}
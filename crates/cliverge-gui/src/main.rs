@@ -2,6 +2,8 @@
 #![cfg_attr(windows, windows_subsystem = "windows")]
 
 mod app;
+#[cfg(unix)]
+mod askpass;
 
 use app::CLIvergeApp;
 use eframe::egui;
@@ -67,6 +69,15 @@ fn load_icon() -> IconData {
 }
 
 fn main() -> Result<(), eframe::Error> {
+    // sudo 通过 SUDO_ASKPASS 调用时只显示密码对话框
+    #[cfg(unix)]
+    {
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() == Some(cliverge_core::ASKPASS_FLAG) {
+            return askpass::run(args.next());
+        }
+    }
+
     // Simplified logging - only for errors in release builds
     #[cfg(debug_assertions)]
     {